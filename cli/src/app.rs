@@ -1,5 +1,5 @@
-use crate::{commands::ClapApp, error::WKCliError, logger};
-use clap::Parser;
+use crate::{commands::ClapApp, config::TelemetryConfig, error::WKCliError, logger, wukong_client};
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
 
 pub struct App {
     pub cli: ClapApp,
@@ -7,14 +7,35 @@ pub struct App {
 
 impl App {
     pub fn new() -> Result<Self, WKCliError> {
-        let cli = ClapApp::parse();
+        let matches = ClapApp::command().get_matches();
+        let cli = ClapApp::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
 
-        // Rewrite this to open file on append mode:
         logger::Builder::new()
             .with_max_level(cli.verbose.log_level_filter())
             .with_report(cli.report)
+            .with_command(command_name(&matches))
             .init();
 
+        if cli.no_cache {
+            wukong_client::disable_cache();
+        }
+
+        let telemetry_config = TelemetryConfig::load_from_default_path();
+        wukong_telemetry::set_enabled(telemetry_config.enabled);
+        wukong_telemetry::set_exporter_config(telemetry_config.exporter_config());
+
         Ok(Self { cli })
     }
 }
+
+/// The subcommands of the command being run, e.g. `deployment list`.
+fn command_name(matches: &ArgMatches) -> Option<String> {
+    let mut names = Vec::new();
+    let mut matches = matches;
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        names.push(name);
+        matches = sub_matches;
+    }
+
+    (!names.is_empty()).then(|| names.join(" "))
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     fs::{create_dir_all, File},
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
 };
 
@@ -242,6 +242,10 @@ impl ApplicationConfigs {
         Ok(())
     }
 
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
     pub fn to_string(&self) -> Result<String, ApplicationConfigError> {
         let serialized =
             toml::to_string(self).map_err(ApplicationConfigError::SerializeTomlError)?;
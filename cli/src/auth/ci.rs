@@ -0,0 +1,309 @@
+//! Non-interactive credentials for CI pipelines, read from the environment.
+//!
+//! - `WUKONG_TOKEN`: a token used as-is for the Wukong API.
+//! - `WUKONG_CLIENT_ID` and `WUKONG_CLIENT_SECRET`: an Okta service app, exchanged for an
+//!   access token with the client credentials flow. The requested scopes and the token
+//!   endpoint can be changed with `WUKONG_CLIENT_SCOPES` and `WUKONG_CLIENT_TOKEN_URL`.
+//! - `WUKONG_VAULT_TOKEN`: a Vault (Bunker) token.
+//! - `WUKONG_GOOGLE_CREDENTIALS`: the path to a Google Cloud service account JSON key.
+//!
+//! When one of them is set, the matching interactive login is skipped, and the tokens are
+//! never written to the config file or the credential store.
+
+use chrono::{DateTime, Duration, Utc};
+use log::debug;
+use serde::Deserialize;
+
+use crate::{
+    auth::jwt_claim,
+    error::{AuthError, WKCliError},
+};
+
+pub const TOKEN_ENV: &str = "WUKONG_TOKEN";
+pub const CLIENT_ID_ENV: &str = "WUKONG_CLIENT_ID";
+pub const CLIENT_SECRET_ENV: &str = "WUKONG_CLIENT_SECRET";
+pub const CLIENT_SCOPES_ENV: &str = "WUKONG_CLIENT_SCOPES";
+pub const CLIENT_TOKEN_URL_ENV: &str = "WUKONG_CLIENT_TOKEN_URL";
+pub const VAULT_TOKEN_ENV: &str = "WUKONG_VAULT_TOKEN";
+pub const GOOGLE_CREDENTIALS_ENV: &str = "WUKONG_GOOGLE_CREDENTIALS";
+
+const DEFAULT_CLIENT_TOKEN_URL: &str = "https://mindvalley.okta.com/oauth2/default/v1/token";
+const EXPIRY_REMAINING_TIME_IN_MINS: i64 = 5;
+
+/// The credentials used for the Wukong API instead of the Okta login.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiCredentials {
+    Token(String),
+    ClientCredentials {
+        client_id: String,
+        client_secret: String,
+        scopes: Vec<String>,
+        token_url: String,
+    },
+}
+
+impl ApiCredentials {
+    /// `WUKONG_TOKEN` takes precedence over the client credentials.
+    pub fn from_env() -> Option<Self> {
+        Self::from_lookup(env_var)
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        if let Some(token) = lookup(TOKEN_ENV) {
+            return Some(Self::Token(token));
+        }
+
+        let client_id = lookup(CLIENT_ID_ENV)?;
+        let client_secret = lookup(CLIENT_SECRET_ENV)?;
+
+        Some(Self::ClientCredentials {
+            client_id,
+            client_secret,
+            scopes: lookup(CLIENT_SCOPES_ENV)
+                .map(|scopes| scopes.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+            token_url: lookup(CLIENT_TOKEN_URL_ENV)
+                .unwrap_or_else(|| DEFAULT_CLIENT_TOKEN_URL.to_string()),
+        })
+    }
+
+    /// Who the requests are made as, for telemetry.
+    pub fn subject(&self) -> String {
+        match self {
+            Self::Token(token) => jwt_claim(token, "sub").unwrap_or_else(|| "ci".to_string()),
+            Self::ClientCredentials { client_id, .. } => client_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientCredentialsResponse {
+    access_token: String,
+    expires_in: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    token: String,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Hands out access tokens for [ApiCredentials], requesting a new one when the
+/// client credentials token is about to expire.
+#[derive(Debug, Clone)]
+pub struct CiAuth {
+    credentials: ApiCredentials,
+    cached_token: Option<CachedToken>,
+}
+
+impl CiAuth {
+    pub fn from_env() -> Option<Self> {
+        ApiCredentials::from_env().map(Self::new)
+    }
+
+    pub fn new(credentials: ApiCredentials) -> Self {
+        Self {
+            credentials,
+            cached_token: None,
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        self.credentials.subject()
+    }
+
+    pub async fn access_token(&mut self) -> Result<String, WKCliError> {
+        if let Some(cached_token) = &self.cached_token {
+            let still_valid = match cached_token.expires_at {
+                Some(expires_at) => {
+                    expires_at - Utc::now() > Duration::minutes(EXPIRY_REMAINING_TIME_IN_MINS)
+                }
+                None => true,
+            };
+            if still_valid {
+                return Ok(cached_token.token.clone());
+            }
+        }
+
+        let cached_token = match &self.credentials {
+            ApiCredentials::Token(token) => CachedToken {
+                token: token.clone(),
+                expires_at: None,
+            },
+            ApiCredentials::ClientCredentials {
+                client_id,
+                client_secret,
+                scopes,
+                token_url,
+            } => {
+                debug!("Requesting an access token with the client credentials flow.");
+                let response =
+                    request_client_credentials_token(token_url, client_id, client_secret, scopes)
+                        .await?;
+
+                CachedToken {
+                    token: response.access_token,
+                    expires_at: response
+                        .expires_in
+                        .map(|expires_in| Utc::now() + Duration::seconds(expires_in)),
+                }
+            }
+        };
+
+        self.cached_token = Some(cached_token.clone());
+        Ok(cached_token.token)
+    }
+}
+
+async fn request_client_credentials_token(
+    token_url: &str,
+    client_id: &str,
+    client_secret: &str,
+    scopes: &[String],
+) -> Result<ClientCredentialsResponse, WKCliError> {
+    let mut form = vec![("grant_type", "client_credentials".to_string())];
+    if !scopes.is_empty() {
+        form.push(("scope", scopes.join(" ")));
+    }
+
+    let response = reqwest::Client::new()
+        .post(token_url)
+        .basic_auth(client_id, Some(client_secret))
+        .form(&form)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let message = response.text().await?;
+        return Err(AuthError::ClientCredentialsError { message }.into());
+    }
+
+    Ok(response.json::<ClientCredentialsResponse>().await?)
+}
+
+/// The Vault (Bunker) token from `WUKONG_VAULT_TOKEN`.
+pub fn vault_token() -> Option<String> {
+    env_var(VAULT_TOKEN_ENV)
+}
+
+/// The path of the Google Cloud service account key from `WUKONG_GOOGLE_CREDENTIALS`.
+pub fn google_credentials_path() -> Option<String> {
+    env_var(GOOGLE_CREDENTIALS_ENV)
+}
+
+/// Whether any of the CI credentials is set, in which case the CLI can run
+/// without a config file.
+pub fn has_credentials() -> bool {
+    ApiCredentials::from_env().is_some()
+        || vault_token().is_some()
+        || google_credentials_path().is_some()
+}
+
+// an empty value is treated as unset, which is what a missing secret expands to in most CI
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use httpmock::prelude::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn token_takes_precedence_over_client_credentials() {
+        let credentials = ApiCredentials::from_lookup(lookup(&[
+            (TOKEN_ENV, "the-token"),
+            (CLIENT_ID_ENV, "client-id"),
+            (CLIENT_SECRET_ENV, "client-secret"),
+        ]));
+
+        assert_eq!(
+            credentials,
+            Some(ApiCredentials::Token("the-token".to_string()))
+        );
+    }
+
+    #[test]
+    fn client_credentials_need_both_id_and_secret() {
+        assert_eq!(
+            ApiCredentials::from_lookup(lookup(&[(CLIENT_ID_ENV, "client-id")])),
+            None
+        );
+
+        let credentials = ApiCredentials::from_lookup(lookup(&[
+            (CLIENT_ID_ENV, "client-id"),
+            (CLIENT_SECRET_ENV, "client-secret"),
+            (CLIENT_SCOPES_ENV, "wukong.read  wukong.deploy"),
+        ]));
+
+        assert_eq!(
+            credentials,
+            Some(ApiCredentials::ClientCredentials {
+                client_id: "client-id".to_string(),
+                client_secret: "client-secret".to_string(),
+                scopes: vec!["wukong.read".to_string(), "wukong.deploy".to_string()],
+                token_url: DEFAULT_CLIENT_TOKEN_URL.to_string(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn client_credentials_token_is_requested_once_while_valid() {
+        let server = MockServer::start();
+
+        let mock_server = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/token")
+                .header_exists("authorization")
+                .body_contains("grant_type=client_credentials")
+                .body_contains("scope=wukong.read");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"access_token": "ci-access-token", "token_type": "Bearer", "expires_in": 3600}"#);
+        });
+
+        let mut ci_auth = CiAuth::new(ApiCredentials::ClientCredentials {
+            client_id: "client-id".to_string(),
+            client_secret: "client-secret".to_string(),
+            scopes: vec!["wukong.read".to_string()],
+            token_url: server.url("/v1/token"),
+        });
+
+        assert_eq!(ci_auth.access_token().await.unwrap(), "ci-access-token");
+        assert_eq!(ci_auth.access_token().await.unwrap(), "ci-access-token");
+        mock_server.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn client_credentials_error_is_reported() {
+        let server = MockServer::start();
+
+        server.mock(|when, then| {
+            when.method(POST).path("/v1/token");
+            then.status(401).body(r#"{"error": "invalid_client"}"#);
+        });
+
+        let mut ci_auth = CiAuth::new(ApiCredentials::ClientCredentials {
+            client_id: "client-id".to_string(),
+            client_secret: "wrong-secret".to_string(),
+            scopes: vec![],
+            token_url: server.url("/v1/token"),
+        });
+
+        assert!(matches!(
+            ci_auth.access_token().await,
+            Err(WKCliError::AuthError(
+                AuthError::ClientCredentialsError { .. }
+            ))
+        ));
+    }
+}
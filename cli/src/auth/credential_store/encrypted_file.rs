@@ -0,0 +1,257 @@
+use super::CredentialStore;
+use crate::{error::CredentialStoreError, utils::fs::write_private_file};
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use once_cell::sync::Lazy;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::create_dir_all,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+const CREDENTIALS_FILE: &str = "credentials.enc";
+const KEY_FILE: &str = "credentials.key";
+
+/// The keys derived from the passphrase, by salt. The salt of the file is kept on
+/// every write, so the argon2 derivation runs once per process.
+static DERIVED_KEYS: Lazy<Mutex<HashMap<Vec<u8>, Key>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Stores the credentials in a ChaCha20-Poly1305 encrypted file.
+///
+/// The encryption key is derived from `WUKONG_CREDENTIAL_PASSPHRASE` when it is
+/// set, and then the credentials are encrypted at rest.
+///
+/// Otherwise a random key is generated into `credentials.key`, next to the
+/// credentials file. Anyone who can read the credentials file can read the key
+/// as well, so this only obfuscates the tokens: like the credentials file, the
+/// key is only readable by the current user, and that is the actual protection.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key_path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u8,
+    /// Only set when the key is derived from a passphrase.
+    salt: Option<String>,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl EncryptedFileStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(CREDENTIALS_FILE),
+            key_path: dir.join(KEY_FILE),
+        }
+    }
+
+    /// The credentials, and the salt of the file when the key is derived from a passphrase.
+    fn read_all(&self) -> Result<(HashMap<String, String>, Option<Vec<u8>>), CredentialStoreError> {
+        if !self.path.exists() {
+            return Ok((HashMap::new(), None));
+        }
+
+        let content = std::fs::read_to_string(&self.path)?;
+        let file: EncryptedFile =
+            serde_json::from_str(&content).map_err(|_| self.decryption_error())?;
+
+        let salt = file.salt.as_deref().map(decode).transpose()?;
+        let key = self.key(salt.as_deref(), false)?;
+        let nonce = decode(&file.nonce)?;
+        let ciphertext = decode(&file.ciphertext)?;
+
+        let plaintext = ChaCha20Poly1305::new(&key)
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| self.decryption_error())?;
+
+        let values = serde_json::from_slice(&plaintext).map_err(|_| self.decryption_error())?;
+
+        Ok((values, salt))
+    }
+
+    fn write_all(
+        &self,
+        values: &HashMap<String, String>,
+        salt: Option<Vec<u8>>,
+    ) -> Result<(), CredentialStoreError> {
+        if let Some(dir) = self.path.parent() {
+            create_dir_all(dir)?;
+        }
+
+        // the nonce is new on every write, the salt is kept so the derived key is reused
+        let salt = passphrase().map(|_| {
+            salt.unwrap_or_else(|| {
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                salt.to_vec()
+            })
+        });
+        let key = self.key(salt.as_deref(), true)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let plaintext = serde_json::to_vec(values).expect("credentials are always serializable");
+
+        let ciphertext = ChaCha20Poly1305::new(&key)
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|_| CredentialStoreError::Encryption)?;
+
+        let file = EncryptedFile {
+            version: 1,
+            salt: salt.map(|salt| general_purpose::STANDARD.encode(salt)),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        };
+
+        write_private_file(
+            &self.path,
+            serde_json::to_string_pretty(&file)
+                .expect("credentials file is always serializable")
+                .as_bytes(),
+        )?;
+
+        Ok(())
+    }
+
+    fn key(&self, salt: Option<&[u8]>, create: bool) -> Result<Key, CredentialStoreError> {
+        if let Some(passphrase) = passphrase() {
+            let salt = salt.ok_or_else(|| self.decryption_error())?;
+            let mut derived_keys = DERIVED_KEYS.lock().expect("derived keys lock poisoned");
+            if let Some(key) = derived_keys.get(salt) {
+                return Ok(*key);
+            }
+
+            let mut key = Key::default();
+            argon2::Argon2::default()
+                .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                .map_err(|_| CredentialStoreError::Encryption)?;
+            derived_keys.insert(salt.to_vec(), key);
+
+            return Ok(key);
+        }
+
+        if self.key_path.exists() {
+            let key = decode(std::fs::read_to_string(&self.key_path)?.trim())?;
+            if key.len() != 32 {
+                return Err(self.decryption_error());
+            }
+            return Ok(Key::clone_from_slice(&key));
+        }
+
+        if !create {
+            return Err(self.decryption_error());
+        }
+
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        write_private_file(
+            &self.key_path,
+            general_purpose::STANDARD.encode(key).as_bytes(),
+        )?;
+
+        Ok(key)
+    }
+
+    fn decryption_error(&self) -> CredentialStoreError {
+        CredentialStoreError::Decryption {
+            path: self.path.to_string_lossy().to_string(),
+        }
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn name(&self) -> &'static str {
+        "encrypted file"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, CredentialStoreError> {
+        Ok(self.read_all()?.0.remove(key))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), CredentialStoreError> {
+        let (mut values, salt) = self.read_all()?;
+        values.insert(key.to_string(), value.to_string());
+        self.write_all(&values, salt)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), CredentialStoreError> {
+        let (mut values, salt) = self.read_all()?;
+        if values.remove(key).is_some() {
+            self.write_all(&values, salt)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn passphrase() -> Option<String> {
+    std::env::var("WUKONG_CREDENTIAL_PASSPHRASE")
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, CredentialStoreError> {
+    general_purpose::STANDARD
+        .decode(value)
+        .map_err(|_| CredentialStoreError::Encryption)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_get_and_delete() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let store = EncryptedFileStore::new(temp.path());
+
+        assert_eq!(store.get("okta.access_token").unwrap(), None);
+
+        store.set("okta.access_token", "access_token").unwrap();
+        store.set("vault.api_token", "api_token").unwrap();
+        assert_eq!(
+            store.get("okta.access_token").unwrap(),
+            Some("access_token".to_string())
+        );
+
+        // the file on disk must not contain the plaintext token
+        let content = std::fs::read_to_string(temp.path().join(CREDENTIALS_FILE)).unwrap();
+        assert!(!content.contains("access_token\""));
+        assert!(!content.contains("api_token\""));
+
+        store.delete("okta.access_token").unwrap();
+        assert_eq!(store.get("okta.access_token").unwrap(), None);
+        assert_eq!(
+            store.get("vault.api_token").unwrap(),
+            Some("api_token".to_string())
+        );
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_fails_with_a_different_key() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let store = EncryptedFileStore::new(temp.path());
+        store.set("okta.access_token", "access_token").unwrap();
+
+        let other_key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        std::fs::write(
+            temp.path().join(KEY_FILE),
+            general_purpose::STANDARD.encode(other_key),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            store.get("okta.access_token"),
+            Err(CredentialStoreError::Decryption { .. })
+        ));
+
+        temp.close().unwrap();
+    }
+}
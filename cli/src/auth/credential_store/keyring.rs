@@ -0,0 +1,57 @@
+use super::CredentialStore;
+use crate::error::CredentialStoreError;
+
+#[cfg(feature = "prod")]
+const SERVICE: &str = "wukong";
+#[cfg(not(feature = "prod"))]
+const SERVICE: &str = "wukong-dev";
+
+/// Stores each credential as a separate entry in the OS keyring
+/// (macOS Keychain, Windows Credential Manager or the Linux Secret Service).
+#[derive(Default)]
+pub struct KeyringStore;
+
+impl KeyringStore {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether the keyring can be reached, e.g. there is a Secret Service running.
+    pub fn is_available(&self) -> bool {
+        match ::keyring::Entry::new(SERVICE, "availability-check") {
+            Ok(entry) => matches!(entry.get_password(), Ok(_) | Err(::keyring::Error::NoEntry)),
+            Err(_) => false,
+        }
+    }
+
+    fn entry(key: &str) -> Result<::keyring::Entry, CredentialStoreError> {
+        ::keyring::Entry::new(SERVICE, key).map_err(CredentialStoreError::Keyring)
+    }
+}
+
+impl CredentialStore for KeyringStore {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, CredentialStoreError> {
+        match Self::entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(::keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(CredentialStoreError::Keyring(err)),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), CredentialStoreError> {
+        Self::entry(key)?
+            .set_password(value)
+            .map_err(CredentialStoreError::Keyring)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), CredentialStoreError> {
+        match Self::entry(key)?.delete_password() {
+            Ok(_) | Err(::keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(CredentialStoreError::Keyring(err)),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use super::CredentialStore;
+use crate::error::CredentialStoreError;
+use std::{cell::RefCell, collections::HashMap};
+
+/// An in-memory store, for tests.
+#[derive(Default)]
+pub struct MemoryStore {
+    values: RefCell<HashMap<String, String>>,
+}
+
+impl CredentialStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, CredentialStoreError> {
+        Ok(self.values.borrow().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), CredentialStoreError> {
+        self.values
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), CredentialStoreError> {
+        self.values.borrow_mut().remove(key);
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! Storage for the auth tokens, so they are not written to `config.toml` in plaintext.
+//!
+//! The OS keyring is used when it is available. On machines without one
+//! (e.g. headless Linux without a Secret Service), the tokens go to an
+//! encrypted file next to the config file instead. Unless
+//! `WUKONG_CREDENTIAL_PASSPHRASE` is set, the key of that file is kept next to
+//! it, so the file is only obfuscated, see [EncryptedFileStore].
+
+mod encrypted_file;
+mod keyring;
+#[cfg(test)]
+mod memory;
+
+pub use self::encrypted_file::EncryptedFileStore;
+pub use self::keyring::KeyringStore;
+#[cfg(test)]
+pub use self::memory::MemoryStore;
+
+use crate::error::CredentialStoreError;
+use log::debug;
+use std::path::Path;
+
+pub const OKTA_ID_TOKEN: &str = "okta.id_token";
+pub const OKTA_ACCESS_TOKEN: &str = "okta.access_token";
+pub const OKTA_REFRESH_TOKEN: &str = "okta.refresh_token";
+pub const VAULT_API_TOKEN: &str = "vault.api_token";
+pub const GOOGLE_CLOUD_ACCESS_TOKEN: &str = "google_cloud.access_token";
+pub const GOOGLE_CLOUD_REFRESH_TOKEN: &str = "google_cloud.refresh_token";
+pub const GOOGLE_CLOUD_ID_TOKEN: &str = "google_cloud.id_token";
+
+pub const OKTA_KEYS: &[&str] = &[OKTA_ID_TOKEN, OKTA_ACCESS_TOKEN, OKTA_REFRESH_TOKEN];
+pub const VAULT_KEYS: &[&str] = &[VAULT_API_TOKEN];
+pub const GOOGLE_CLOUD_KEYS: &[&str] = &[
+    GOOGLE_CLOUD_ACCESS_TOKEN,
+    GOOGLE_CLOUD_REFRESH_TOKEN,
+    GOOGLE_CLOUD_ID_TOKEN,
+];
+
+pub trait CredentialStore {
+    /// A short name of the backend, shown to the user.
+    fn name(&self) -> &'static str;
+    fn get(&self, key: &str) -> Result<Option<String>, CredentialStoreError>;
+    fn set(&self, key: &str, value: &str) -> Result<(), CredentialStoreError>;
+    fn delete(&self, key: &str) -> Result<(), CredentialStoreError>;
+}
+
+/// Pick the credential store for the config file at `config_path`.
+///
+/// The backend can be forced with `WUKONG_CREDENTIAL_STORE=keyring|file`.
+/// Otherwise the keyring is preferred, falling back to the encrypted file when
+/// the keyring is not reachable. Dev builds default to the encrypted file, so
+/// running the tests never touches the developer's keyring.
+pub fn for_config_path(config_path: &str) -> Box<dyn CredentialStore> {
+    let file_store = || -> Box<dyn CredentialStore> {
+        let config_dir = Path::new(config_path)
+            .parent()
+            .unwrap_or_else(|| Path::new("."));
+        Box::new(EncryptedFileStore::new(config_dir))
+    };
+
+    match std::env::var("WUKONG_CREDENTIAL_STORE").as_deref() {
+        Ok("keyring") => return Box::new(KeyringStore::new()),
+        Ok("file") => return file_store(),
+        _ => {}
+    }
+
+    if cfg!(feature = "prod") {
+        let keyring = KeyringStore::new();
+        if keyring.is_available() {
+            return Box::new(keyring);
+        }
+        debug!("OS keyring is not available, falling back to the encrypted credentials file.");
+    }
+
+    file_store()
+}
@@ -1,13 +1,21 @@
-use crate::config::Config;
+use crate::{
+    auth::{ci, jwt_claim, LoginFlow},
+    config::Config,
+    error::{AuthError, WKCliError},
+};
 use async_trait::async_trait;
+use crossterm::style::Stylize;
 use serde::{Deserialize, Serialize};
 use std::{future::Future, pin::Pin};
 use time::{format_description, OffsetDateTime};
+use tokio::io::AsyncBufReadExt;
+use url::Url;
 use yup_oauth2::{
     authenticator_delegate::{DefaultInstalledFlowDelegate, InstalledFlowDelegate},
     hyper, hyper_rustls,
     storage::{TokenInfo, TokenStorage},
     ApplicationSecret, InstalledFlowAuthenticator, InstalledFlowReturnMethod,
+    ServiceAccountAuthenticator,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +57,74 @@ impl InstalledFlowDelegate for InstalledFlowBrowserDelegate {
     }
 }
 
+/// async function to be pinned by the `present_user_url` method of the trait
+/// the user opens the URL on another device, and pastes back the URL the browser is
+/// redirected to, as nothing listens on this machine for the redirect.
+async fn pasted_user_url(url: &str) -> Result<String, String> {
+    let url = format!("{}&prompt=consent", url);
+    println!(
+        "To login, open the following URL in a browser on any device:\n\n\t{}\n",
+        url.as_str().underlined()
+    );
+    println!("Once you have allowed the access, the browser is redirected to a page on 127.0.0.1 which does not load. Copy the URL of this page from the address bar and paste it here:");
+
+    let mut pasted = String::new();
+    tokio::io::BufReader::new(tokio::io::stdin())
+        .read_line(&mut pasted)
+        .await
+        .map_err(|error| format!("couldn't read the URL: {error}"))?;
+
+    auth_code_from_pasted(pasted.trim())
+}
+
+/// The authorization code in the redirected URL pasted by the user, or the code itself if
+/// only the code is pasted.
+fn auth_code_from_pasted(pasted: &str) -> Result<String, String> {
+    if pasted.is_empty() {
+        return Err("no URL pasted".to_string());
+    }
+
+    let url = match Url::parse(pasted) {
+        Ok(url) => url,
+        Err(_) => return Ok(pasted.to_string()),
+    };
+
+    let query_value = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    match (query_value("code"), query_value("error")) {
+        (Some(code), _) => Ok(code),
+        (None, Some(error)) => Err(format!("the login is denied: {error}")),
+        (None, None) => Err(format!("there is no code in the URL {pasted}")),
+    }
+}
+
+/// The delegate of the login without a browser on this machine, similar to
+/// `gcloud auth login --no-browser`. Google only allows the device authorization grant
+/// for the "TVs and Limited Input devices" clients, and not with the scopes we need,
+/// so the authorization code is pasted back instead.
+#[derive(Copy, Clone)]
+struct InstalledFlowPasteDelegate;
+
+impl InstalledFlowDelegate for InstalledFlowPasteDelegate {
+    /// a loopback address, as for the browser flow, the only kind of redirect URI
+    /// accepted for an installed app
+    fn redirect_uri(&self) -> Option<&str> {
+        Some(PASTE_REDIRECT_URI)
+    }
+
+    fn present_user_url<'a>(
+        &'a self,
+        url: &'a str,
+        _need_code: bool,
+    ) -> Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>> {
+        Box::pin(pasted_user_url(url))
+    }
+}
+
 // Injected at compile time from the GOOGLE_CLIENT_ID / GOOGLE_CLIENT_SECRET
 // secrets in CI (same pattern as WUKONG_HONEYCOMB_API_KEY in the telemetry crate).
 // Prod builds fail if the env vars are missing or empty; dev builds fall back
@@ -84,13 +160,23 @@ const _: () = assert!(
 const TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
 const AUTH_URI: &str = "https://accounts.google.com/o/oauth2/auth";
 const REDIRECT_URI: &str = "http://127.0.0.1/8855";
+const PASTE_REDIRECT_URI: &str = "http://127.0.0.1:8855";
 const AUTH_PROVIDER_X509_CERT_URL: &str = "https://www.googleapis.com/oauth2/v1/certs";
+const REVOKE_URI: &str = "https://oauth2.googleapis.com/revoke";
+const SCOPES: [&str; 4] = [
+    "https://www.googleapis.com/auth/logging.read",
+    "https://www.googleapis.com/auth/cloud-platform.read-only",
+    "https://www.googleapis.com/auth/sqlservice.admin",
+    "https://www.googleapis.com/auth/monitoring.read",
+];
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct GoogleCloudConfig {
     /// used when authorizing calls to oauth2 enabled services.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub access_token: String,
     /// used to refresh an expired access_token.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub refresh_token: String,
     /// The time when the token expires.
     pub expiry_time: String,
@@ -98,6 +184,7 @@ pub struct GoogleCloudConfig {
     /// used to obtain the access token.
     /// Specifically Google API:s include this if the additional scopes "email" and/or "profile"
     /// are used. In that case the content is an JWT token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id_token: Option<String>,
 }
 
@@ -143,7 +230,22 @@ impl TokenStorage for ConfigTokenStore {
     }
 }
 
-pub async fn get_token_or_login(config: Option<Config>) -> String {
+pub async fn get_token_or_login(config: Option<Config>) -> Result<String, WKCliError> {
+    get_token_or_login_with(config, LoginFlow::Browser).await
+}
+
+/// Same as [get_token_or_login], using the given flow if the user has to login.
+///
+/// With a service account key in `WUKONG_GOOGLE_CREDENTIALS`, the token is requested
+/// for the service account instead, and the user is never asked to login.
+pub async fn get_token_or_login_with(
+    config: Option<Config>,
+    flow: LoginFlow,
+) -> Result<String, WKCliError> {
+    if let Some(key_path) = ci::google_credentials_path() {
+        return get_service_account_token(&key_path).await;
+    }
+
     let secret = ApplicationSecret {
         client_id: GOOGLE_CLIENT_ID.to_string(),
         client_secret: GOOGLE_CLIENT_SECRET.to_string(),
@@ -156,46 +258,118 @@ pub async fn get_token_or_login(config: Option<Config>) -> String {
         client_x509_cert_url: None,
     };
 
-    let client = hyper::Client::builder().build(
-        hyper_rustls::HttpsConnectorBuilder::new()
-            .with_native_roots()
-            .https_only()
-            .enable_http1()
-            .enable_http2()
-            .build(),
-    );
+    let client = https_client();
 
     let config = match config {
         Some(config) => config,
         None => Config::load_from_default_path().expect("Unable to load config"),
     };
 
-    let authenticator = InstalledFlowAuthenticator::with_client(
-        secret,
-        InstalledFlowReturnMethod::HTTPPortRedirect(8855),
-        client,
-    )
-    .with_storage(Box::new(ConfigTokenStore { config }))
-    .flow_delegate(Box::new(InstalledFlowBrowserDelegate))
-    .build()
-    .await
+    let storage = Box::new(ConfigTokenStore { config });
+    let authenticator = match flow {
+        LoginFlow::Browser => {
+            InstalledFlowAuthenticator::with_client(
+                secret,
+                InstalledFlowReturnMethod::HTTPPortRedirect(8855),
+                client,
+            )
+            .with_storage(storage)
+            .flow_delegate(Box::new(InstalledFlowBrowserDelegate))
+            .build()
+            .await
+        }
+        LoginFlow::Device => {
+            InstalledFlowAuthenticator::with_client(
+                secret,
+                InstalledFlowReturnMethod::Interactive,
+                client,
+            )
+            .with_storage(storage)
+            .flow_delegate(Box::new(InstalledFlowPasteDelegate))
+            .build()
+            .await
+        }
+    }
     .unwrap();
 
-    authenticator
-        .token(&[
-            "https://www.googleapis.com/auth/logging.read",
-            "https://www.googleapis.com/auth/cloud-platform.read-only",
-            "https://www.googleapis.com/auth/sqlservice.admin",
-            "https://www.googleapis.com/auth/monitoring.read",
-        ])
+    Ok(authenticator
+        .token(&SCOPES)
         .await
         .unwrap()
         .token()
         .unwrap()
-        .to_string()
+        .to_string())
+}
+
+/// The token of the service account, which is kept in memory only.
+async fn get_service_account_token(key_path: &str) -> Result<String, WKCliError> {
+    let service_account_error = |message: String| AuthError::GoogleServiceAccountError {
+        path: key_path.to_string(),
+        message,
+    };
+
+    let key = yup_oauth2::read_service_account_key(key_path)
+        .await
+        .map_err(|error| service_account_error(error.to_string()))?;
+
+    let authenticator = ServiceAccountAuthenticator::with_client(key, https_client())
+        .build()
+        .await
+        .map_err(|error| service_account_error(error.to_string()))?;
+
+    let token = authenticator
+        .token(&SCOPES)
+        .await
+        .map_err(|error| service_account_error(error.to_string()))?;
+
+    Ok(token
+        .token()
+        .ok_or_else(|| service_account_error("no access token in the response".to_string()))?
+        .to_string())
+}
+
+fn https_client(
+) -> hyper::Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>, hyper::Body> {
+    hyper::Client::builder().build(
+        hyper_rustls::HttpsConnectorBuilder::new()
+            .with_native_roots()
+            .https_only()
+            .enable_http1()
+            .enable_http2()
+            .build(),
+    )
+}
+
+/// Revoke the token, which also revokes the other tokens of the same grant.
+pub async fn revoke_token(token: &str) -> Result<(), WKCliError> {
+    let response = reqwest::Client::new()
+        .post(REVOKE_URI)
+        .form(&[("token", token)])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let message = response.text().await?;
+        return Err(AuthError::RevokeTokenError {
+            provider: "Google Cloud",
+            message,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// The email of the account, from the claims of the ID token.
+pub fn id_token_email(id_token: &str) -> Option<String> {
+    jwt_claim(id_token, "email")
 }
 
 pub async fn get_access_token() -> Option<String> {
+    if ci::google_credentials_path().is_some() {
+        return get_token_or_login(None).await.ok();
+    }
+
     let config = match Config::load_from_default_path() {
         Ok(config) => config,
         Err(_) => return None,
@@ -204,8 +378,42 @@ pub async fn get_access_token() -> Option<String> {
     // Sometimes access token exist but is expired, so call get_token_or_login() to refresh it
     // before returning it.
     if config.auth.google_cloud.is_some() {
-        Some(get_token_or_login(None).await)
+        get_token_or_login(None).await.ok()
     } else {
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_auth_code_from_pasted() {
+        assert_eq!(
+            auth_code_from_pasted(
+                "http://127.0.0.1:8855/?state=xyz&code=4/0Ab_cD-eF&scope=https://www.googleapis.com/auth/logging.read"
+            )
+            .unwrap(),
+            "4/0Ab_cD-eF"
+        );
+        assert_eq!(
+            auth_code_from_pasted("http://127.0.0.1:8855/?code=4%2F0AbcD").unwrap(),
+            "4/0AbcD"
+        );
+        // only the code is pasted
+        assert_eq!(auth_code_from_pasted("4/0AbcD").unwrap(), "4/0AbcD");
+
+        assert!(auth_code_from_pasted("")
+            .unwrap_err()
+            .contains("no URL pasted"));
+        assert!(
+            auth_code_from_pasted("http://127.0.0.1:8855/?error=access_denied")
+                .unwrap_err()
+                .contains("access_denied")
+        );
+        assert!(auth_code_from_pasted("http://127.0.0.1:8855/")
+            .unwrap_err()
+            .contains("there is no code"));
+    }
+}
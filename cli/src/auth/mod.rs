@@ -1,3 +1,43 @@
+pub mod ci;
+pub mod credential_store;
 pub mod google_cloud;
 pub mod okta;
 pub mod vault;
+
+use base64::{engine::general_purpose, Engine as _};
+use crossterm::style::Stylize;
+
+/// How the user completes an interactive OAuth login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoginFlow {
+    /// Open a browser and receive the redirect on a local port.
+    #[default]
+    Browser,
+    /// Login with a browser on another device, for machines without one, e.g. over SSH:
+    /// the OAuth device authorization grant for Okta, and the authorization code pasted
+    /// back for Google, which only allows the device grant for limited input devices.
+    Device,
+}
+
+/// Show the user where to complete a device authorization login.
+pub fn print_device_code(verification_uri: &str, user_code: &str) {
+    println!(
+        "To login, open the following URL in a browser on any device:\n\n\t{}\n\nand enter the code: {}\n",
+        verification_uri.underlined(),
+        user_code.bold()
+    );
+}
+
+/// A string claim of a JWT, e.g. the `email` of an ID token.
+///
+/// The token is only decoded, not verified, so this is only for display.
+pub fn jwt_claim(token: &str, claim: &str) -> Option<String> {
+    let payload = token.split('.').nth(1)?;
+    let claims: serde_json::Value =
+        serde_json::from_slice(&general_purpose::URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+
+    claims
+        .get(claim)
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string())
+}
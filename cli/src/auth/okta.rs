@@ -1,4 +1,5 @@
 use crate::{
+    auth::{print_device_code, LoginFlow},
     config::{Config, OktaConfig},
     error::{AuthError, WKCliError},
     loader::new_spinner,
@@ -9,15 +10,18 @@ use chrono::{DateTime, Duration, Utc};
 use log::debug;
 use openidconnect::{
     core::{
-        CoreClient, CoreIdTokenClaims, CoreIdTokenVerifier, CoreProviderMetadata, CoreResponseType,
+        CoreClient, CoreDeviceAuthorizationResponse, CoreIdTokenClaims, CoreIdTokenVerifier,
+        CoreProviderMetadata, CoreResponseType, CoreRevocableToken, CoreTokenResponse,
     },
     reqwest::async_http_client,
     AccessToken, AccessTokenHash, AdditionalClaims, AuthenticationFlow, AuthorizationCode,
-    ClientId, CsrfToken, IntrospectionUrl, IssuerUrl, Nonce, OAuth2TokenResponse,
-    PkceCodeChallenge, RedirectUrl, RefreshToken, Scope, TokenIntrospectionResponse,
+    ClientId, CsrfToken, DeviceAuthorizationUrl, IntrospectionUrl, IssuerUrl, Nonce,
+    OAuth2TokenResponse, PkceCodeChallenge, RedirectUrl, RefreshToken, RevocationUrl, Scope,
+    TokenIntrospectionResponse,
 };
 use serde::{Deserialize, Serialize};
 use std::{
+    future::Future,
     io::{BufRead, BufReader, Write},
     net::TcpListener,
 };
@@ -78,7 +82,14 @@ pub fn need_tokens_refresh(config: &Config) -> Result<bool, WKCliError> {
     Ok(remaining_duration < EXPIRY_REMAINING_TIME_IN_MINS.minutes())
 }
 
-pub async fn login() -> Result<OktaAuth, WKCliError> {
+pub async fn login(flow: LoginFlow) -> Result<OktaAuth, WKCliError> {
+    match flow {
+        LoginFlow::Browser => login_with_browser().await,
+        LoginFlow::Device => login_with_device_code().await,
+    }
+}
+
+async fn login_with_browser() -> Result<OktaAuth, WKCliError> {
     let okta_client_id = ClientId::new(OKTA_CLIENT_ID.to_string());
 
     let issuer_url =
@@ -195,6 +206,96 @@ pub async fn login() -> Result<OktaAuth, WKCliError> {
             },
         })?;
 
+    okta_auth_from_token_response(&client, &okta_client_id, &token_response, Some(&nonce))
+}
+
+/// Login with the OAuth device authorization grant. The user opens the printed
+/// URL on any device, so it works without a browser or a local port on this machine.
+async fn login_with_device_code() -> Result<OktaAuth, WKCliError> {
+    let okta_client_id = ClientId::new(OKTA_CLIENT_ID.to_string());
+
+    let issuer_url =
+        IssuerUrl::new("https://mindvalley.okta.com".to_string()).expect("Invalid issuer URL");
+
+    // Fetch Okta's OpenID Connect discovery document.
+    let provider_metadata = CoreProviderMetadata::discover_async(issuer_url, async_http_client)
+        .await
+        .map_err(|_err| AuthError::OpenIDDiscoveryError)?;
+
+    let client =
+        CoreClient::from_provider_metadata(provider_metadata, okta_client_id.clone(), None)
+            .set_device_authorization_uri(
+                DeviceAuthorizationUrl::new(
+                    "https://mindvalley.okta.com/oauth2/v1/device/authorize".to_string(),
+                )
+                .expect("Invalid device authorization URL"),
+            );
+
+    let token_response = request_device_token(&client, tokio::time::sleep).await?;
+
+    okta_auth_from_token_response(&client, &okta_client_id, &token_response, None)
+}
+
+/// Request a token with the device authorization grant: print the URL and the code the
+/// user enters on another device, then poll the token endpoint, sleeping with `sleep_fn`
+/// between the polls, until the login is approved or denied.
+async fn request_device_token<S, SF>(
+    client: &CoreClient,
+    sleep_fn: S,
+) -> Result<CoreTokenResponse, WKCliError>
+where
+    S: Fn(std::time::Duration) -> SF,
+    SF: Future<Output = ()>,
+{
+    let details: CoreDeviceAuthorizationResponse = client
+        .exchange_device_code()
+        .map_err(|_err| AuthError::OpenIDConnectError {
+            message: "Device authorization endpoint is not configured".to_string(),
+        })?
+        .add_scope(Scope::new("email".to_string()))
+        .add_scope(Scope::new("profile".to_string()))
+        .add_scope(Scope::new("offline_access".to_string()))
+        .request_async(async_http_client)
+        .await
+        .map_err(|_err| AuthError::OpenIDConnectError {
+            message: "Failed to contact device authorization endpoint".to_string(),
+        })?;
+
+    print_device_code(
+        details.verification_uri().as_str(),
+        details.user_code().secret(),
+    );
+    let spinner = new_spinner().with_message("Waiting for login");
+
+    // polls the token endpoint at the interval given by Okta until the user
+    // approves or denies the login, or the device code expires
+    let token_response = client
+        .exchange_device_access_token(&details)
+        .request_async(async_http_client, sleep_fn, None)
+        .await
+        .map_err(|err| match err {
+            openidconnect::RequestTokenError::ServerResponse(error) => {
+                AuthError::OpenIDConnectError {
+                    message: error.to_string(),
+                }
+            }
+            _ => AuthError::OpenIDConnectError {
+                message: "Failed to contact token endpoint".to_string(),
+            },
+        })?;
+
+    spinner.finish_and_clear();
+
+    Ok(token_response)
+}
+
+/// Turn the token response of a login into [OktaAuth], after verifying the ID token.
+fn okta_auth_from_token_response(
+    client: &CoreClient,
+    okta_client_id: &ClientId,
+    token_response: &CoreTokenResponse,
+    nonce: Option<&Nonce>,
+) -> Result<OktaAuth, WKCliError> {
     let id_token_verifier: CoreIdTokenVerifier = client.id_token_verifier();
     let id_token = token_response
         .extra_fields()
@@ -205,19 +306,20 @@ pub async fn login() -> Result<OktaAuth, WKCliError> {
         .refresh_token()
         .expect("Server did not return a refresh token");
 
-    // println!("resp: {:?}", token_response);
     let access_token = token_response.access_token();
 
     let expires_in = token_response
         .expires_in()
         .expect("Server did not return access token expiration");
 
-    let id_token_claims: &CoreIdTokenClaims =
-        id_token
-            .claims(&id_token_verifier, &nonce)
-            .map_err(|_err| AuthError::OpenIDConnectError {
-                message: "Failed to verify ID token".to_string(),
-            })?;
+    let id_token_claims: &CoreIdTokenClaims = match nonce {
+        Some(nonce) => id_token.claims(&id_token_verifier, nonce),
+        // the device flow has no authorization request to bind a nonce to
+        None => id_token.claims(&id_token_verifier, |_: Option<&Nonce>| Ok::<(), String>(())),
+    }
+    .map_err(|_err| AuthError::OpenIDConnectError {
+        message: "Failed to verify ID token".to_string(),
+    })?;
 
     // Verify the access token hash to ensure that the access token hasn't been substituted for
     // another user's.
@@ -396,3 +498,194 @@ pub async fn introspect_token(
         iat: token_response.iat(),
     })
 }
+
+/// Revoke the refresh and access tokens, so they can't be used anymore even if
+/// they were copied from this machine.
+pub async fn revoke_tokens(okta_config: &OktaConfig) -> Result<(), WKCliError> {
+    let okta_client_id = ClientId::new(okta_config.client_id.clone());
+
+    let issuer_url =
+        IssuerUrl::new("https://mindvalley.okta.com".to_string()).expect("Invalid issuer URL");
+
+    // Fetch Okta's OpenID Connect discovery document.
+    let provider_metadata = CoreProviderMetadata::discover_async(issuer_url, async_http_client)
+        .await
+        .map_err(|_err| AuthError::OpenIDDiscoveryError)?;
+
+    let client = CoreClient::from_provider_metadata(provider_metadata, okta_client_id, None)
+        .set_revocation_uri(
+            RevocationUrl::new("https://mindvalley.okta.com/oauth2/v1/revoke".to_string())
+                .expect("Invalid revocation URL"),
+        );
+
+    let tokens = [
+        CoreRevocableToken::RefreshToken(RefreshToken::new(okta_config.refresh_token.clone())),
+        CoreRevocableToken::AccessToken(AccessToken::new(okta_config.access_token.clone())),
+    ];
+
+    for token in tokens {
+        client
+            .revoke_token(token)
+            .map_err(|_err| AuthError::OpenIDConnectError {
+                message: "Revocation endpoint is not configured".to_string(),
+            })?
+            .request_async(async_http_client)
+            .await
+            .map_err(|err| AuthError::RevokeTokenError {
+                provider: "Okta",
+                message: err.to_string(),
+            })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use openidconnect::{core::CoreJsonWebKeySet, AuthUrl, TokenUrl};
+    use std::cell::{Cell, RefCell};
+
+    const DEVICE_AUTHORIZATION_PATH: &str = "/oauth2/v1/device/authorize";
+    const TOKEN_PATH: &str = "/oauth2/v1/token";
+
+    fn device_client(server: &MockServer) -> CoreClient {
+        CoreClient::new(
+            ClientId::new("okta-client-id".to_string()),
+            None,
+            IssuerUrl::new(server.base_url()).unwrap(),
+            AuthUrl::new(server.url("/oauth2/v1/authorize")).unwrap(),
+            Some(TokenUrl::new(server.url(TOKEN_PATH)).unwrap()),
+            None,
+            CoreJsonWebKeySet::default(),
+        )
+        .set_device_authorization_uri(
+            DeviceAuthorizationUrl::new(server.url(DEVICE_AUTHORIZATION_PATH)).unwrap(),
+        )
+    }
+
+    fn device_authorization_mock(server: &MockServer) -> httpmock::Mock {
+        server.mock(|when, then| {
+            when.method(POST)
+                .path(DEVICE_AUTHORIZATION_PATH)
+                .body_contains("client_id=okta-client-id")
+                .body_contains("offline_access");
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(
+                    r#"{
+                      "device_code": "device-code",
+                      "user_code": "ABCD-EFGH",
+                      "verification_uri": "https://mindvalley.okta.com/activate",
+                      "expires_in": 600,
+                      "interval": 5
+                    }"#,
+                );
+        })
+    }
+
+    fn token_error_mock<'a>(server: &'a MockServer, error: &str) -> httpmock::Mock<'a> {
+        server.mock(|when, then| {
+            when.method(POST)
+                .path(TOKEN_PATH)
+                .body_contains("device_code=device-code");
+            then.status(400)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(format!(r#"{{"error": "{error}"}}"#));
+        })
+    }
+
+    #[tokio::test]
+    async fn test_request_device_token() {
+        let server = MockServer::start();
+
+        let device_authorization_mock = device_authorization_mock(&server);
+        let pending_mock = RefCell::new(token_error_mock(&server, "authorization_pending"));
+        let token_mock = RefCell::new(None);
+        let polls = Cell::new(0);
+
+        // the user approves the login while the token endpoint is polled
+        let sleep_fn = |_interval| {
+            polls.set(polls.get() + 1);
+            if token_mock.borrow().is_none() {
+                pending_mock.borrow_mut().delete();
+                *token_mock.borrow_mut() = Some(server.mock(|when, then| {
+                    when.method(POST)
+                        .path(TOKEN_PATH)
+                        .body_contains("device_code=device-code");
+                    then.status(200)
+                        .header("content-type", "application/json; charset=UTF-8")
+                        .body(
+                            r#"{
+                              "access_token": "access-token",
+                              "token_type": "Bearer",
+                              "expires_in": 3600,
+                              "refresh_token": "refresh-token"
+                            }"#,
+                        );
+                }));
+            }
+            std::future::ready(())
+        };
+
+        let token_response = request_device_token(&device_client(&server), sleep_fn)
+            .await
+            .unwrap();
+
+        device_authorization_mock.assert();
+        token_mock.borrow().as_ref().unwrap().assert();
+        assert_eq!(polls.get(), 1);
+        assert_eq!(token_response.access_token().secret(), "access-token");
+        assert_eq!(
+            token_response.refresh_token().unwrap().secret(),
+            "refresh-token"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_device_token_denied() {
+        let server = MockServer::start();
+
+        let device_authorization_mock = device_authorization_mock(&server);
+        let denied_mock = token_error_mock(&server, "access_denied");
+
+        let response =
+            request_device_token(&device_client(&server), |_| std::future::ready(())).await;
+
+        device_authorization_mock.assert();
+        denied_mock.assert();
+        match response {
+            Err(WKCliError::AuthError(AuthError::OpenIDConnectError { message })) => {
+                assert!(message.contains("access_denied"))
+            }
+            _ => panic!("it should be returning AuthError::OpenIDConnectError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_device_token_with_an_unauthorized_client() {
+        let server = MockServer::start();
+
+        let device_authorization_mock = server.mock(|when, then| {
+            when.method(POST).path(DEVICE_AUTHORIZATION_PATH);
+            then.status(401)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(r#"{"error": "invalid_client"}"#);
+        });
+        let token_mock = server.mock(|when, then| {
+            when.method(POST).path(TOKEN_PATH);
+            then.status(500);
+        });
+
+        let response =
+            request_device_token(&device_client(&server), |_| std::future::ready(())).await;
+
+        device_authorization_mock.assert();
+        token_mock.assert_hits(0);
+        assert!(matches!(
+            response,
+            Err(WKCliError::AuthError(AuthError::OpenIDConnectError { .. }))
+        ));
+    }
+}
@@ -7,6 +7,7 @@ use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    auth::ci,
     config::{Config, VaultConfig},
     error::{AuthError, WKCliError},
     loader::new_spinner,
@@ -30,9 +31,22 @@ pub struct Login {
     pub auth: Auth,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenLookupData {
+    pub display_name: String,
+    #[serde(default)]
+    pub policies: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenLookup {
+    pub data: TokenLookupData,
+}
+
 const LOGIN_URL: &str = "/v1/auth/okta/login";
 const RENEW_TOKEN_URL: &str = "/v1/auth/token/renew-self";
 const VERIFY_TOKEN_URL: &str = "/v1/auth/token/lookup-self";
+const REVOKE_TOKEN_URL: &str = "/v1/auth/token/revoke-self";
 
 pub static BASE_URL: Lazy<String> = Lazy::new(|| {
     #[cfg(feature = "prod")]
@@ -48,6 +62,11 @@ pub static BASE_URL: Lazy<String> = Lazy::new(|| {
 });
 
 pub async fn get_token_or_login(config: &mut Config) -> Result<String, WKCliError> {
+    // in CI, the token is used as it is, without renewing or saving it
+    if let Some(token) = ci::vault_token() {
+        return Ok(token);
+    }
+
     let okta_config = config
         .auth
         .okta
@@ -228,6 +247,54 @@ async fn renew_token(
     response.json::<Renew>().await.map_err(|err| err.into())
 }
 
+/// Get the identity and policies of the token.
+pub async fn lookup_token(api_token: &str) -> Result<TokenLookup, WKCliError> {
+    debug!("Looking up token ...");
+    let url = format!("{}{}", *BASE_URL, VERIFY_TOKEN_URL);
+
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("X-Vault-Token", api_token)
+        .send()
+        .await?;
+
+    debug!("lookup token: {:?}", response);
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let message = response.text().await?;
+
+        return Err(handle_error(status, message).await);
+    }
+
+    response
+        .json::<TokenLookup>()
+        .await
+        .map_err(|err| err.into())
+}
+
+pub async fn revoke_token(api_token: &str) -> Result<(), WKCliError> {
+    debug!("Revoking token ...");
+    let url = format!("{}{}", *BASE_URL, REVOKE_TOKEN_URL);
+
+    let response = reqwest::Client::new()
+        .post(url)
+        .header("X-Vault-Token", api_token)
+        .send()
+        .await?;
+
+    debug!("revoke token: {:?}", response);
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let message = response.text().await?;
+
+        return Err(handle_error(status, message).await);
+    }
+
+    Ok(())
+}
+
 fn calculate_expiry_time(lease_duration: i64) -> String {
     let current_time: DateTime<Local> = Local::now();
     let expiry_time = current_time + Duration::try_seconds(lease_duration).unwrap();
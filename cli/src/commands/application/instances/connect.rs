@@ -46,6 +46,7 @@ pub async fn handle_connect(
     context: Context,
     namespace_arg: &Option<ApplicationNamespace>,
     version_arg: &Option<ApplicationVersion>,
+    pod_arg: &Option<String>,
 ) -> Result<bool, WKCliError> {
     let spinner_style =
         ProgressStyle::with_template("{prefix:.bold.dim} {spinner} {wide_msg}").unwrap();
@@ -143,38 +144,17 @@ pub async fn handle_connect(
         version.bright_green()
     ));
 
-    let mut items = Vec::new();
-    if !main_pods.is_empty() {
-        items.push("Main:".to_string());
-        items.extend(main_pods.iter().map(|pod| format!("  {}", pod.name)));
-    }
-    if !preview_pods.is_empty() {
-        items.push("Preview:".to_string());
-        items.extend(preview_pods.iter().map(|pod| format!("  {}", pod.name)));
-    }
-
-    let instance_name_idx = loop {
-        let instance_name_idx = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Please choose the instance you want to connect")
-            .default(0)
-            .items(&items)
-            .interact()?;
+    let instance_name = match pod_arg {
+        Some(pod) => {
+            if !k8s_pods.iter().any(|k8s_pod| &k8s_pod.name == pod) {
+                return Err(
+                    ApplicationInstanceError::InstanceNotFound { name: pod.clone() }.into(),
+                );
+            }
 
-        if instance_name_idx == 0 || instance_name_idx == main_pods.len() + 1 {
-            // If "Main pods" or "Preview pods" is selected, continue the loop
-            continue;
-        } else {
-            // Otherwise, break the loop and return the selected index
-            break instance_name_idx;
+            pod.clone()
         }
-    };
-
-    let instance_name = if instance_name_idx < main_pods.len() + 1 {
-        main_pods[instance_name_idx - 1].name.clone()
-    } else {
-        preview_pods[instance_name_idx - main_pods.len() - 2]
-            .name
-            .clone()
+        None => select_instance(&main_pods, &preview_pods)?,
     };
 
     let instance_object = k8s_pods
@@ -420,6 +400,47 @@ async fn cleanup_previous_livebook_instance(
     Ok(())
 }
 
+fn select_instance(
+    main_pods: &[&KubernetesPod],
+    preview_pods: &[&KubernetesPod],
+) -> Result<String, WKCliError> {
+    let mut items = Vec::new();
+    if !main_pods.is_empty() {
+        items.push("Main:".to_string());
+        items.extend(main_pods.iter().map(|pod| format!("  {}", pod.name)));
+    }
+    if !preview_pods.is_empty() {
+        items.push("Preview:".to_string());
+        items.extend(preview_pods.iter().map(|pod| format!("  {}", pod.name)));
+    }
+
+    let instance_name_idx = loop {
+        let instance_name_idx = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Please choose the instance you want to connect")
+            .default(0)
+            .items(&items)
+            .interact()?;
+
+        if instance_name_idx == 0 || instance_name_idx == main_pods.len() + 1 {
+            // If "Main pods" or "Preview pods" is selected, continue the loop
+            continue;
+        } else {
+            // Otherwise, break the loop and return the selected index
+            break instance_name_idx;
+        }
+    };
+
+    let instance_name = if instance_name_idx < main_pods.len() + 1 {
+        main_pods[instance_name_idx - 1].name.clone()
+    } else {
+        preview_pods[instance_name_idx - main_pods.len() - 2]
+            .name
+            .clone()
+    };
+
+    Ok(instance_name)
+}
+
 async fn get_ready_k8s_pods(
     wk_client: &mut WKClient,
     application: &str,
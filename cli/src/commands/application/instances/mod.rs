@@ -1,8 +1,9 @@
 use crate::{
-    commands::{application::instances::connect::handle_connect, Context},
+    commands::{application::instances::connect::handle_connect, completion::candidates, Context},
     error::WKCliError,
 };
 use clap::{Args, Subcommand};
+use clap_complete::engine::ArgValueCompleter;
 
 use self::list::handle_list;
 
@@ -39,6 +40,10 @@ pub enum InstancesSubcommand {
         /// (optional) The version of the application to filter the returning running instances.
         #[arg(long, value_enum)]
         version: Option<ApplicationVersion>,
+
+        /// (optional) The name of the instance to connect to, instead of choosing it interactively.
+        #[arg(long, add = ArgValueCompleter::new(candidates::pods))]
+        pod: Option<String>,
     },
 }
 
@@ -48,9 +53,11 @@ impl Instances {
             InstancesSubcommand::List { namespace, version } => {
                 handle_list(context, &namespace.to_string(), &version.to_string()).await
             }
-            InstancesSubcommand::Connect { namespace, version } => {
-                handle_connect(context, namespace, version).await
-            }
+            InstancesSubcommand::Connect {
+                namespace,
+                version,
+                pod,
+            } => handle_connect(context, namespace, version, pod).await,
         }
     }
 }
@@ -0,0 +1,76 @@
+use crossterm::style::Stylize;
+use log::debug;
+
+use super::Provider;
+use crate::{
+    auth::{google_cloud, okta, vault},
+    config::Config,
+    error::WKCliError,
+    loader::new_spinner,
+};
+
+pub async fn handle_logout(provider: Option<Provider>) -> Result<bool, WKCliError> {
+    let mut config = Config::load_from_default_path()?;
+
+    let providers = match provider {
+        Some(provider) => vec![provider],
+        None => vec![Provider::Okta, Provider::Vault, Provider::GoogleCloud],
+    };
+
+    for provider in providers {
+        let logged_in = match provider {
+            Provider::Okta => config.auth.okta.is_some(),
+            Provider::Vault => config.auth.vault.is_some(),
+            Provider::GoogleCloud => config.auth.google_cloud.is_some(),
+        };
+
+        if !logged_in {
+            println!("Not logged in to {}.", provider.display_name());
+            continue;
+        }
+
+        let loader = new_spinner().with_message(format!(
+            "Revoking the {} tokens ...",
+            provider.display_name()
+        ));
+        let revoke_result = match provider {
+            Provider::Okta => match config.auth.okta.take() {
+                Some(okta_config) => okta::revoke_tokens(&okta_config).await,
+                None => Ok(()),
+            },
+            Provider::Vault => match config.auth.vault.take() {
+                Some(vault_config) => vault::revoke_token(&vault_config.api_token).await,
+                None => Ok(()),
+            },
+            Provider::GoogleCloud => match config.auth.google_cloud.take() {
+                // revoking the refresh token also revokes its access tokens
+                Some(google_cloud_config) => {
+                    google_cloud::revoke_token(&google_cloud_config.refresh_token).await
+                }
+                None => Ok(()),
+            },
+        };
+        loader.finish_and_clear();
+
+        // the tokens are removed from this machine even if they couldn't be
+        // revoked, e.g. because they have expired already
+        if let Err(error) = revoke_result {
+            debug!("Failed to revoke the tokens: {error:?}");
+            println!(
+                "{}",
+                format!(
+                    "Unable to revoke the {} tokens, they are removed from this machine only.",
+                    provider.display_name()
+                )
+                .yellow()
+            );
+        }
+
+        config.save_to_default_path()?;
+        config.delete_credentials(provider.credential_keys())?;
+
+        println!("Logged out from {}.", provider.display_name());
+    }
+
+    Ok(true)
+}
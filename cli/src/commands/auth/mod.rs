@@ -0,0 +1,61 @@
+mod logout;
+mod status;
+
+use clap::{Args, Subcommand, ValueEnum};
+
+use crate::{auth::credential_store, error::WKCliError};
+
+use self::{logout::handle_logout, status::handle_status};
+
+#[derive(Debug, Args)]
+pub struct Auth {
+    #[command(subcommand)]
+    pub subcommand: AuthSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthSubcommand {
+    /// Show the logged in accounts and the state of their tokens
+    Status,
+    /// Revoke the tokens and remove them from this machine
+    Logout {
+        /// Only logout from this provider, instead of all of them
+        #[arg(long, value_enum)]
+        provider: Option<Provider>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Provider {
+    Okta,
+    Vault,
+    GoogleCloud,
+}
+
+impl Provider {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Provider::Okta => "Okta",
+            Provider::Vault => "Vault (Bunker)",
+            Provider::GoogleCloud => "Google Cloud",
+        }
+    }
+
+    /// The credential store keys of the provider's tokens.
+    pub fn credential_keys(&self) -> &'static [&'static str] {
+        match self {
+            Provider::Okta => credential_store::OKTA_KEYS,
+            Provider::Vault => credential_store::VAULT_KEYS,
+            Provider::GoogleCloud => credential_store::GOOGLE_CLOUD_KEYS,
+        }
+    }
+}
+
+impl Auth {
+    pub async fn handle_command(&self) -> Result<bool, WKCliError> {
+        match &self.subcommand {
+            AuthSubcommand::Status => handle_status().await,
+            AuthSubcommand::Logout { provider } => handle_logout(*provider).await,
+        }
+    }
+}
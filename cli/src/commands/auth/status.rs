@@ -0,0 +1,167 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use crossterm::style::Stylize;
+use time_humanize::HumanTime;
+use wukong_sdk::services::gcloud::GCloudClient;
+
+use super::Provider;
+use crate::{
+    auth::{google_cloud, okta, vault},
+    config::Config,
+    error::WKCliError,
+    loader::new_spinner,
+};
+
+struct ProviderStatus {
+    provider: Provider,
+    /// `None` when the user is not logged in to the provider.
+    fields: Option<Vec<(&'static str, String)>>,
+}
+
+impl std::fmt::Display for ProviderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.provider.display_name().bold())?;
+
+        match &self.fields {
+            Some(fields) => {
+                for (name, value) in fields {
+                    writeln!(f, "  {:<16}{}", format!("{name}:"), value)?;
+                }
+            }
+            None => {
+                let login_command = match self.provider {
+                    Provider::Okta => "wukong login",
+                    Provider::Vault => "wukong init",
+                    Provider::GoogleCloud => "wukong google login",
+                };
+                writeln!(
+                    f,
+                    "  Not logged in. Run {} to login.",
+                    login_command.yellow()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn handle_status() -> Result<bool, WKCliError> {
+    let config = Config::load_from_default_path()?;
+
+    let loader = new_spinner().with_message("Checking the tokens ...");
+    let statuses = [
+        okta_status(&config).await,
+        vault_status(&config).await,
+        google_cloud_status(&config).await,
+    ];
+    loader.finish_and_clear();
+
+    let output = statuses
+        .iter()
+        .map(|status| status.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    print!("{output}");
+
+    Ok(true)
+}
+
+async fn okta_status(config: &Config) -> ProviderStatus {
+    let fields = match &config.auth.okta {
+        Some(okta_config) => {
+            let refresh_token =
+                match okta::introspect_token(config, &okta_config.refresh_token).await {
+                    Ok(introspection) if introspection.active => match introspection.exp {
+                        Some(exp) => format!("valid, {}", fmt_expiry(&exp.to_rfc3339())),
+                        None => "valid".to_string(),
+                    },
+                    Ok(_) => "invalid".red().to_string(),
+                    Err(error) => format!("unknown ({error})"),
+                };
+
+            Some(vec![
+                ("Account", okta_config.account.clone()),
+                ("Access token", fmt_expiry(&okta_config.expiry_time)),
+                ("Refresh token", refresh_token),
+            ])
+        }
+        None => None,
+    };
+
+    ProviderStatus {
+        provider: Provider::Okta,
+        fields,
+    }
+}
+
+async fn vault_status(config: &Config) -> ProviderStatus {
+    let fields = match &config.auth.vault {
+        Some(vault_config) => {
+            let (account, policies) = match vault::lookup_token(&vault_config.api_token).await {
+                Ok(lookup) => (lookup.data.display_name, lookup.data.policies.join(", ")),
+                Err(error) => ("unknown".to_string(), format!("unknown ({error})")),
+            };
+
+            Some(vec![
+                ("Account", account),
+                ("Token", fmt_expiry(&vault_config.expiry_time)),
+                ("Policies", policies),
+            ])
+        }
+        None => None,
+    };
+
+    ProviderStatus {
+        provider: Provider::Vault,
+        fields,
+    }
+}
+
+async fn google_cloud_status(config: &Config) -> ProviderStatus {
+    let fields = match &config.auth.google_cloud {
+        Some(google_cloud_config) => {
+            let account = google_cloud_config
+                .id_token
+                .as_deref()
+                .and_then(google_cloud::id_token_email)
+                .unwrap_or_else(|| "N/A".to_string());
+
+            // the token info endpoint only knows about the tokens which are not expired yet
+            let scopes = match GCloudClient::new(google_cloud_config.access_token.clone())
+                .fetch_access_token_info()
+                .await
+            {
+                Ok(token_info) => token_info.scope.split(' ').collect::<Vec<_>>().join(", "),
+                Err(error) => format!("unknown ({error})"),
+            };
+
+            Some(vec![
+                ("Account", account),
+                ("Access token", fmt_expiry(&google_cloud_config.expiry_time)),
+                ("Scopes", scopes),
+            ])
+        }
+        None => None,
+    };
+
+    ProviderStatus {
+        provider: Provider::GoogleCloud,
+        fields,
+    }
+}
+
+fn fmt_expiry(expiry_time: &str) -> String {
+    match DateTime::parse_from_rfc3339(expiry_time) {
+        Ok(expiry) => {
+            let human_time = HumanTime::from(SystemTime::from(expiry));
+            if expiry < Utc::now() {
+                format!("expired {human_time}").red().to_string()
+            } else {
+                format!("expires {human_time}")
+            }
+        }
+        Err(_) => "unknown".to_string(),
+    }
+}
@@ -0,0 +1,14 @@
+use wukong_sdk::graphql::ResponseCache;
+
+use crate::{config::CACHE_DIR, error::WKCliError};
+
+pub fn handle_clear() -> Result<bool, WKCliError> {
+    let cache_dir = CACHE_DIR
+        .as_ref()
+        .expect("Unable to identify user's home directory");
+
+    ResponseCache::clear(cache_dir)?;
+    println!("The cached API responses are removed.");
+
+    Ok(true)
+}
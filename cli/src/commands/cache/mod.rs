@@ -0,0 +1,27 @@
+mod clear;
+
+use clap::{Args, Subcommand};
+
+use crate::error::WKCliError;
+
+use self::clear::handle_clear;
+
+#[derive(Debug, Args)]
+pub struct Cache {
+    #[command(subcommand)]
+    pub subcommand: CacheSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CacheSubcommand {
+    /// Remove the cached API responses of every account
+    Clear,
+}
+
+impl Cache {
+    pub fn handle_command(&self) -> Result<bool, WKCliError> {
+        match &self.subcommand {
+            CacheSubcommand::Clear => handle_clear(),
+        }
+    }
+}
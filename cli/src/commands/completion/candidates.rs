@@ -0,0 +1,465 @@
+//! The argument values completed by the `wukong completion <SHELL> --dynamic` scripts.
+//!
+//! The values from the Wukong API are cached for a few minutes under the cache directory,
+//! and the request is abandoned after a short timeout, falling back to the expired values
+//! if there are any, so that pressing tab never hangs the shell.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    ffi::OsStr,
+    fs,
+    future::Future,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::Utc;
+use clap_complete::engine::CompletionCandidate;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    application_config::ApplicationConfigs,
+    commands::skills,
+    config::{ApiChannel, Config, CACHE_DIR},
+    error::WKCliError,
+    wukong_client::WKClient,
+};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(2);
+const CANDIDATES_TTL_IN_SECS: i64 = 5 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Candidate {
+    value: String,
+    help: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedCandidates {
+    cached_at: i64,
+    candidates: Vec<Candidate>,
+}
+
+/// The build artifacts of the recent builds, for `deployment execute --artifact`.
+///
+/// The builds are the ones of the `--namespace` and `--version` already typed on the
+/// command line, `prod` and `green` otherwise.
+pub fn artifacts(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(application) = current_application() else {
+        return Vec::new();
+    };
+    let namespace = arg_value("--namespace").unwrap_or_else(|| "prod".to_string());
+    let version = arg_value("--version").unwrap_or_else(|| "green".to_string());
+
+    let candidates = cached(
+        &format!("artifacts:{application}:{namespace}:{version}"),
+        async move {
+            let mut wk_client = api_client()?;
+            let builds = wk_client
+                .fetch_cd_pipeline_github(&application, &namespace, &version)
+                .await?
+                .cd_pipeline
+                .map(|cd_pipeline| cd_pipeline.github_builds)
+                .unwrap_or_default();
+
+            Ok(builds
+                .into_iter()
+                .map(|build| Candidate {
+                    value: build.build_artifact_name,
+                    help: Some(build.build_branch),
+                })
+                .collect())
+        },
+    );
+
+    complete(current, candidates)
+}
+
+/// The skills in the registry, for `skills add`.
+pub fn skill_slugs(current: &OsStr) -> Vec<CompletionCandidate> {
+    let candidates = cached("skills", async {
+        let mut wk_client = api_client()?;
+        let skills = wk_client.fetch_skills(None).await?.skills;
+
+        Ok(skills
+            .into_iter()
+            .map(|skill| Candidate {
+                value: skill.slug,
+                help: Some(skill.name),
+            })
+            .collect())
+    });
+
+    complete(current, candidates)
+}
+
+/// The skills installed on this machine, for `skills remove`.
+pub fn installed_skills(current: &OsStr) -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<Candidate> = skills::installed_skills()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|skill| Candidate {
+            value: skill.name,
+            help: Some(skill.scope),
+        })
+        .collect();
+    // the same skill is usually installed in both .claude and .agents
+    candidates.sort_by(|a, b| a.value.cmp(&b.value));
+    candidates.dedup_by(|a, b| a.value == b.value);
+
+    complete(current, candidates)
+}
+
+/// The instances ready to connect to, for `application instances connect --pod`.
+///
+/// The pods are the ones of the `--namespace` and `--version` already typed on the
+/// command line, `prod` and `green` otherwise.
+pub fn pods(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(application) = current_application() else {
+        return Vec::new();
+    };
+    let namespace = arg_value("--namespace").unwrap_or_else(|| "prod".to_string());
+    let version = arg_value("--version").unwrap_or_else(|| "green".to_string());
+
+    let candidates = cached(
+        &format!("pods:{application}:{namespace}:{version}"),
+        async move {
+            let mut wk_client = api_client()?;
+            let pods = wk_client
+                .fetch_kubernetes_pods(&application, &namespace, &version)
+                .await?
+                .kubernetes_pods;
+
+            Ok(pods
+                .into_iter()
+                .filter(|pod| pod.ready && !pod.labels.contains(&"livebook".to_string()))
+                .map(|pod| Candidate {
+                    value: pod.name,
+                    help: pod.pod_ip,
+                })
+                .collect())
+        },
+    );
+
+    complete(current, candidates)
+}
+
+fn complete(current: &OsStr, candidates: Vec<Candidate>) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.value.starts_with(current.as_ref()))
+        .map(|candidate| {
+            CompletionCandidate::new(candidate.value).help(candidate.help.map(Into::into))
+        })
+        .collect()
+}
+
+fn current_application() -> Option<String> {
+    ApplicationConfigs::load()
+        .ok()
+        .map(|application_configs| application_configs.application.name)
+}
+
+fn api_client() -> Result<WKClient, WKCliError> {
+    let config = Config::load_from_default_path()?;
+    WKClient::for_channel(&config, &ApiChannel::Canary)
+}
+
+// The value of an option typed before the one being completed. The shell passes the
+// whole command line as the arguments of the completion request.
+fn arg_value(name: &str) -> Option<String> {
+    find_arg_value(std::env::args(), name)
+}
+
+fn find_arg_value(args: impl IntoIterator<Item = String>, name: &str) -> Option<String> {
+    let mut args = args.into_iter();
+    let prefix = format!("{name}=");
+
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+
+    None
+}
+
+// Use the cached candidates while they are fresh, otherwise fetch them with a timeout.
+// The completion runs before the async runtime of the CLI is started, so it has its own.
+fn cached(
+    key: &str,
+    fetch: impl Future<Output = Result<Vec<Candidate>, WKCliError>>,
+) -> Vec<Candidate> {
+    cached_in(
+        CACHE_DIR.as_deref().map(Path::new),
+        FETCH_TIMEOUT,
+        key,
+        fetch,
+    )
+}
+
+fn cached_in(
+    cache_dir: Option<&Path>,
+    fetch_timeout: Duration,
+    key: &str,
+    fetch: impl Future<Output = Result<Vec<Candidate>, WKCliError>>,
+) -> Vec<Candidate> {
+    let cache_file = cache_dir.map(|cache_dir| cache_file(cache_dir, key));
+    let cached_candidates = cache_file
+        .as_ref()
+        .and_then(|cache_file| fs::read_to_string(cache_file).ok())
+        .and_then(|content| serde_json::from_str::<CachedCandidates>(&content).ok());
+
+    if let Some(cached_candidates) = &cached_candidates {
+        if Utc::now().timestamp() - cached_candidates.cached_at < CANDIDATES_TTL_IN_SECS {
+            return cached_candidates.candidates.clone();
+        }
+    }
+
+    let fetched = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(WKCliError::from)
+        .and_then(|runtime| {
+            runtime
+                .block_on(async { tokio::time::timeout(fetch_timeout, fetch).await })
+                .unwrap_or_else(|_| {
+                    debug!("Timed out fetching the completion candidates of {key}.");
+                    Ok(Vec::new())
+                })
+        });
+
+    match fetched {
+        Ok(candidates) if !candidates.is_empty() => {
+            if let Some(cache_file) = &cache_file {
+                let cached_candidates = CachedCandidates {
+                    cached_at: Utc::now().timestamp(),
+                    candidates: candidates.clone(),
+                };
+                if let Some(parent) = cache_file.parent() {
+                    let _ = fs::create_dir_all(parent);
+                }
+                if let Ok(content) = serde_json::to_string(&cached_candidates) {
+                    let _ = fs::write(cache_file, content);
+                }
+            }
+
+            candidates
+        }
+        result => {
+            if let Err(error) = result {
+                debug!("Failed to fetch the completion candidates of {key}: {error:?}");
+            }
+
+            cached_candidates
+                .map(|cached_candidates| cached_candidates.candidates)
+                .unwrap_or_default()
+        }
+    }
+}
+
+fn cache_file(cache_dir: &Path, key: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+
+    cache_dir
+        .join("completion")
+        .join(format!("{:016x}.json", hasher.finish()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_TIMEOUT: Duration = Duration::from_millis(200);
+
+    fn candidates(values: &[&str]) -> Vec<Candidate> {
+        values
+            .iter()
+            .map(|value| Candidate {
+                value: value.to_string(),
+                help: None,
+            })
+            .collect()
+    }
+
+    fn values(candidates: &[Candidate]) -> Vec<&str> {
+        candidates
+            .iter()
+            .map(|candidate| candidate.value.as_str())
+            .collect()
+    }
+
+    fn write_cache(cache_dir: &Path, key: &str, cached_at: i64, values: &[&str]) {
+        let cache_file = cache_file(cache_dir, key);
+        fs::create_dir_all(cache_file.parent().unwrap()).unwrap();
+        fs::write(
+            cache_file,
+            serde_json::to_string(&CachedCandidates {
+                cached_at,
+                candidates: candidates(values),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn expired() -> i64 {
+        Utc::now().timestamp() - CANDIDATES_TTL_IN_SECS - 1
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn find_the_value_of_a_typed_option() {
+        let line = args(&[
+            "wukong",
+            "--",
+            "wukong",
+            "deployment",
+            "execute",
+            "--namespace",
+            "staging",
+            "--version=blue",
+            "--artifact",
+            "",
+        ]);
+
+        assert_eq!(
+            find_arg_value(line.clone(), "--namespace"),
+            Some("staging".to_string())
+        );
+        assert_eq!(
+            find_arg_value(line.clone(), "--version"),
+            Some("blue".to_string())
+        );
+        assert_eq!(find_arg_value(line, "--pod"), None);
+    }
+
+    #[test]
+    fn complete_only_the_candidates_starting_with_the_current_value() {
+        let candidates = vec![
+            Candidate {
+                value: "main-build-100".to_string(),
+                help: Some("main".to_string()),
+            },
+            Candidate {
+                value: "feature-build-99".to_string(),
+                help: None,
+            },
+        ];
+
+        let completions = complete(OsStr::new("main"), candidates);
+
+        assert_eq!(completions.len(), 1);
+        assert_eq!(completions[0].get_value(), "main-build-100");
+    }
+
+    #[test]
+    fn cache_the_fetched_candidates() {
+        let temp = assert_fs::TempDir::new().unwrap();
+
+        let fetched = cached_in(Some(temp.path()), TEST_TIMEOUT, "skills", async {
+            Ok(candidates(&["wukong-cli", "elixir"]))
+        });
+        assert_eq!(values(&fetched), vec!["wukong-cli", "elixir"]);
+        assert!(cache_file(temp.path(), "skills").exists());
+
+        // the fresh candidates are used without fetching them again
+        let cached = cached_in(Some(temp.path()), TEST_TIMEOUT, "skills", async {
+            Ok(candidates(&["fetched-again"]))
+        });
+        assert_eq!(values(&cached), vec!["wukong-cli", "elixir"]);
+
+        // each key has its own cache
+        let other = cached_in(
+            Some(temp.path()),
+            TEST_TIMEOUT,
+            "pods:app:prod:green",
+            async { Ok(candidates(&["app-pod-1"])) },
+        );
+        assert_eq!(values(&other), vec!["app-pod-1"]);
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn fetch_the_candidates_again_once_expired() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        write_cache(temp.path(), "skills", expired(), &["old-skill"]);
+
+        let fetched = cached_in(Some(temp.path()), TEST_TIMEOUT, "skills", async {
+            Ok(candidates(&["new-skill"]))
+        });
+        assert_eq!(values(&fetched), vec!["new-skill"]);
+
+        let cached = cached_in(Some(temp.path()), TEST_TIMEOUT, "skills", async {
+            Ok(candidates(&["fetched-again"]))
+        });
+        assert_eq!(values(&cached), vec!["new-skill"]);
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn fall_back_to_the_expired_candidates_when_the_fetch_times_out() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        write_cache(temp.path(), "skills", expired(), &["old-skill"]);
+
+        let fetched = cached_in(Some(temp.path()), TEST_TIMEOUT, "skills", async {
+            tokio::time::sleep(TEST_TIMEOUT * 10).await;
+            Ok(candidates(&["new-skill"]))
+        });
+        assert_eq!(values(&fetched), vec!["old-skill"]);
+
+        // without any cached candidates, nothing is completed
+        let fetched = cached_in(Some(temp.path()), TEST_TIMEOUT, "other", async {
+            tokio::time::sleep(TEST_TIMEOUT * 10).await;
+            Ok(candidates(&["new-skill"]))
+        });
+        assert!(fetched.is_empty());
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn complete_nothing_when_not_logged_in() {
+        let temp = assert_fs::TempDir::new().unwrap();
+
+        let fetched = cached_in(Some(temp.path()), TEST_TIMEOUT, "skills", async {
+            // a config without any okta login
+            let mut wk_client = WKClient::for_channel(&Config::default(), &ApiChannel::Canary)?;
+            let skills = wk_client.fetch_skills(None).await?.skills;
+
+            Ok(skills
+                .into_iter()
+                .map(|skill| Candidate {
+                    value: skill.slug,
+                    help: Some(skill.name),
+                })
+                .collect())
+        });
+        assert!(fetched.is_empty());
+        // the failure isn't cached, so the candidates are fetched once logged in
+        assert!(!cache_file(temp.path(), "skills").exists());
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn complete_without_a_cache_dir() {
+        let fetched = cached_in(None, TEST_TIMEOUT, "skills", async {
+            Ok(candidates(&["wukong-cli"]))
+        });
+        assert_eq!(values(&fetched), vec!["wukong-cli"]);
+    }
+}
@@ -0,0 +1,74 @@
+pub mod candidates;
+
+use crate::{error::WKCliError, loader::new_spinner};
+use clap::CommandFactory;
+use clap_complete::{
+    env::{Bash, Elvish, EnvCompleter, Fish, Powershell, Zsh},
+    generate, CompleteEnv, Shell,
+};
+
+use super::ClapApp;
+
+// the environment variable telling the CLI to answer a completion request of the shell
+const COMPLETE_ENV: &str = "COMPLETE";
+
+pub fn handle_completion(shell: Shell, dynamic: bool) -> Result<bool, WKCliError> {
+    if dynamic {
+        write_dynamic_registration(shell)?;
+        return Ok(true);
+    }
+
+    let loader = new_spinner();
+    loader.set_message("Generating completion ...");
+
+    let mut cmd = ClapApp::command();
+    cmd.set_bin_name("wukong");
+
+    generate(shell, &mut cmd, "wukong", &mut std::io::stdout().lock());
+
+    loader.finish_and_clear();
+
+    Ok(true)
+}
+
+/// Answer the completion request of the shell, and exit, when the CLI is run by a
+/// completion script generated with `wukong completion <SHELL> --dynamic`. Otherwise
+/// it does nothing.
+pub fn complete_dynamically() {
+    CompleteEnv::with_factory(|| {
+        let mut cmd = ClapApp::command();
+        cmd.set_bin_name("wukong");
+        cmd
+    })
+    .var(COMPLETE_ENV)
+    .complete();
+}
+
+// The dynamic completion script only registers the CLI itself as the completer, which
+// then completes the arguments like the static script, plus the values from the API.
+fn write_dynamic_registration(shell: Shell) -> Result<(), WKCliError> {
+    let completer: &dyn EnvCompleter = match shell {
+        Shell::Bash => &Bash,
+        Shell::Elvish => &Elvish,
+        Shell::Fish => &Fish,
+        Shell::PowerShell => &Powershell,
+        Shell::Zsh => &Zsh,
+        _ => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("The dynamic completion is not supported for {shell}."),
+            )
+            .into())
+        }
+    };
+
+    completer.write_registration(
+        COMPLETE_ENV,
+        "wukong",
+        "wukong",
+        "wukong",
+        &mut std::io::stdout().lock(),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use toml::Value;
+
+use crate::{
+    application_config::ApplicationConfigs,
+    config::{Config as CliConfig, CONFIG_FILE},
+    error::{ApplicationConfigError, ConfigError, WKCliError},
+};
+
+use super::key_path;
+
+/// A config file loaded as a TOML document, so any key can be read or written
+/// without a dedicated field on the typed config.
+pub struct ConfigDocument {
+    pub value: Value,
+    target: Target,
+}
+
+enum Target {
+    /// The CLI config, e.g. `~/.config/wukong/config.toml`.
+    Global,
+    /// The `.wukong.toml` of the current application.
+    Local(PathBuf),
+}
+
+impl ConfigDocument {
+    pub fn load(local: bool) -> Result<Self, WKCliError> {
+        if local {
+            let path = ApplicationConfigs::load()?.config_path().to_path_buf();
+            let content = std::fs::read_to_string(&path)?;
+            let value = toml::from_str(&content).map_err(ApplicationConfigError::BadTomlData)?;
+
+            Ok(Self {
+                value,
+                target: Target::Local(path),
+            })
+        } else {
+            // the tokens live in the credential store, keep them out of `config get/list/edit`
+            let config = CliConfig::load_from_default_path()?.without_credentials();
+            let value = Value::try_from(&config).map_err(ConfigError::SerializeTomlError)?;
+
+            Ok(Self {
+                value,
+                target: Target::Global,
+            })
+        }
+    }
+
+    /// Replace the document with the given TOML content, e.g. after editing it by hand.
+    pub fn replace(&mut self, content: &str) -> Result<(), WKCliError> {
+        self.value = match self.target {
+            Target::Global => toml::from_str(content).map_err(ConfigError::BadTomlData)?,
+            Target::Local(_) => {
+                toml::from_str(content).map_err(ApplicationConfigError::BadTomlData)?
+            }
+        };
+
+        Ok(())
+    }
+
+    pub fn path(&self) -> String {
+        match &self.target {
+            Target::Global => CONFIG_FILE
+                .as_ref()
+                .expect("Unable to identify user's home directory")
+                .to_string(),
+            Target::Local(path) => path.to_string_lossy().to_string(),
+        }
+    }
+
+    /// Validate the document and render it the way it will be written to disk.
+    ///
+    /// The global config goes through [CliConfig] so the output is identical
+    /// to what the rest of the CLI writes. For `.wukong.toml`, only the
+    /// `application` table is typed; the other tables (e.g. `[[secrets]]`)
+    /// are kept as they are.
+    pub fn render(&self) -> Result<String, WKCliError> {
+        key_path::validate(&self.value)?;
+
+        match &self.target {
+            Target::Global => {
+                let config: CliConfig = self
+                    .value
+                    .clone()
+                    .try_into()
+                    .map_err(ConfigError::InvalidConfig)?;
+                let typed = Value::try_from(&config).map_err(ConfigError::SerializeTomlError)?;
+                if let Some(key) = key_path::find_unknown_key(&self.value, &typed) {
+                    return Err(ConfigError::InvalidKey { key }.into());
+                }
+
+                Ok(toml::to_string(&config).map_err(ConfigError::SerializeTomlError)?)
+            }
+            Target::Local(_) => {
+                let mut others = match &self.value {
+                    Value::Table(table) => table.clone(),
+                    _ => toml::map::Map::new(),
+                };
+                let application = others
+                    .remove("application")
+                    .unwrap_or_else(|| Value::Table(toml::map::Map::new()));
+
+                let mut modelled = toml::map::Map::new();
+                modelled.insert("application".to_string(), application);
+                let modelled = Value::Table(modelled);
+
+                let config: ApplicationConfigs = modelled
+                    .clone()
+                    .try_into()
+                    .map_err(ConfigError::InvalidConfig)?;
+                let typed = Value::try_from(&config).map_err(ConfigError::SerializeTomlError)?;
+                if let Some(key) = key_path::find_unknown_key(&modelled, &typed) {
+                    return Err(ConfigError::InvalidKey { key }.into());
+                }
+
+                let mut content = config.to_string()?;
+                if !others.is_empty() {
+                    content.push('\n');
+                    content.push_str(
+                        &toml::to_string(&Value::Table(others))
+                            .map_err(ConfigError::SerializeTomlError)?,
+                    );
+                }
+
+                Ok(content)
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), WKCliError> {
+        let content = self.render()?;
+
+        match &self.target {
+            Target::Global => {
+                // reuse `Config::save_to_default_path` so the file is written the usual way
+                let config: CliConfig =
+                    toml::from_str(&content).map_err(ConfigError::BadTomlData)?;
+                config.save_to_default_path()?;
+            }
+            Target::Local(path) => std::fs::write(path, content)?,
+        }
+
+        Ok(())
+    }
+}
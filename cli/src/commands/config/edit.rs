@@ -0,0 +1,41 @@
+use log::error;
+use owo_colors::OwoColorize;
+
+use crate::{error::WKCliError, output::error::ErrorOutput, utils::inquire::inquire_render_config};
+
+use super::document::ConfigDocument;
+
+pub fn handle_edit(local: bool) -> Result<bool, WKCliError> {
+    let mut document = ConfigDocument::load(local)?;
+    let original = document.render()?;
+    let mut content = original.clone();
+
+    loop {
+        content = edit::edit(&content)?;
+        if content == original {
+            println!("No changes made to {}.", document.path());
+            return Ok(true);
+        }
+
+        // only save when the edited content is valid, otherwise let the user fix it
+        match document.replace(&content).and_then(|_| document.save()) {
+            Ok(_) => {
+                println!("Updated {}.", document.path());
+                return Ok(true);
+            }
+            Err(error) => {
+                error!("{}", ErrorOutput(error));
+
+                let edit_again = inquire::Confirm::new("Do you want to edit the file again?")
+                    .with_render_config(inquire_render_config())
+                    .with_default(true)
+                    .prompt()?;
+
+                if !edit_again {
+                    println!("{}", "Aborted, the config file is not changed.".yellow());
+                    return Ok(true);
+                }
+            }
+        }
+    }
+}
@@ -1,20 +1,27 @@
-use crate::{config::Config as CliConfig, error::WKCliError};
+use toml::Value;
 
-use super::ConfigName;
+use crate::error::{ConfigError, WKCliError};
 
-pub fn handle_get(config_name: &ConfigName) -> Result<bool, WKCliError> {
-    let config = CliConfig::load_from_default_path()?;
-    println!("{:?}", config_name);
-    match config_name {
-        ConfigName::WukongApiUrl => println!("{}", config.core.wukong_api_url),
-        ConfigName::OktaClientId => println!(
+use super::{document::ConfigDocument, key_path};
+
+pub fn handle_get(key: &str, local: bool) -> Result<bool, WKCliError> {
+    let segments = key_path::parse_key(key)?;
+    let document = ConfigDocument::load(local)?;
+
+    match key_path::get(&document.value, &segments) {
+        Some(Value::String(value)) => println!("{value}"),
+        Some(value @ Value::Table(_)) => print!(
             "{}",
-            if let Some(okta) = config.auth.okta {
-                okta.client_id
-            } else {
-                "Okta client id not set. Please use `wukong login`".to_string()
-            }
+            toml::to_string(value).map_err(ConfigError::SerializeTomlError)?
         ),
+        Some(value) => println!("{value}"),
+        None => {
+            return Err(ConfigError::KeyNotFound {
+                key: segments.join("."),
+            }
+            .into())
+        }
     };
+
     Ok(true)
 }
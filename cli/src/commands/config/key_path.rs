@@ -0,0 +1,465 @@
+use crate::error::ConfigError;
+use toml::Value;
+
+/// The validation rule of a config key, looked up by its dotted path.
+///
+/// `*` in a pattern matches any single segment, e.g. an array index.
+enum Rule {
+    Url,
+    OneOf(&'static [&'static str]),
+}
+
+const RULES: &[(&str, Rule)] = &[
+    ("core.wukong_api_url", Rule::Url),
+    (
+        "update_check.channel",
+        Rule::OneOf(&["stable", "prerelease"]),
+    ),
+    ("telemetry.exporter", Rule::OneOf(&["honeycomb", "otlp"])),
+    ("telemetry.endpoint", Rule::Url),
+    (
+        "application.namespaces.*.type",
+        Rule::OneOf(&["prod", "staging"]),
+    ),
+    (
+        "application.workflows.provider",
+        Rule::OneOf(&["github_actions"]),
+    ),
+    (
+        "application.namespaces.*.delivery.rollout_strategy",
+        Rule::OneOf(&["rolling_upgrade", "blue_green", "canary"]),
+    ),
+];
+
+/// The config names supported before dotted paths were introduced, kept so
+/// `wukong config get wukong-api-url` still works.
+const ALIASES: &[(&str, &str)] = &[
+    ("wukong-api-url", "core.wukong_api_url"),
+    ("okta-client-id", "auth.okta.client_id"),
+];
+
+/// Resolve a user-supplied key (a dotted path or a legacy alias) into path segments.
+pub fn parse_key(key: &str) -> Result<Vec<String>, ConfigError> {
+    let key = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, path)| *path)
+        .unwrap_or(key);
+
+    let segments: Vec<String> = key.split('.').map(|s| s.trim().to_string()).collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(ConfigError::InvalidKey {
+            key: key.to_string(),
+        });
+    }
+
+    Ok(segments)
+}
+
+pub fn display_key(segments: &[String]) -> String {
+    segments.join("/")
+}
+
+pub fn get<'a>(root: &'a Value, segments: &[String]) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(root, |value, segment| match value {
+            Value::Table(table) => table.get(segment),
+            Value::Array(array) => segment.parse::<usize>().ok().and_then(|i| array.get(i)),
+            _ => None,
+        })
+}
+
+/// Set the value at `segments`, creating the missing intermediate tables.
+///
+/// The raw string is coerced to the type of the existing value, so
+/// `true`/`false` stay booleans and numbers stay numbers.
+pub fn set(root: &mut Value, segments: &[String], raw_value: &str) -> Result<(), ConfigError> {
+    let key = segments.join(".");
+    let (last, parents) = segments
+        .split_last()
+        .ok_or(ConfigError::InvalidKey { key: key.clone() })?;
+
+    let mut current = root;
+    for segment in parents {
+        current = match current {
+            Value::Table(table) => table
+                .entry(segment.clone())
+                .or_insert_with(|| Value::Table(toml::map::Map::new())),
+            Value::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| array.get_mut(i))
+                .ok_or(ConfigError::InvalidKey { key: key.clone() })?,
+            _ => return Err(ConfigError::InvalidKey { key }),
+        };
+    }
+
+    let existing = get(current, std::slice::from_ref(last));
+    let new_value = coerce(&key, existing, raw_value)?;
+
+    match current {
+        Value::Table(table) => {
+            table.insert(last.clone(), new_value);
+        }
+        Value::Array(array) => {
+            let slot = last
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| array.get_mut(i))
+                .ok_or(ConfigError::InvalidKey { key: key.clone() })?;
+            *slot = new_value;
+        }
+        _ => return Err(ConfigError::InvalidKey { key }),
+    }
+
+    Ok(())
+}
+
+/// Remove the value at `segments`. Returns `false` if there was nothing to remove.
+pub fn unset(root: &mut Value, segments: &[String]) -> bool {
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+
+    let mut current = root;
+    for segment in parents {
+        let next = match current {
+            Value::Table(table) => table.get_mut(segment),
+            Value::Array(array) => segment.parse::<usize>().ok().and_then(|i| array.get_mut(i)),
+            _ => None,
+        };
+        match next {
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+
+    match current {
+        Value::Table(table) => table.remove(last).is_some(),
+        Value::Array(array) => match last.parse::<usize>() {
+            Ok(i) if i < array.len() => {
+                array.remove(i);
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Run the key specific rules (URL syntax, enum values) on every leaf of the config.
+pub fn validate(root: &Value) -> Result<(), ConfigError> {
+    validate_value(root, &mut Vec::new())
+}
+
+/// Find the first leaf in `raw` that is missing from `typed`, i.e. a key the
+/// typed config doesn't know about and would silently drop when saved.
+pub fn find_unknown_key(raw: &Value, typed: &Value) -> Option<String> {
+    find_unknown_leaf(raw, typed, &mut Vec::new())
+}
+
+fn find_unknown_leaf(raw: &Value, typed: &Value, path: &mut Vec<String>) -> Option<String> {
+    let children: Vec<(String, &Value)> = match raw {
+        Value::Table(table) => table.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        _ => return None,
+    };
+
+    for (key, value) in children {
+        path.push(key);
+        match get(typed, std::slice::from_ref(path.last().unwrap())) {
+            Some(typed_child) => {
+                if let Some(unknown) = find_unknown_leaf(value, typed_child, path) {
+                    return Some(unknown);
+                }
+            }
+            None => return Some(path.join(".")),
+        }
+        path.pop();
+    }
+
+    None
+}
+
+fn validate_value(value: &Value, path: &mut Vec<String>) -> Result<(), ConfigError> {
+    match value {
+        Value::Table(table) => {
+            for (key, value) in table {
+                path.push(key.clone());
+                validate_value(value, path)?;
+                path.pop();
+            }
+        }
+        Value::Array(array) => {
+            for (index, value) in array.iter().enumerate() {
+                path.push(index.to_string());
+                validate_value(value, path)?;
+                path.pop();
+            }
+        }
+        value => {
+            if let Some(rule) = find_rule(path) {
+                check_rule(&path.join("."), rule, value)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn find_rule(path: &[String]) -> Option<&'static Rule> {
+    RULES.iter().find_map(|(pattern, rule)| {
+        let pattern: Vec<&str> = pattern.split('.').collect();
+        let matched = pattern.len() == path.len()
+            && pattern
+                .iter()
+                .zip(path)
+                .all(|(pattern, segment)| *pattern == "*" || pattern == segment);
+
+        matched.then_some(rule)
+    })
+}
+
+fn check_rule(key: &str, rule: &Rule, value: &Value) -> Result<(), ConfigError> {
+    let Some(value) = value.as_str() else {
+        return Err(ConfigError::InvalidValue {
+            key: key.to_string(),
+            reason: "expected a string".to_string(),
+        });
+    };
+
+    match rule {
+        Rule::Url => {
+            url::Url::parse(value).map_err(|err| ConfigError::InvalidValue {
+                key: key.to_string(),
+                reason: format!("\"{value}\" is not a valid URL ({err})"),
+            })?;
+        }
+        Rule::OneOf(allowed) => {
+            if !allowed.contains(&value) {
+                return Err(ConfigError::InvalidValue {
+                    key: key.to_string(),
+                    reason: format!(
+                        "\"{value}\" is not one of [possible values: {}]",
+                        allowed.join(", ")
+                    ),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn coerce(key: &str, existing: Option<&Value>, raw_value: &str) -> Result<Value, ConfigError> {
+    let raw_value = raw_value.trim();
+    let invalid = |reason: &str| ConfigError::InvalidValue {
+        key: key.to_string(),
+        reason: reason.to_string(),
+    };
+
+    match existing {
+        Some(Value::String(_)) => Ok(Value::String(raw_value.to_string())),
+        Some(Value::Boolean(_)) => raw_value
+            .parse::<bool>()
+            .map(Value::Boolean)
+            .map_err(|_| invalid("expected `true` or `false`")),
+        Some(Value::Integer(_)) => raw_value
+            .parse::<i64>()
+            .map(Value::Integer)
+            .map_err(|_| invalid("expected an integer")),
+        Some(Value::Float(_)) => raw_value
+            .parse::<f64>()
+            .map(Value::Float)
+            .map_err(|_| invalid("expected a number")),
+        Some(Value::Table(_)) => Err(invalid(
+            "it is a table, set the individual keys under it instead",
+        )),
+        // Arrays and new keys accept any TOML literal, falling back to a plain string.
+        Some(Value::Array(_)) | Some(Value::Datetime(_)) | None => {
+            match toml::from_str::<Value>(&format!("value = {raw_value}")) {
+                Ok(Value::Table(mut table)) => Ok(table
+                    .remove("value")
+                    .unwrap_or_else(|| Value::String(raw_value.to_string()))),
+                _ if matches!(existing, Some(Value::Array(_))) => {
+                    Err(invalid("expected a TOML array, e.g. [\"a\", \"b\"]"))
+                }
+                _ => Ok(Value::String(raw_value.to_string())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> Value {
+        toml::from_str(
+            r#"
+[core]
+wukong_api_url = "https://wukong-api.com"
+
+[application]
+name = "wukong"
+enable = true
+
+[[application.namespaces]]
+type = "prod"
+continuous_delivery = false
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_key_with_alias() {
+        assert_eq!(
+            parse_key("wukong-api-url").unwrap(),
+            vec!["core".to_string(), "wukong_api_url".to_string()]
+        );
+        assert!(parse_key("core..wukong_api_url").is_err());
+    }
+
+    #[test]
+    fn test_get_nested_and_array_values() {
+        let config = sample();
+
+        assert_eq!(
+            get(&config, &parse_key("core.wukong_api_url").unwrap()),
+            Some(&Value::String("https://wukong-api.com".to_string()))
+        );
+        assert_eq!(
+            get(
+                &config,
+                &parse_key("application.namespaces.0.type").unwrap()
+            ),
+            Some(&Value::String("prod".to_string()))
+        );
+        assert_eq!(
+            get(&config, &parse_key("application.missing").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_coerces_to_existing_type() {
+        let mut config = sample();
+
+        set(
+            &mut config,
+            &parse_key("application.enable").unwrap(),
+            "false",
+        )
+        .unwrap();
+        assert_eq!(
+            get(&config, &parse_key("application.enable").unwrap()),
+            Some(&Value::Boolean(false))
+        );
+
+        let result = set(
+            &mut config,
+            &parse_key("application.enable").unwrap(),
+            "nope",
+        );
+        assert!(matches!(result, Err(ConfigError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_set_creates_missing_tables() {
+        let mut config = sample();
+
+        set(
+            &mut config,
+            &parse_key("application.addons.elixir_livebook.enable").unwrap(),
+            "true",
+        )
+        .unwrap();
+
+        assert_eq!(
+            get(
+                &config,
+                &parse_key("application.addons.elixir_livebook.enable").unwrap()
+            ),
+            Some(&Value::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn test_unset() {
+        let mut config = sample();
+
+        assert!(unset(
+            &mut config,
+            &parse_key("application.enable").unwrap()
+        ));
+        assert!(!unset(
+            &mut config,
+            &parse_key("application.enable").unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        let mut config = sample();
+        assert!(validate(&config).is_ok());
+
+        set(
+            &mut config,
+            &parse_key("core.wukong_api_url").unwrap(),
+            "not a url",
+        )
+        .unwrap();
+        assert!(matches!(
+            validate(&config),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+
+        let mut config = sample();
+        set(
+            &mut config,
+            &parse_key("application.namespaces.0.type").unwrap(),
+            "dev",
+        )
+        .unwrap();
+        assert!(matches!(
+            validate(&config),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+
+        let mut config = sample();
+        set(
+            &mut config,
+            &parse_key("telemetry.exporter").unwrap(),
+            "jaeger",
+        )
+        .unwrap();
+        assert!(matches!(
+            validate(&config),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_unknown_key() {
+        let typed = sample();
+        let mut raw = sample();
+        assert_eq!(find_unknown_key(&raw, &typed), None);
+
+        set(
+            &mut raw,
+            &parse_key("application.namespaces.0.typo").unwrap(),
+            "x",
+        )
+        .unwrap();
+        assert_eq!(
+            find_unknown_key(&raw, &typed),
+            Some("application.namespaces.0.typo".to_string())
+        );
+    }
+}
@@ -1,15 +1,11 @@
-use crate::{
-    config::Config as CliConfig,
-    error::{ConfigError, WKCliError},
-};
-
-pub fn handle_list() -> Result<bool, WKCliError> {
-    let config = CliConfig::load_from_default_path()?;
-
-    println!(
-        "{}",
-        toml::to_string(&config).map_err(ConfigError::SerializeTomlError)?
-    );
+use crate::error::WKCliError;
+
+use super::document::ConfigDocument;
+
+pub fn handle_list(local: bool) -> Result<bool, WKCliError> {
+    let document = ConfigDocument::load(local)?;
+
+    println!("{}", document.render()?);
 
     Ok(true)
 }
@@ -1,11 +1,15 @@
 use crate::{commands::config::list::handle_list, error::WKCliError};
-use clap::{Args, Subcommand, ValueEnum};
+use clap::{Args, Subcommand};
 
-use self::{get::handle_get, set::handle_set};
+use self::{edit::handle_edit, get::handle_get, set::handle_set, unset::handle_unset};
 
+mod document;
+mod edit;
 mod get;
+mod key_path;
 mod list;
 mod set;
+mod unset;
 
 #[derive(Debug, Args)]
 pub struct Config {
@@ -16,38 +20,53 @@ pub struct Config {
 #[derive(Debug, Subcommand)]
 pub enum ConfigSubcommand {
     /// List the configurations
-    List,
+    List {
+        /// Use the application config (.wukong.toml) instead of the CLI config
+        #[arg(long)]
+        local: bool,
+    },
     /// Set the value of a configuration
     Set {
-        /// The config name
-        #[arg(value_enum)]
-        config_name: ConfigName,
+        /// The config key, as a dotted path, e.g. core.wukong_api_url
+        key: String,
         /// The config value
-        config_value: String,
+        value: String,
+        /// Use the application config (.wukong.toml) instead of the CLI config
+        #[arg(long)]
+        local: bool,
     },
     /// Print the value of a configuration
     Get {
-        /// The config name
-        #[arg(value_enum)]
-        config_name: ConfigName,
+        /// The config key, as a dotted path, e.g. core.wukong_api_url
+        key: String,
+        /// Use the application config (.wukong.toml) instead of the CLI config
+        #[arg(long)]
+        local: bool,
+    },
+    /// Remove a configuration
+    Unset {
+        /// The config key, as a dotted path, e.g. auth.vault
+        key: String,
+        /// Use the application config (.wukong.toml) instead of the CLI config
+        #[arg(long)]
+        local: bool,
+    },
+    /// Open the configuration in your $EDITOR
+    Edit {
+        /// Use the application config (.wukong.toml) instead of the CLI config
+        #[arg(long)]
+        local: bool,
     },
-}
-
-#[derive(Debug, ValueEnum, Clone)]
-pub enum ConfigName {
-    WukongApiUrl,
-    OktaClientId,
 }
 
 impl Config {
     pub fn handle_command(&self) -> Result<bool, WKCliError> {
         match &self.subcommand {
-            ConfigSubcommand::List => handle_list(),
-            ConfigSubcommand::Set {
-                config_name,
-                config_value,
-            } => handle_set(config_name, config_value),
-            ConfigSubcommand::Get { config_name } => handle_get(config_name),
+            ConfigSubcommand::List { local } => handle_list(*local),
+            ConfigSubcommand::Set { key, value, local } => handle_set(key, value, *local),
+            ConfigSubcommand::Get { key, local } => handle_get(key, *local),
+            ConfigSubcommand::Unset { key, local } => handle_unset(key, *local),
+            ConfigSubcommand::Edit { local } => handle_edit(*local),
         }
     }
 }
@@ -0,0 +1,83 @@
+use crate::{
+    auth::credential_store::{GOOGLE_CLOUD_KEYS, OKTA_KEYS, VAULT_KEYS},
+    config::Config as CliConfig,
+    error::{ConfigError, WKCliError},
+};
+
+use super::{document::ConfigDocument, key_path};
+
+pub fn handle_unset(key: &str, local: bool) -> Result<bool, WKCliError> {
+    let segments = key_path::parse_key(key)?;
+    let mut document = ConfigDocument::load(local)?;
+
+    if !key_path::unset(&mut document.value, &segments) {
+        return Err(ConfigError::KeyNotFound {
+            key: segments.join("."),
+        }
+        .into());
+    }
+    document.save()?;
+
+    // the tokens are not part of the document, remove the ones under the unset key
+    // from the credential store as well
+    if !local {
+        let credential_keys = credential_keys_under(&segments);
+        if !credential_keys.is_empty() {
+            CliConfig::default().delete_credentials(&credential_keys)?;
+        }
+    }
+
+    println!("Unset property [{}].", key_path::display_key(&segments));
+
+    Ok(true)
+}
+
+/// The credential store keys of the tokens under the config key, e.g. all the Okta
+/// tokens for `auth.okta`. The token `okta.id_token` is stored for `auth.okta.id_token`.
+fn credential_keys_under(segments: &[String]) -> Vec<&'static str> {
+    [OKTA_KEYS, VAULT_KEYS, GOOGLE_CLOUD_KEYS]
+        .concat()
+        .into_iter()
+        .filter(|credential_key| {
+            let key_segments = std::iter::once("auth")
+                .chain(credential_key.split('.'))
+                .collect::<Vec<_>>();
+
+            segments.len() <= key_segments.len()
+                && segments
+                    .iter()
+                    .zip(&key_segments)
+                    .all(|(segment, key_segment)| segment == key_segment)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::auth::credential_store::{OKTA_ID_TOKEN, VAULT_API_TOKEN};
+
+    fn segments(key: &str) -> Vec<String> {
+        key_path::parse_key(key).unwrap()
+    }
+
+    #[test]
+    fn test_credential_keys_under() {
+        assert_eq!(credential_keys_under(&segments("auth.okta")), OKTA_KEYS);
+        assert_eq!(credential_keys_under(&segments("auth.vault")), VAULT_KEYS);
+        assert_eq!(
+            credential_keys_under(&segments("auth")).len(),
+            OKTA_KEYS.len() + VAULT_KEYS.len() + GOOGLE_CLOUD_KEYS.len()
+        );
+        assert_eq!(
+            credential_keys_under(&segments("auth.okta.id_token")),
+            vec![OKTA_ID_TOKEN]
+        );
+        assert_eq!(
+            credential_keys_under(&segments("auth.vault.api_token")),
+            vec![VAULT_API_TOKEN]
+        );
+        assert!(credential_keys_under(&segments("auth.okta.expiry_time")).is_empty());
+        assert!(credential_keys_under(&segments("core")).is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+mod bundle;
+
+use std::path::Path;
+
+use clap::{Args, Subcommand};
+
+use crate::error::WKCliError;
+
+use self::bundle::handle_bundle;
+
+#[derive(Debug, Args)]
+pub struct Debug {
+    #[command(subcommand)]
+    pub subcommand: DebugSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DebugSubcommand {
+    /// Zip the recent debug logs, the redacted config and the version info for a bug report
+    Bundle {
+        /// The path of the zip file, wukong-debug-<timestamp>.zip by default
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+}
+
+impl Debug {
+    pub fn handle_command(&self) -> Result<bool, WKCliError> {
+        match &self.subcommand {
+            DebugSubcommand::Bundle { output } => handle_bundle(output.as_deref().map(Path::new)),
+        }
+    }
+}
@@ -146,6 +146,7 @@ pub async fn handle_execute(
     namespace: &Option<DeploymentNamespace>,
     version: &Option<DeploymentVersion>,
     artifact: &Option<String>,
+    yes: bool,
 ) -> Result<bool, WKCliError> {
     if namespace.is_none() && version.is_none() && artifact.is_none() {
         println!("Not detecting any flags, entering deployment terminal......");
@@ -335,7 +336,7 @@ pub async fn handle_execute(
 
         println!("Deployment status: {}\n", deployment_status.bold());
 
-        if deployment_status != "SUCCEEDED" {
+        if deployment_status != "SUCCEEDED" && !yes {
             let agree_to_continue = Confirm::with_theme(&ColorfulTheme::default())
                 .with_prompt(
                     format!(
@@ -509,13 +510,20 @@ pub async fn handle_execute(
         },
     }
 
-    if let Ok(edited) = edit::edit_with_builder(
-        &changelog,
-        Builder::new()
-            .prefix("my-temporary-file")
-            .suffix(".md")
-            .rand_bytes(5),
-    ) {
+    // with `--yes`, the generated CHANGELOG is used as it is
+    let edited = if yes {
+        Ok(changelog)
+    } else {
+        edit::edit_with_builder(
+            &changelog,
+            Builder::new()
+                .prefix("my-temporary-file")
+                .suffix(".md")
+                .rand_bytes(5),
+        )
+    };
+
+    if let Ok(edited) = edited {
         // remove all comments
         let cleaned_changelog = edited
             .split('\n')
@@ -527,7 +535,9 @@ pub async fn handle_execute(
         println!("Please review your deployment CHANGELOG before execute it.\n");
         println!("{cleaned_changelog}");
 
-        let agree_to_deploy = if !is_same_build {
+        let agree_to_deploy = if yes {
+            true
+        } else if !is_same_build {
             Confirm::with_theme(&ColorfulTheme::default())
                 .with_prompt("Do you agree to deploy this build ?")
                 .interact()?
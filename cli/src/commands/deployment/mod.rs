@@ -11,10 +11,11 @@ use rollback::handle_rollback;
 
 use crate::WKCliError;
 use clap::{Args, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCompleter;
 
 use self::status::handle_status;
 
-use super::Context;
+use super::{completion::candidates, Context};
 
 #[derive(Debug, Args)]
 pub struct Deployment {
@@ -36,8 +37,11 @@ pub enum DeploymentSubcommand {
         #[arg(long, value_enum)]
         version: Option<DeploymentVersion>,
         /// The build artifact that the deployment will use.
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(candidates::artifacts))]
         artifact: Option<String>,
+        /// Deploy without the confirmations and the CHANGELOG editor, e.g. in CI.
+        #[arg(long, requires_all = ["namespace", "version", "artifact"])]
+        yes: bool,
     },
     /// Rollback the deployment pipeline
     Rollback {
@@ -103,7 +107,8 @@ impl Deployment {
                 namespace,
                 version,
                 artifact,
-            } => handle_execute(context, namespace, version, artifact).await,
+                yes,
+            } => handle_execute(context, namespace, version, artifact, *yes).await,
             DeploymentSubcommand::Rollback { namespace, version } => {
                 handle_rollback(context, namespace, version).await
             }
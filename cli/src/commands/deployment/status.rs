@@ -167,7 +167,7 @@ pub async fn handle_status(
         }
         Ok(ApplicationNamespaceCloudsqlConfig { project_id, .. }) => {
             fetch_loader.set_message("Fetching CloudSQL data ... ");
-            let gcloud_access_token = auth::google_cloud::get_token_or_login(None).await;
+            let gcloud_access_token = auth::google_cloud::get_token_or_login(None).await?;
             let database_metrics = wk_client
                 .fetch_gcloud_database_metrics(&project_id, gcloud_access_token)
                 .await?;
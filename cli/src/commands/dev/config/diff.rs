@@ -1,6 +1,12 @@
-use super::utils::{
-    extract_secret_infos, get_local_config_path, get_secret_config_files, get_updated_configs,
-    vault_token_for,
+use super::{
+    kind::{diff_entries, ConfigKind, KeyChange},
+    mask::{mask_line, mask_value},
+    state::content_hash,
+    utils::{
+        extract_secret_infos, get_local_config_path, get_secret_config_files, get_updated_configs,
+        get_workspace_secret_infos, group_by_application, prefetch_secrets,
+        print_applications_summary, secret_providers_for, vault_token_for, SecretsCache,
+    },
 };
 use crate::{
     commands::{dev::config::utils::make_path_relative, Context},
@@ -13,11 +19,21 @@ use crate::{
 use dialoguer::console::{style, Style};
 use owo_colors::OwoColorize;
 use similar::{ChangeTag, TextDiff};
+use std::{borrow::Cow, collections::BTreeMap};
+use wukong_sdk::secret_extractors::SecretInfo;
 use wukong_telemetry::*;
 use wukong_telemetry_macro::*;
 
 #[wukong_telemetry(command_event = "dev_config_diff")]
-pub async fn handle_config_diff(context: Context) -> Result<bool, WKCliError> {
+pub async fn handle_config_diff(
+    context: Context,
+    reveal: bool,
+    all: bool,
+) -> Result<bool, WKCliError> {
+    if all {
+        return diff_all_applications(&context, reveal).await;
+    }
+
     let loader = new_spinner();
     loader.set_message("🔍 Finding config with annotation");
 
@@ -39,39 +55,120 @@ pub async fn handle_config_diff(context: Context) -> Result<bool, WKCliError> {
 
     let mut config = Config::load_from_default_path()?;
     let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
-    let vault_token = vault_token_for(&extracted_infos, &mut config).await?;
+    let mut secrets_cache = SecretsCache::new();
+    let vault_token = vault_token_for(&mut secrets_cache, &extracted_infos, &mut config).await?;
+    let providers = secret_providers_for(
+        &mut secrets_cache,
+        &extracted_infos,
+        &mut wk_client,
+        &vault_token,
+    )
+    .await?;
 
+    // Comparing local vs remote ....
+    println!("{}", "comparing local config vs remote config...".cyan());
     let updated_configs =
-        get_updated_configs(&mut wk_client, &vault_token, &extracted_infos).await?;
+        get_updated_configs(&providers, &mut secrets_cache, &extracted_infos).await?;
 
     if updated_configs.is_empty() {
         println!("The config file is already up to date with the remote.");
         return Ok(true);
     }
 
-    for (secret_annotation, remote_config, local_config, config_path) in &updated_configs {
+    print_updated_configs(&updated_configs, reveal);
+
+    Ok(true)
+}
+
+/// Show the changes of every application in the workspace, with the secrets they share
+/// fetched once.
+async fn diff_all_applications(context: &Context, reveal: bool) -> Result<bool, WKCliError> {
+    let loader = new_spinner();
+    loader.set_message("🔍 Finding config with annotation");
+
+    let (repo_root, extracted_infos) = get_workspace_secret_infos()?;
+
+    loader.finish_and_clear();
+
+    let mut config = Config::load_from_default_path()?;
+    let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
+    let mut secrets_cache = SecretsCache::new();
+    let vault_token = vault_token_for(&mut secrets_cache, &extracted_infos, &mut config).await?;
+    let providers = secret_providers_for(
+        &mut secrets_cache,
+        &extracted_infos,
+        &mut wk_client,
+        &vault_token,
+    )
+    .await?;
+
+    // the secrets of all the applications are fetched in parallel
+    println!("{}", "comparing local config vs remote config...".cyan());
+    prefetch_secrets(&providers, &mut secrets_cache, &extracted_infos).await;
+    let mut outcomes = Vec::new();
+    let mut has_error = false;
+
+    for application in group_by_application(&repo_root, extracted_infos) {
+        println!();
+        println!("📦 {}", application.name.bold());
+
+        // a failing application is reported in the summary, the other ones are still compared
+        let updated_configs =
+            match get_updated_configs(&providers, &mut secrets_cache, &application.extracted_infos)
+                .await
+            {
+                Ok(updated_configs) => updated_configs,
+                Err(error) => {
+                    eprintln!("{}", error.to_string().red());
+                    outcomes.push((application.name, false, "failed".to_string()));
+                    has_error = true;
+                    continue;
+                }
+            };
+
+        if updated_configs.is_empty() {
+            println!("The config files are already up to date with the remote.");
+            outcomes.push((application.name, true, "up-to-date".to_string()));
+        } else {
+            print_updated_configs(&updated_configs, reveal);
+            let outcome = format!("{} changed", updated_configs.len());
+            outcomes.push((application.name, false, outcome));
+        }
+    }
+
+    print_applications_summary(&outcomes);
+
+    Ok(!has_error)
+}
+
+fn print_updated_configs(updated_configs: &[(&SecretInfo, String, String, String)], reveal: bool) {
+    for (secret_annotation, remote_config, local_config, config_path) in updated_configs {
         let local_config_path =
             get_local_config_path(config_path, &secret_annotation.destination_file);
 
-        print_diff(
+        print_config_diff(
+            ConfigKind::of(secret_annotation),
             remote_config,
             local_config,
             &local_config_path.to_string_lossy(),
+            reveal,
         );
     }
-
-    Ok(true)
 }
 
+/// Whether the configs are different, by comparing their hashes.
 pub fn has_diff(old_secret_config: &str, new_secret_config: &str) -> bool {
-    let changeset = TextDiff::from_lines(old_secret_config, new_secret_config);
-
-    changeset
-        .iter_all_changes()
-        .any(|change| matches!(change.tag(), ChangeTag::Delete | ChangeTag::Insert))
+    content_hash(old_secret_config) != content_hash(new_secret_config)
 }
 
-pub fn print_diff(old_secret_config: &str, new_secret_config: &str, local_config_path: &str) {
+/// Print the changes line by line, with the values masked unless `reveal`.
+pub fn print_diff(
+    old_secret_config: &str,
+    new_secret_config: &str,
+    local_config_path: &str,
+    kind: ConfigKind,
+    reveal: bool,
+) {
     println!();
     println!("{}", make_path_relative(local_config_path).dimmed());
 
@@ -95,12 +192,21 @@ pub fn print_diff(old_secret_config: &str, new_secret_config: &str, local_config
                     style(Line(change.new_index())).dim(),
                     s.apply_to(sign).bold(),
                 );
-                for (emphasized, value) in change.iter_strings_lossy() {
-                    if emphasized {
-                        print!("{}", s.apply_to(value).underlined().on_black());
-                    } else {
-                        print!("{}", s.apply_to(value));
+                if reveal {
+                    for (emphasized, value) in change.iter_strings_lossy() {
+                        if emphasized {
+                            print!("{}", s.apply_to(value).underlined().on_black());
+                        } else {
+                            print!("{}", s.apply_to(value));
+                        }
                     }
+                } else {
+                    // the changed characters aren't emphasized, as they'd give the values away
+                    let line = change
+                        .iter_strings_lossy()
+                        .map(|(_, value)| value)
+                        .collect::<String>();
+                    print!("{}", s.apply_to(mask_line(kind, &line)));
                 }
                 if change.missing_newline() {
                     println!();
@@ -112,6 +218,78 @@ pub fn print_diff(old_secret_config: &str, new_secret_config: &str, local_config
     println!();
 }
 
+/// Print the changes key by key for the kinds made of keys and values, or line by line for
+/// the others, with the values masked unless `reveal`.
+pub fn print_config_diff(
+    kind: ConfigKind,
+    old_secret_config: &str,
+    new_secret_config: &str,
+    local_config_path: &str,
+    reveal: bool,
+) {
+    match (kind.parse(old_secret_config), kind.parse(new_secret_config)) {
+        (Some(old_entries), Some(new_entries)) => {
+            print_key_diff(&old_entries, &new_entries, local_config_path, reveal)
+        }
+        _ => print_diff(
+            old_secret_config,
+            new_secret_config,
+            local_config_path,
+            kind,
+            reveal,
+        ),
+    }
+}
+
+pub fn print_key_diff(
+    old_entries: &BTreeMap<String, String>,
+    new_entries: &BTreeMap<String, String>,
+    local_config_path: &str,
+    reveal: bool,
+) {
+    println!();
+    println!("{}", make_path_relative(local_config_path).dimmed());
+
+    let changes = diff_entries(old_entries, new_entries);
+    if changes.is_empty() {
+        println!(
+            "{}",
+            "Only the comments or the formatting are changed.".dimmed()
+        );
+    }
+
+    for change in changes {
+        match change {
+            KeyChange::Added { key, value } => println!(
+                "{} {key} = {}",
+                Style::new().green().bold().apply_to("+"),
+                Style::new().green().apply_to(shown(value, reveal))
+            ),
+            KeyChange::Removed { key, value } => println!(
+                "{} {key} = {}",
+                Style::new().red().bold().apply_to("-"),
+                Style::new().red().apply_to(shown(value, reveal))
+            ),
+            KeyChange::Changed { key, old, new } => println!(
+                "{} {key} = {} → {}",
+                Style::new().yellow().bold().apply_to("~"),
+                Style::new().red().apply_to(shown(old, reveal)),
+                Style::new().green().apply_to(shown(new, reveal))
+            ),
+        }
+    }
+
+    println!();
+}
+
+fn shown(value: &str, reveal: bool) -> Cow<str> {
+    if reveal {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(mask_value(value))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
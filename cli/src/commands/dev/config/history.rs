@@ -0,0 +1,107 @@
+use owo_colors::OwoColorize;
+
+use crate::{
+    auth::vault, commands::Context, config::Config, error::WKCliError, loader::new_spinner,
+    wukong_client::WKClient,
+};
+
+use super::{
+    diff::{has_diff, print_config_diff},
+    kind::ConfigKind,
+    utils::{find_secret_info, get_local_config_path},
+};
+use wukong_telemetry::*;
+use wukong_telemetry_macro::*;
+
+#[wukong_telemetry(command_event = "dev_config_history")]
+pub async fn handle_config_history(
+    context: Context,
+    key: &str,
+    reveal: bool,
+) -> Result<bool, WKCliError> {
+    let (config_path, secret_info) = find_secret_info(key)?;
+    // without the path, if it was given with it
+    let key = secret_info.name.as_str();
+    let local_config_path = get_local_config_path(&secret_info.destination_file, &config_path);
+
+    let mut config = Config::load_from_default_path()?;
+    let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
+    let vault_token = if secret_info.provider == "bunker" {
+        vault::get_token_or_login(&mut config).await?
+    } else {
+        String::new()
+    };
+    if secret_info.provider == "wukong" {
+        wk_client.check_and_refresh_tokens().await?;
+    }
+    let providers = wk_client.secret_providers(&vault_token);
+
+    let loader = new_spinner();
+    loader.set_message("Fetching the versions ...");
+
+    // every version kept is read, to show what has changed in it (the Bunker keeps the
+    // last 10 versions by default)
+    let revisions = providers
+        .get(&secret_info.provider)?
+        .history(&secret_info.src)
+        .await?;
+    let versions = revisions
+        .into_iter()
+        .rev()
+        .map(|mut revision| {
+            let content = revision.data.take().and_then(|mut data| data.remove(key));
+            (revision, content)
+        })
+        .collect::<Vec<_>>();
+
+    loader.finish_and_clear();
+
+    // the latest first, each one compared with the previous version which can be read
+    for (index, (revision, content)) in versions.iter().enumerate().rev() {
+        let mut labels = Vec::new();
+        if revision.current {
+            labels.push("current");
+        }
+        if revision.destroyed {
+            labels.push("destroyed");
+        } else if revision.deleted {
+            labels.push("deleted");
+        }
+
+        let mut header = format!(
+            "{} {}",
+            format!("Version {}", revision.version).bold(),
+            revision.created_time.dimmed()
+        );
+        if !labels.is_empty() {
+            header.push_str(&format!(" {}", format!("({})", labels.join(", ")).yellow()));
+        }
+
+        println!();
+        println!("{header}");
+
+        let previous = versions[..index]
+            .iter()
+            .rev()
+            .find_map(|(_, content)| content.as_deref());
+
+        match (content, previous) {
+            (None, _) if revision.deleted => {
+                println!("{}", "The content of this version is deleted.".dimmed());
+            }
+            (None, _) => println!("{}", format!("\"{key}\" is not in this version.").dimmed()),
+            (Some(content), Some(previous)) if !has_diff(previous, content) => {
+                println!("{}", format!("No changes to \"{key}\".").dimmed());
+            }
+            (Some(content), previous) => print_config_diff(
+                ConfigKind::of(&secret_info),
+                previous.unwrap_or_default(),
+                content,
+                &local_config_path.to_string_lossy(),
+                reveal,
+            ),
+        }
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,362 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+};
+
+use log::debug;
+use wukong_sdk::secret_extractors::SecretInfo;
+
+/// How the content of a dev config is compared, from the `kind` of its annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigKind {
+    /// `generic`, compared line by line.
+    Text,
+    /// `elixir_config`, compared line by line.
+    Elixir,
+    Dotenv,
+    Json,
+    Yaml,
+}
+
+impl ConfigKind {
+    pub fn of(info: &SecretInfo) -> Self {
+        match info.kind.as_str() {
+            "elixir_config" => Self::Elixir,
+            "dotenv" => Self::Dotenv,
+            "json" => Self::Json,
+            "yaml" => Self::Yaml,
+            // the annotated YAML and JSON configs can load any kind of secrets file
+            "yaml_config" | "json_config" | "template" => Self::of_path(&info.destination_file),
+            _ => Self::Text,
+        }
+    }
+
+    /// The kind of a rendered template or an annotated YAML or JSON config, from the name of
+    /// the file it's written to.
+    fn of_path(path: &str) -> Self {
+        let name = Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        match name.rsplit_once('.').map(|(_, extension)| extension) {
+            _ if name == ".env" || name.starts_with(".env.") || name.ends_with(".env") => {
+                Self::Dotenv
+            }
+            Some("ex" | "exs") => Self::Elixir,
+            Some("json") => Self::Json,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Text,
+        }
+    }
+
+    /// Parse the content into its keys and values, the nested keys of JSON and YAML are
+    /// joined with a `.`, e.g. `database.password`. It's `None` for the text kinds, or if
+    /// the content can't be parsed, in which case it's compared as text.
+    pub fn parse(&self, content: &str) -> Option<BTreeMap<String, String>> {
+        match self {
+            Self::Text | Self::Elixir => None,
+            Self::Dotenv => Some(
+                parse_dotenv(content)
+                    .into_iter()
+                    .map(|(key, value, _)| (key.to_string(), value.to_string()))
+                    .collect(),
+            ),
+            Self::Json => match serde_json::from_str::<serde_json::Value>(content) {
+                Ok(value) => Some(flatten(&value)),
+                Err(error) => {
+                    debug!("Comparing the invalid JSON as text: {:?}", error);
+                    None
+                }
+            },
+            Self::Yaml => match serde_yaml::from_str::<serde_json::Value>(content) {
+                Ok(value) => Some(flatten(&value)),
+                Err(error) => {
+                    debug!("Comparing the invalid YAML as text: {:?}", error);
+                    None
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeyChange<'a> {
+    Added {
+        key: &'a str,
+        value: &'a str,
+    },
+    Removed {
+        key: &'a str,
+        value: &'a str,
+    },
+    Changed {
+        key: &'a str,
+        old: &'a str,
+        new: &'a str,
+    },
+}
+
+/// The keys added, removed and changed from `old` to `new`, sorted by key.
+pub fn diff_entries<'a>(
+    old: &'a BTreeMap<String, String>,
+    new: &'a BTreeMap<String, String>,
+) -> Vec<KeyChange<'a>> {
+    let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+
+    keys.into_iter()
+        .filter_map(|key| match (old.get(key), new.get(key)) {
+            (None, Some(value)) => Some(KeyChange::Added { key, value }),
+            (Some(value), None) => Some(KeyChange::Removed { key, value }),
+            (Some(old), Some(new)) if old != new => Some(KeyChange::Changed { key, old, new }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Merge the `remote` dotenv into the `local` one, without losing the local overrides:
+/// - the keys which only exist locally are kept at the end, unless they've been removed
+///   on the remote since the `base`, i.e. the last pull,
+/// - the values changed locally are kept if they're not changed on the remote since the
+///   `base`,
+/// - otherwise the remote value is taken.
+pub fn merge_dotenv(base: Option<&str>, local: &str, remote: &str) -> String {
+    let base = base.map(|base| {
+        parse_dotenv(base)
+            .into_iter()
+            .map(|(key, value, _)| (key, value))
+            .collect::<HashMap<_, _>>()
+    });
+    let local_entries = parse_dotenv(local);
+    let locals = local_entries
+        .iter()
+        .map(|(key, value, line)| (*key, (*value, *line)))
+        .collect::<HashMap<_, _>>();
+
+    let mut merged = String::new();
+    let mut remote_keys = BTreeSet::new();
+    for remote_line in remote.split_inclusive('\n') {
+        let Some((key, remote_value)) = parse_dotenv_line(remote_line) else {
+            merged.push_str(remote_line);
+            continue;
+        };
+        remote_keys.insert(key);
+
+        let base_value = base.as_ref().and_then(|base| base.get(key));
+        match locals.get(key) {
+            Some((local_value, local_line))
+                if *local_value != remote_value && base_value == Some(&remote_value) =>
+            {
+                merged.push_str(local_line.trim_end_matches(['\r', '\n']));
+                merged.push_str(&remote_line[remote_line.trim_end_matches(['\r', '\n']).len()..]);
+            }
+            _ => merged.push_str(remote_line),
+        }
+    }
+
+    let local_only = local_entries.iter().filter(|(key, _, _)| {
+        !remote_keys.contains(key) && !base.as_ref().is_some_and(|base| base.contains_key(key))
+    });
+    for (_, _, line) in local_only {
+        if !merged.is_empty() && !merged.ends_with('\n') {
+            merged.push('\n');
+        }
+        merged.push_str(line);
+    }
+
+    merged
+}
+
+/// The keys, values and lines of a dotenv, in order. The comments, the empty lines and
+/// the lines without a `=` are skipped.
+fn parse_dotenv(content: &str) -> Vec<(&str, &str, &str)> {
+    content
+        .split_inclusive('\n')
+        .filter_map(|line| parse_dotenv_line(line).map(|(key, value)| (key, value, line)))
+        .collect()
+}
+
+fn parse_dotenv_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return None;
+    }
+
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim();
+    if key.is_empty() {
+        return None;
+    }
+
+    let value = value.trim();
+    let value = ['"', '\'']
+        .iter()
+        .find_map(|quote| {
+            value
+                .strip_prefix(*quote)
+                .and_then(|value| value.strip_suffix(*quote))
+        })
+        .unwrap_or(value);
+
+    Some((key, value))
+}
+
+fn flatten(value: &serde_json::Value) -> BTreeMap<String, String> {
+    let mut entries = BTreeMap::new();
+    flatten_into(&mut entries, String::new(), value);
+
+    entries
+}
+
+fn flatten_into(entries: &mut BTreeMap<String, String>, key: String, value: &serde_json::Value) {
+    let child_key = |child: &str| {
+        if key.is_empty() {
+            child.to_string()
+        } else {
+            format!("{key}.{child}")
+        }
+    };
+
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            for (child, value) in map {
+                flatten_into(entries, child_key(child), value);
+            }
+        }
+        serde_json::Value::Array(items) if !items.is_empty() => {
+            for (index, value) in items.iter().enumerate() {
+                flatten_into(entries, child_key(&index.to_string()), value);
+            }
+        }
+        // an empty document
+        serde_json::Value::Null if key.is_empty() => {}
+        serde_json::Value::String(value) => {
+            entries.insert(key, value.clone());
+        }
+        value => {
+            entries.insert(key, value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entries(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_kind_of_path() {
+        assert_eq!(ConfigKind::of_path(".env"), ConfigKind::Dotenv);
+        assert_eq!(
+            ConfigKind::of_path("apps/api/.env.local"),
+            ConfigKind::Dotenv
+        );
+        assert_eq!(
+            ConfigKind::of_path("config/dev.secret.exs"),
+            ConfigKind::Elixir
+        );
+        assert_eq!(ConfigKind::of_path("settings.json"), ConfigKind::Json);
+        assert_eq!(ConfigKind::of_path("values.yml"), ConfigKind::Yaml);
+        assert_eq!(
+            ConfigKind::of_path("priv/files/kubeconfig"),
+            ConfigKind::Text
+        );
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = "# the database\nDB_USER=admin\nexport DB_PASS=\"p@ss=word\"\n\nAPI_KEY='abc'\nnot a key\nEMPTY=\n";
+
+        assert_eq!(
+            ConfigKind::Dotenv.parse(content).unwrap(),
+            entries(&[
+                ("API_KEY", "abc"),
+                ("DB_PASS", "p@ss=word"),
+                ("DB_USER", "admin"),
+                ("EMPTY", ""),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_json_and_yaml() {
+        let json =
+            r#"{"database": {"user": "admin", "port": 5432}, "hosts": ["a", "b"], "debug": false}"#;
+        let yaml = "database:\n  user: admin\n  port: 5432\nhosts:\n  - a\n  - b\ndebug: false\n";
+        let expected = entries(&[
+            ("database.port", "5432"),
+            ("database.user", "admin"),
+            ("debug", "false"),
+            ("hosts.0", "a"),
+            ("hosts.1", "b"),
+        ]);
+
+        assert_eq!(ConfigKind::Json.parse(json).unwrap(), expected);
+        assert_eq!(ConfigKind::Yaml.parse(yaml).unwrap(), expected);
+        assert!(ConfigKind::Json.parse("{ invalid").is_none());
+        assert!(ConfigKind::Text.parse("A=1").is_none());
+    }
+
+    #[test]
+    fn test_diff_entries() {
+        let old = entries(&[("A", "1"), ("B", "2"), ("C", "3")]);
+        let new = entries(&[("A", "1"), ("B", "20"), ("D", "4")]);
+
+        assert_eq!(
+            diff_entries(&old, &new),
+            vec![
+                KeyChange::Changed {
+                    key: "B",
+                    old: "2",
+                    new: "20"
+                },
+                KeyChange::Removed {
+                    key: "C",
+                    value: "3"
+                },
+                KeyChange::Added {
+                    key: "D",
+                    value: "4"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_dotenv_keeps_the_local_only_keys() {
+        let local = "A=1\nLOCAL=yes\nB=2\n";
+        let remote = "A=10\nB=2\nC=3\n";
+
+        assert_eq!(
+            merge_dotenv(None, local, remote),
+            "A=10\nB=2\nC=3\nLOCAL=yes\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_dotenv_with_base() {
+        let base = "A=1\nB=2\nGONE=x\n";
+        // B is changed locally, GONE is removed on the remote
+        let local = "A=1\nB=20\nGONE=x\nLOCAL=yes";
+        let remote = "# comment\nA=10\nB=2\n";
+
+        assert_eq!(
+            merge_dotenv(Some(base), local, remote),
+            "# comment\nA=10\nB=20\nLOCAL=yes"
+        );
+
+        // the remote wins when both have changed the value
+        let remote = "A=1\nB=30\n";
+        assert_eq!(
+            merge_dotenv(Some(base), local, remote),
+            "A=1\nB=30\nLOCAL=yes"
+        );
+    }
+}
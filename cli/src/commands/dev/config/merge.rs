@@ -0,0 +1,402 @@
+use std::ops::Range;
+
+use dialoguer::{theme::ColorfulTheme, Select};
+use owo_colors::OwoColorize;
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+use super::{kind::ConfigKind, mask::mask_line};
+use crate::error::{DevConfigError, WKCliError};
+
+/// A part of the result of a three-way merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeChunk {
+    /// The lines which are merged cleanly.
+    Resolved(Vec<String>),
+    /// The lines changed differently on both sides since the base.
+    Conflict {
+        base: Vec<String>,
+        local: Vec<String>,
+        remote: Vec<String>,
+    },
+}
+
+/// How to resolve a [MergeChunk::Conflict].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Local,
+    Remote,
+    Both,
+}
+
+// a change of one side, the lines `base` of the base are replaced with the lines `side`
+#[derive(Debug)]
+struct Hunk {
+    base: Range<usize>,
+    side: Range<usize>,
+}
+
+fn split_lines(content: &str) -> Vec<&str> {
+    content.split_inclusive('\n').collect()
+}
+
+fn hunks(base: &[&str], side: &[&str]) -> Vec<Hunk> {
+    capture_diff_slices(Algorithm::Myers, base, side)
+        .iter()
+        .map(|op| op.as_tag_tuple())
+        .filter(|(tag, _, _)| *tag != DiffTag::Equal)
+        .map(|(_, base, side)| Hunk { base, side })
+        .collect()
+}
+
+fn to_owned_lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+// the lines of a side covering the lines `range` of the base, given the hunks of the side
+// in that range, the lines around the hunks being the same as the base
+fn side_lines<'a>(side: &[&'a str], hunks: &[&Hunk], range: &Range<usize>) -> Vec<&'a str> {
+    let (first, last) = (hunks.first().unwrap(), hunks.last().unwrap());
+    let start = first.side.start - (first.base.start - range.start);
+    let end = last.side.end + (range.end - last.base.end);
+
+    side[start..end].to_vec()
+}
+
+/// Three-way merge the lines of `local` and `remote`, which are both changed from `base`.
+///
+/// The changes of one side only are taken as they are, the same change on both sides is
+/// taken once, and the changes overlapping (or touching) each other are conflicts, the same
+/// way as `git merge` does.
+pub fn merge(base: &str, local: &str, remote: &str) -> Vec<MergeChunk> {
+    let base_lines = split_lines(base);
+    let local_lines = split_lines(local);
+    let remote_lines = split_lines(remote);
+
+    let local_hunks = hunks(&base_lines, &local_lines);
+    let remote_hunks = hunks(&base_lines, &remote_lines);
+
+    let mut chunks = Vec::new();
+    let mut resolved = Vec::new();
+    let mut position = 0;
+    let (mut i, mut j) = (0, 0);
+
+    loop {
+        let next_local = local_hunks.get(i).map(|hunk| hunk.base.start);
+        let next_remote = remote_hunks.get(j).map(|hunk| hunk.base.start);
+        let start = match (next_local, next_remote) {
+            (Some(local), Some(remote)) => local.min(remote),
+            (Some(local), None) => local,
+            (None, Some(remote)) => remote,
+            (None, None) => break,
+        };
+
+        resolved.extend(to_owned_lines(&base_lines[position..start]));
+
+        // the group of the hunks of both sides overlapping each other
+        let mut range = start..start;
+        let (mut group_local, mut group_remote): (Vec<&Hunk>, Vec<&Hunk>) = (vec![], vec![]);
+        loop {
+            if let Some(hunk) = local_hunks
+                .get(i)
+                .filter(|hunk| hunk.base.start <= range.end)
+            {
+                range.end = range.end.max(hunk.base.end);
+                group_local.push(hunk);
+                i += 1;
+            } else if let Some(hunk) = remote_hunks
+                .get(j)
+                .filter(|hunk| hunk.base.start <= range.end)
+            {
+                range.end = range.end.max(hunk.base.end);
+                group_remote.push(hunk);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        if group_remote.is_empty() {
+            resolved.extend(to_owned_lines(&side_lines(
+                &local_lines,
+                &group_local,
+                &range,
+            )));
+        } else if group_local.is_empty() {
+            resolved.extend(to_owned_lines(&side_lines(
+                &remote_lines,
+                &group_remote,
+                &range,
+            )));
+        } else {
+            let local = side_lines(&local_lines, &group_local, &range);
+            let remote = side_lines(&remote_lines, &group_remote, &range);
+
+            if local == remote {
+                resolved.extend(to_owned_lines(&local));
+            } else {
+                if !resolved.is_empty() {
+                    chunks.push(MergeChunk::Resolved(std::mem::take(&mut resolved)));
+                }
+                chunks.push(MergeChunk::Conflict {
+                    base: to_owned_lines(&base_lines[range.clone()]),
+                    local: to_owned_lines(&local),
+                    remote: to_owned_lines(&remote),
+                });
+            }
+        }
+
+        position = range.end;
+    }
+
+    resolved.extend(to_owned_lines(&base_lines[position..]));
+    if !resolved.is_empty() {
+        chunks.push(MergeChunk::Resolved(resolved));
+    }
+
+    chunks
+}
+
+/// The number of the conflicts in the merged chunks.
+pub fn conflict_count(chunks: &[MergeChunk]) -> usize {
+    chunks
+        .iter()
+        .filter(|chunk| matches!(chunk, MergeChunk::Conflict { .. }))
+        .count()
+}
+
+/// Join the merged chunks into the content, resolving each conflict with `resolve`.
+pub fn resolve<F>(chunks: &[MergeChunk], mut resolve: F) -> Result<String, WKCliError>
+where
+    F: FnMut(&[String], &[String], &[String]) -> Result<Resolution, WKCliError>,
+{
+    let mut content = String::new();
+
+    for chunk in chunks {
+        match chunk {
+            MergeChunk::Resolved(lines) => content.extend(lines.iter().map(String::as_str)),
+            MergeChunk::Conflict {
+                base,
+                local,
+                remote,
+            } => {
+                let lines = match resolve(base, local, remote)? {
+                    Resolution::Local => local.clone(),
+                    Resolution::Remote => remote.clone(),
+                    Resolution::Both => {
+                        // so the last local line doesn't run onto the first remote line
+                        let mut lines = local.clone();
+                        if let Some(last) = lines.last_mut() {
+                            if !last.ends_with('\n') && !remote.is_empty() {
+                                last.push('\n');
+                            }
+                        }
+                        lines.extend(remote.iter().cloned());
+                        lines
+                    }
+                };
+                content.extend(lines.iter().map(String::as_str));
+            }
+        }
+    }
+
+    Ok(content)
+}
+
+/// Merge `local` and `remote` changed from `base`, asking how to resolve each conflict,
+/// or failing on the first conflict if `no_input` is set. The conflicting lines are shown
+/// with the values masked unless `reveal`.
+pub fn merge_interactively(
+    base: &str,
+    local: &str,
+    remote: &str,
+    config_path: &str,
+    kind: ConfigKind,
+    no_input: bool,
+    reveal: bool,
+) -> Result<String, WKCliError> {
+    let chunks = merge(base, local, remote);
+    let conflicts = conflict_count(&chunks);
+
+    if conflicts == 0 {
+        return resolve(&chunks, |_, _, _| unreachable!());
+    }
+
+    if no_input {
+        return Err(WKCliError::DevConfigError(DevConfigError::MergeConflict {
+            config_path: config_path.to_string(),
+            conflicts,
+        }));
+    }
+
+    eprintln!(
+        "{}",
+        format!("{conflicts} conflict(s) found in {config_path}").bright_yellow()
+    );
+
+    let mut current = 0;
+    resolve(&chunks, |base, local, remote| {
+        current += 1;
+        eprintln!();
+        eprintln!("{}", format!("Conflict {current}/{conflicts}").bold());
+        print_conflict(base, local, remote, kind, reveal);
+
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Which change do you want to keep?")
+            .items(&[
+                "Keep my local change",
+                "Keep the remote change",
+                "Keep both, the local change first",
+            ])
+            .default(0)
+            .interact_opt()?;
+
+        match selection {
+            Some(0) => Ok(Resolution::Local),
+            Some(1) => Ok(Resolution::Remote),
+            Some(_) => Ok(Resolution::Both),
+            None => Err(WKCliError::DevConfigError(DevConfigError::MergeConflict {
+                config_path: config_path.to_string(),
+                conflicts,
+            })),
+        }
+    })
+}
+
+fn print_conflict(
+    base: &[String],
+    local: &[String],
+    remote: &[String],
+    kind: ConfigKind,
+    reveal: bool,
+) {
+    let print_lines = |lines: &[String]| {
+        for line in lines {
+            let line = if reveal {
+                line.to_string()
+            } else {
+                mask_line(kind, line)
+            };
+            eprintln!("{}", line.trim_end_matches('\n'));
+        }
+    };
+
+    eprintln!("{}", "<<<<<<< local".red());
+    print_lines(local);
+    eprintln!("{}", "||||||| base".dimmed());
+    print_lines(base);
+    eprintln!("{}", "=======".dimmed());
+    print_lines(remote);
+    eprintln!("{}", ">>>>>>> remote".green());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn merged(base: &str, local: &str, remote: &str) -> Option<String> {
+        let chunks = merge(base, local, remote);
+        if conflict_count(&chunks) > 0 {
+            return None;
+        }
+        Some(resolve(&chunks, |_, _, _| unreachable!()).unwrap())
+    }
+
+    #[test]
+    fn test_merge_changes_on_different_lines() {
+        let base = "A=1\nB=2\nC=3\nD=4\nE=5\n";
+        let local = "A=1\nB=local\nC=3\nD=4\nE=5\n";
+        let remote = "A=1\nB=2\nC=3\nD=4\nE=remote\nF=6\n";
+
+        assert_eq!(
+            merged(base, local, remote).unwrap(),
+            "A=1\nB=local\nC=3\nD=4\nE=remote\nF=6\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_only_one_side_changed() {
+        let base = "A=1\nB=2\n";
+        let changed = "A=1\nB=3\nC=4\n";
+
+        assert_eq!(merged(base, changed, base).unwrap(), changed);
+        assert_eq!(merged(base, base, changed).unwrap(), changed);
+    }
+
+    #[test]
+    fn test_merge_same_change_on_both_sides() {
+        let base = "A=1\nB=2\nC=3\n";
+        let changed = "A=1\nB=changed\nC=3\n";
+
+        assert_eq!(merged(base, changed, changed).unwrap(), changed);
+    }
+
+    #[test]
+    fn test_merge_deletion_and_change() {
+        let base = "A=1\nB=2\nC=3\nD=4\nE=5\n";
+        let local = "A=1\nC=3\nD=4\nE=5\n";
+        let remote = "A=1\nB=2\nC=3\nD=4\nE=changed\n";
+
+        assert_eq!(
+            merged(base, local, remote).unwrap(),
+            "A=1\nC=3\nD=4\nE=changed\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let base = "A=1\nB=2\nC=3\n";
+        let local = "A=1\nB=local\nC=3\n";
+        let remote = "A=1\nB=remote\nC=3\n";
+
+        let chunks = merge(base, local, remote);
+        assert_eq!(
+            chunks,
+            vec![
+                MergeChunk::Resolved(vec!["A=1\n".to_string()]),
+                MergeChunk::Conflict {
+                    base: vec!["B=2\n".to_string()],
+                    local: vec!["B=local\n".to_string()],
+                    remote: vec!["B=remote\n".to_string()],
+                },
+                MergeChunk::Resolved(vec!["C=3\n".to_string()]),
+            ]
+        );
+
+        let resolved = |resolution| resolve(&chunks, |_, _, _| Ok(resolution)).unwrap();
+        assert_eq!(resolved(Resolution::Local), local);
+        assert_eq!(resolved(Resolution::Remote), remote);
+        assert_eq!(resolved(Resolution::Both), "A=1\nB=local\nB=remote\nC=3\n");
+    }
+
+    #[test]
+    fn test_merge_both_appended_at_the_end() {
+        let base = "A=1\n";
+        let local = "A=1\nB=local\n";
+        let remote = "A=1\nC=remote\n";
+
+        let chunks = merge(base, local, remote);
+        assert_eq!(conflict_count(&chunks), 1);
+        assert_eq!(
+            resolve(&chunks, |_, _, _| Ok(Resolution::Both)).unwrap(),
+            "A=1\nB=local\nC=remote\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_interactively_fails_on_conflicts_without_input() {
+        let result = merge_interactively("A=1\n", "A=2\n", "A=3\n", ".env", true);
+
+        assert!(matches!(
+            result,
+            Err(WKCliError::DevConfigError(DevConfigError::MergeConflict {
+                conflicts: 1,
+                ..
+            }))
+        ));
+        assert_eq!(
+            merge_interactively("A=1\nB=2\n", "A=2\nB=2\n", "A=1\nB=2\nC=3\n", ".env", true)
+                .unwrap(),
+            "A=2\nB=2\nC=3\n"
+        );
+    }
+}
@@ -1,13 +1,28 @@
 mod diff;
+mod history;
+mod kind;
 mod lint;
+mod mask;
+mod merge;
 mod pull;
 mod push;
+mod restore;
+mod rotate;
+mod show;
+mod state;
+mod status;
+mod template;
 mod utils;
 
 use diff::handle_config_diff;
+use history::handle_config_history;
 use lint::handle_config_lint;
 use pull::handle_config_pull;
 use push::handle_config_push;
+use restore::handle_config_restore;
+use rotate::handle_config_rotate;
+use show::handle_config_show;
+use status::handle_config_status;
 
 use crate::{
     commands::{get_context_without_application, ClapApp},
@@ -25,14 +40,44 @@ pub struct Config {
 #[derive(Debug, Subcommand)]
 pub enum ConfigSubcommand {
     /// Push the current configuration changes to the Bunker.
-    Push,
+    Push {
+        /// Push all the changes without prompting, and fail on the merge conflicts
+        #[arg(long)]
+        no_input: bool,
+
+        /// Show the values in the changes instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
     /// Show changes between the local configuration and the Bunker.
-    Diff,
+    Diff {
+        /// Show the values in the changes instead of masking them
+        #[arg(long)]
+        reveal: bool,
+        /// Every application under the root of the git repository, instead of the current directory
+        #[arg(long)]
+        all: bool,
+    },
     /// Pull the development config file from Bunker.
     Pull {
         /// The path to the project
         #[arg(default_value = ".")]
         path: PathBuf,
+
+        /// Merge the local changes without prompting, and skip the files with merge conflicts
+        #[arg(long)]
+        no_input: bool,
+
+        /// Show the values in the merge conflicts instead of masking them
+        #[arg(long)]
+        reveal: bool,
+
+        /// Every application under the root of the git repository, without giving the path
+        ///
+        /// The secrets of all the applications are fetched in parallel, but the applications
+        /// are pulled one by one, as merging the local changes may prompt.
+        #[arg(long)]
+        all: bool,
     },
     /// Linting the config and show possible warnings, as well as suggestion how to fix the config file.
     Lint {
@@ -40,21 +85,155 @@ pub enum ConfigSubcommand {
         #[arg(default_value = ".")]
         path: PathBuf,
     },
+    /// Show the versions of a secret kept in the Bunker, with the changes made in each one.
+    History {
+        /// The secret, the key after `#` in the annotation, e.g. `dotenv`, with its path if several secrets have this key
+        key: String,
+
+        /// Show the values in the changes instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Print a secret from the Bunker.
+    Show {
+        /// The secret, the key after `#` in the annotation, e.g. `dotenv`, with its path if several secrets have this key
+        key: String,
+
+        /// Print this version instead of the latest one
+        #[arg(long)]
+        version: Option<u64>,
+    },
+    /// Restore a secret in the Bunker to one of its previous versions.
+    Restore {
+        /// The secret, the key after `#` in the annotation, e.g. `dotenv`, with its path if several secrets have this key
+        key: String,
+
+        /// The version to restore
+        #[arg(long)]
+        version: u64,
+
+        /// Restore without asking for confirmation
+        #[arg(long)]
+        no_input: bool,
+
+        /// Show the values in the changes instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Rotate a secret, and pull the config files in this workspace written from it.
+    Rotate {
+        /// The secret, e.g. `bunker:mv/tech/app/dev#DB_PASS` or `vault:secret/mv/tech/app/dev#DB_PASS`
+        secret: String,
+
+        /// The new value, a random one is generated if it's not given
+        #[arg(long)]
+        value: Option<String>,
+
+        /// Also rotate the key in this secret kept by the Wukong API, e.g. my-app/prod/db, can be given several times
+        #[arg(long, value_name = "APPLICATION/NAMESPACE/PATH")]
+        also_wukong: Vec<String>,
+
+        /// Rotate without asking for confirmation, and without pulling the config files unless --pull
+        #[arg(long)]
+        no_input: bool,
+
+        /// Pull the config files written from the secret without asking
+        #[arg(long)]
+        pull: bool,
+
+        /// Show the generated value, and the values in the merge conflicts
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Check whether the local config files are up to date with the Bunker.
+    ///
+    /// Nothing is written. It exits with 2 if any config file is stale or missing, e.g. to
+    /// warn about it in CI or in a git pre-commit hook, and with 1 if the check fails.
+    Status {
+        /// The path to the project
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Every application under the root of the git repository, without giving the path
+        #[arg(long)]
+        all: bool,
+    },
 }
 
 impl Config {
     pub async fn handle_command(&self, clap_app: &ClapApp) -> Result<bool, WKCliError> {
         match &self.subcommand {
-            ConfigSubcommand::Push => {
-                handle_config_push(get_context_without_application(clap_app)?).await
+            ConfigSubcommand::Push { no_input, reveal } => {
+                handle_config_push(
+                    get_context_without_application(clap_app)?,
+                    *no_input,
+                    *reveal,
+                )
+                .await
             }
-            ConfigSubcommand::Diff => {
-                handle_config_diff(get_context_without_application(clap_app)?).await
+            ConfigSubcommand::Diff { reveal, all } => {
+                handle_config_diff(get_context_without_application(clap_app)?, *reveal, *all).await
             }
-            ConfigSubcommand::Pull { path } => {
-                handle_config_pull(get_context_without_application(clap_app)?, path).await
+            ConfigSubcommand::Pull {
+                path,
+                no_input,
+                reveal,
+                all,
+            } => {
+                handle_config_pull(
+                    get_context_without_application(clap_app)?,
+                    path,
+                    *no_input,
+                    *reveal,
+                    *all,
+                )
+                .await
             }
             ConfigSubcommand::Lint { path } => handle_config_lint(path),
+            ConfigSubcommand::History { key, reveal } => {
+                handle_config_history(get_context_without_application(clap_app)?, key, *reveal)
+                    .await
+            }
+            ConfigSubcommand::Show { key, version } => {
+                handle_config_show(get_context_without_application(clap_app)?, key, *version).await
+            }
+            ConfigSubcommand::Restore {
+                key,
+                version,
+                no_input,
+                reveal,
+            } => {
+                handle_config_restore(
+                    get_context_without_application(clap_app)?,
+                    key,
+                    *version,
+                    *no_input,
+                    *reveal,
+                )
+                .await
+            }
+            ConfigSubcommand::Rotate {
+                secret,
+                value,
+                also_wukong,
+                no_input,
+                pull,
+                reveal,
+            } => {
+                handle_config_rotate(
+                    get_context_without_application(clap_app)?,
+                    secret,
+                    value.as_deref(),
+                    also_wukong,
+                    *no_input,
+                    *pull,
+                    *reveal,
+                )
+                .await
+            }
+            ConfigSubcommand::Status { path, all } => {
+                handle_config_status(get_context_without_application(clap_app)?, path, *all).await
+            }
         }
     }
 }
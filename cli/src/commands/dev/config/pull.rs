@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashSet,
     env::current_dir,
     fs::{File, OpenOptions},
     io::{ErrorKind, Write},
@@ -8,18 +8,23 @@ use std::{
 
 use log::debug;
 use owo_colors::OwoColorize;
-use wukong_sdk::services::vault::client::FetchSecretsData;
+use wukong_sdk::secret_extractors::{SecretInfo, SecretProviders};
 
 use crate::{
     commands::{dev::config::utils::get_local_config_path, Context},
     config::Config,
-    error::WKCliError,
+    error::{DevConfigError, WKCliError},
     wukong_client::WKClient,
 };
 
 use super::diff::has_diff;
+use super::kind::{merge_dotenv, ConfigKind};
+use super::merge::merge_interactively;
+use super::state::{state_key, ConfigState};
 use super::utils::{
-    extract_secret_infos, get_secret_config_files, parse_wukong_src, vault_token_for,
+    extract_secret_infos, get_remote_config, get_secret_config_files, get_workspace_secret_infos,
+    group_by_application, prefetch_secrets, print_applications_summary, secret_providers_for,
+    vault_token_for, SecretsCache,
 };
 use wukong_telemetry::*;
 use wukong_telemetry_macro::*;
@@ -32,7 +37,23 @@ const BACKUP_SUFFIX: &str = ".bak";
 const BACKUP_GITIGNORE_PATTERN: &str = "*.bak";
 
 #[wukong_telemetry(command_event = "dev_config_pull")]
-pub async fn handle_config_pull(context: Context, path: &Path) -> Result<bool, WKCliError> {
+pub async fn handle_config_pull(
+    context: Context,
+    path: &Path,
+    no_input: bool,
+    reveal: bool,
+    all: bool,
+) -> Result<bool, WKCliError> {
+    if all {
+        if path != Path::new(".") {
+            return Err(WKCliError::DevConfigError(DevConfigError::PathWithAll {
+                path: path.to_string_lossy().to_string(),
+            }));
+        }
+
+        return pull_all_applications(&context, no_input, reveal).await;
+    }
+
     let path = path.try_exists().map(|value| match value {
         true => {
             if path.to_string_lossy() == "." {
@@ -52,31 +73,112 @@ pub async fn handle_config_pull(context: Context, path: &Path) -> Result<bool, W
 
     let mut config = Config::load_from_default_path()?;
     let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
-    let vault_token = vault_token_for(&extracted_infos, &mut config).await?;
+    let mut secrets_cache = SecretsCache::new();
+    let vault_token = vault_token_for(&mut secrets_cache, &extracted_infos, &mut config).await?;
+    let providers = secret_providers_for(
+        &mut secrets_cache,
+        &extracted_infos,
+        &mut wk_client,
+        &vault_token,
+    )
+    .await?;
+
+    pull_config_files(
+        &providers,
+        &mut secrets_cache,
+        extracted_infos,
+        no_input,
+        reveal,
+    )
+    .await
+}
 
+/// Pull the config files of every application in the workspace, with the secrets they share
+/// fetched once.
+async fn pull_all_applications(
+    context: &Context,
+    no_input: bool,
+    reveal: bool,
+) -> Result<bool, WKCliError> {
+    let (repo_root, extracted_infos) = get_workspace_secret_infos()?;
+
+    let mut config = Config::load_from_default_path()?;
+    let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
+    let mut secrets_cache = SecretsCache::new();
+    let vault_token = vault_token_for(&mut secrets_cache, &extracted_infos, &mut config).await?;
+    let providers = secret_providers_for(
+        &mut secrets_cache,
+        &extracted_infos,
+        &mut wk_client,
+        &vault_token,
+    )
+    .await?;
+
+    // the secrets of all the applications are fetched in parallel, then the applications are
+    // pulled one by one, as the merges may prompt
+    prefetch_secrets(&providers, &mut secrets_cache, &extracted_infos).await;
+    let mut outcomes = Vec::new();
+
+    for application in group_by_application(&repo_root, extracted_infos) {
+        eprintln!();
+        eprintln!("📦 {}", application.name.bold());
+
+        // an application that fails doesn't stop the ones after it
+        let pulled = match pull_config_files(
+            &providers,
+            &mut secrets_cache,
+            application.extracted_infos,
+            no_input,
+            reveal,
+        )
+        .await
+        {
+            Ok(pulled) => pulled,
+            Err(err) => {
+                eprintln!("{}", err.to_string().red());
+                false
+            }
+        };
+        let outcome = if pulled { "pulled" } else { "failed" };
+        outcomes.push((application.name, pulled, outcome.to_string()));
+    }
+
+    print_applications_summary(&outcomes);
+
+    Ok(outcomes.iter().all(|(_, pulled, _)| *pulled))
+}
+
+/// Write the config files of the extracted entries, merged with the local changes not pushed
+/// yet, with the values in the merge conflicts masked unless `reveal`. Returns whether every
+/// one of them is written.
+pub async fn pull_config_files(
+    providers: &SecretProviders<'_>,
+    secrets_cache: &mut SecretsCache,
+    extracted_infos: Vec<(String, Vec<SecretInfo>)>,
+    no_input: bool,
+    reveal: bool,
+) -> Result<bool, WKCliError> {
     let mut has_error = false;
-    let mut secrets_cache: HashMap<(String, String), FetchSecretsData> = HashMap::new();
     // Tracks which `.gitignore` files we've already touched this run so we
     // don't re-read/append them per annotation.
     let mut gitignored_dirs: HashSet<PathBuf> = HashSet::new();
+    let mut state = ConfigState::load();
 
     for info in extracted_infos {
         eprintln!();
         eprintln!("🔍 {} annotation(s) found in {}", info.1.len(), info.0);
 
         for annotation in info.1 {
-            let source_path = annotation.src.clone();
             let destination_path = annotation.destination_file.clone();
-            let cache_key = (annotation.provider.clone(), source_path.clone());
 
             let file_path = get_local_config_path(&destination_path, &info.0);
 
-            // cache the secrets so we don't call the remote multiple times for the
-            // same (provider, path)
-            let secret = match secrets_cache.get(&cache_key) {
-                Some(secrets) => match secrets.data.get(&annotation.name) {
-                    Some(secret) => secret.to_string(),
-                    None => {
+            // the secrets are cached so we don't call the remote multiple times for the
+            // same (provider, path), a template is rendered with the secrets it references
+            let secret =
+                match get_remote_config(providers, secrets_cache, &annotation, &info.0).await {
+                    Ok(Some(secret)) => secret,
+                    Ok(None) => {
                         debug!("Secret not found: {:?}", annotation.name);
                         eprintln!(
                             "\t{} {} {} {}",
@@ -88,53 +190,19 @@ pub async fn handle_config_pull(context: Context, path: &Path) -> Result<bool, W
                         has_error = true;
                         continue;
                     }
-                },
-                None => {
-                    let fetch_result = if annotation.provider == "wukong" {
-                        let (app, ns, path) = parse_wukong_src(&source_path);
-                        wk_client.get_wukong_secrets(&app, &ns, &path).await
-                    } else {
-                        wk_client.get_secrets(&vault_token, &source_path).await
-                    };
-                    let secrets = match fetch_result {
-                        Ok(secrets) => secrets,
-                        Err(err) => {
-                            debug!("Error while fetching secrets: {:?}", &source_path);
-                            eprintln!(
-                                "\t{} {} {} {}",
-                                "Not created".red(),
-                                file_path.to_string_lossy(),
-                                "because".bold(),
-                                err.bold().red()
-                            );
-                            has_error = true;
-                            continue;
-                        }
-                    };
-                    secrets_cache.insert(cache_key.clone(), secrets);
-
-                    match secrets_cache
-                        .get(&cache_key)
-                        .unwrap()
-                        .data
-                        .get(&annotation.name)
-                    {
-                        Some(secret) => secret.to_string(),
-                        None => {
-                            debug!("Secret not found: {:?}", annotation.name);
-                            eprintln!(
-                                "\t{} {} {} {}",
-                                "Not created".red(),
-                                file_path.to_string_lossy(),
-                                "because".bold(),
-                                "Secret not found".bold().red()
-                            );
-                            has_error = true;
-                            continue;
-                        }
+                    Err(err) => {
+                        debug!("Error while fetching secrets: {:?}", &annotation.src);
+                        eprintln!(
+                            "\t{} {} {} {}",
+                            "Not created".red(),
+                            file_path.to_string_lossy(),
+                            "because".bold(),
+                            err.bold().red()
+                        );
+                        has_error = true;
+                        continue;
                     }
-                }
-            };
+                };
 
             if destination_path.contains('/') {
                 let dir_path = file_path.parent().unwrap();
@@ -152,11 +220,49 @@ pub async fn handle_config_pull(context: Context, path: &Path) -> Result<bool, W
                 };
             }
 
+            // The local changes not pushed yet are kept, merged with the changes
+            // made on the remote since the last pull. A dotenv is merged key by key,
+            // so the local-only keys are kept as well.
+            let key = state_key(&annotation, &file_path);
+            let content = match (state.base(&key), std::fs::read_to_string(&file_path)) {
+                (base, Ok(local)) if ConfigKind::of(&annotation) == ConfigKind::Dotenv => {
+                    merge_dotenv(base.map(|base| base.content.as_str()), &local, &secret)
+                }
+                (Some(base), Ok(local))
+                    if has_diff(&base.content, &local) && has_diff(&local, &secret) =>
+                {
+                    match merge_interactively(
+                        &base.content,
+                        &local,
+                        &secret,
+                        &file_path.to_string_lossy(),
+                        ConfigKind::of(&annotation),
+                        no_input,
+                        reveal,
+                    ) {
+                        Ok(merged) => merged,
+                        Err(err) => {
+                            debug!("Error while merging the local changes: {:?}", err);
+                            eprintln!(
+                                "\t{} {} {} {}",
+                                "Not updated".red(),
+                                file_path.to_string_lossy(),
+                                "because".bold(),
+                                err.to_string().bold().red()
+                            );
+                            has_error = true;
+                            continue;
+                        }
+                    }
+                }
+                _ => secret.clone(),
+            };
+
             // Snapshot the previous version before we overwrite it, but only
             // when the file actually exists and its content differs from what
             // we're about to write. First-pull (file absent) and no-op pull
             // (identical content) both skip backup.
-            if let Some(backup_path) = backup_existing_if_changed(&file_path, &secret) {
+            if let Some(backup_path) = backup_existing_if_changed(&file_path, &content) {
                 eprintln!(
                     "\t{} {}",
                     "📦 Backed up previous version to".cyan(),
@@ -167,7 +273,7 @@ pub async fn handle_config_pull(context: Context, path: &Path) -> Result<bool, W
 
             match File::create(&file_path) {
                 Ok(mut file) => {
-                    if let Err(err) = file.write_all(secret.as_bytes()) {
+                    if let Err(err) = file.write_all(content.as_bytes()) {
                         debug!("Error while creating file: {:?}", err);
                         eprintln!(
                             "\t{} {} {} {}",
@@ -194,10 +300,24 @@ pub async fn handle_config_pull(context: Context, path: &Path) -> Result<bool, W
                 }
             }
 
-            eprintln!("\t{} {}", "Created".green(), file_path.to_string_lossy());
+            // the remote content is the base of the next merge
+            state.set_base(key, &secret);
+
+            if content == secret {
+                eprintln!("\t{} {}", "Created".green(), file_path.to_string_lossy());
+            } else {
+                eprintln!(
+                    "\t{} {} {}",
+                    "Merged".green(),
+                    file_path.to_string_lossy(),
+                    "with your local changes".dimmed()
+                );
+            }
         }
     }
 
+    state.save()?;
+
     if has_error {
         Ok(false)
     } else {
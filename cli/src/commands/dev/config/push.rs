@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use dialoguer::{theme::ColorfulTheme, Confirm, Select};
 use owo_colors::OwoColorize;
-use wukong_sdk::secret_extractors::SecretInfo;
+use wukong_sdk::secret_extractors::{SecretInfo, SecretProviders};
 
 use crate::{
     commands::{dev::config::utils::make_path_relative, Context},
@@ -14,17 +14,24 @@ use crate::{
 };
 
 use super::{
-    diff::print_diff,
+    diff::{has_diff, print_config_diff},
+    kind::ConfigKind,
+    merge::merge_interactively,
+    state::{state_key, ConfigState},
     utils::{
         extract_secret_infos, get_local_config_path, get_secret_config_files, get_updated_configs,
-        parse_wukong_src, vault_token_for,
+        secret_providers_for, vault_token_for, SecretsCache,
     },
 };
 use wukong_telemetry::*;
 use wukong_telemetry_macro::*;
 
 #[wukong_telemetry(command_event = "dev_config_push")]
-pub async fn handle_config_push(context: Context) -> Result<bool, WKCliError> {
+pub async fn handle_config_push(
+    context: Context,
+    no_input: bool,
+    reveal: bool,
+) -> Result<bool, WKCliError> {
     let loader = new_spinner();
     loader.set_message("🔍 Finding config with annotation");
 
@@ -46,10 +53,41 @@ pub async fn handle_config_push(context: Context) -> Result<bool, WKCliError> {
 
     let mut config = Config::load_from_default_path()?;
     let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
-    let vault_token = vault_token_for(&extracted_infos, &mut config).await?;
+    let mut secrets_cache = SecretsCache::new();
+    let vault_token = vault_token_for(&mut secrets_cache, &extracted_infos, &mut config).await?;
+    let providers = secret_providers_for(
+        &mut secrets_cache,
+        &extracted_infos,
+        &mut wk_client,
+        &vault_token,
+    )
+    .await?;
 
+    // Comparing local vs remote ....
+    println!("{}", "comparing local config vs remote config...".cyan());
     let updated_configs =
-        get_updated_configs(&mut wk_client, &vault_token, &extracted_infos).await?;
+        get_updated_configs(&providers, &mut secrets_cache, &extracted_infos).await?;
+
+    // a rendered template can't be pushed, the secrets it references are pushed instead
+    let (rendered_templates, updated_configs): (Vec<_>, Vec<_>) = updated_configs
+        .into_iter()
+        .partition(|(secret_info, ..)| secret_info.kind == "template");
+    for (secret_info, _, _, config_path) in &rendered_templates {
+        println!(
+            "{}",
+            format!(
+                "{} is rendered from {}, change the template or the secrets it references instead.",
+                make_path_relative(
+                    &get_local_config_path(&secret_info.destination_file, config_path)
+                        .to_string_lossy()
+                ),
+                make_path_relative(
+                    &get_local_config_path(&secret_info.src, config_path).to_string_lossy()
+                ),
+            )
+            .bright_yellow()
+        );
+    }
 
     if updated_configs.is_empty() {
         println!(
@@ -59,33 +97,35 @@ pub async fn handle_config_push(context: Context) -> Result<bool, WKCliError> {
         return Ok(true);
     }
 
-    if updated_configs.len() == 1 {
-        println!(
-            "{}",
-            "There is only one config file to update...".bright_yellow()
-        );
-        let (annotation, remote_config, local_config, config_path) =
-            updated_configs.first().unwrap();
-
-        update_secrets(
-            &mut wk_client,
-            &vault_token,
-            annotation,
-            config_path,
-            remote_config,
-            local_config,
-        )
-        .await?;
+    // without the prompts, all the changed config files are pushed
+    let selected_configs = if no_input || updated_configs.len() == 1 {
+        if updated_configs.len() == 1 {
+            println!(
+                "{}",
+                "There is only one config file to update...".bright_yellow()
+            );
+        }
+        updated_configs.clone()
     } else {
-        let (annotation, remote_config, local_config, config_path) =
-            select_config(&updated_configs).await;
+        vec![select_config(&providers, &updated_configs).await]
+    };
+
+    let mut state = ConfigState::load();
+    for updated_config in &selected_configs {
+        let (secret_info, ..) = updated_config;
+        // the version of the remote config compared with, the push fails if it's changed since
+        let version = secrets_cache
+            .secrets
+            .get(&(secret_info.provider.clone(), secret_info.src.clone()))
+            .and_then(|secrets| secrets.version);
+
         update_secrets(
-            &mut wk_client,
-            &vault_token,
-            annotation,
-            &config_path,
-            &remote_config,
-            &local_config,
+            &providers,
+            &mut state,
+            updated_config,
+            version,
+            no_input,
+            reveal,
         )
         .await?;
     }
@@ -94,43 +134,91 @@ pub async fn handle_config_push(context: Context) -> Result<bool, WKCliError> {
 }
 
 async fn update_secrets(
-    wk_client: &mut WKClient,
-    vault_token: &str,
-    secret_info: &SecretInfo,
-    config_path: &str,
-    remote_config: &str,
-    local_config_string: &str,
+    providers: &SecretProviders<'_>,
+    state: &mut ConfigState,
+    updated_config: &(&SecretInfo, String, String, String),
+    version: Option<u64>,
+    no_input: bool,
+    reveal: bool,
 ) -> Result<(), WKCliError> {
+    let (secret_info, remote_config, local_config_string, config_path) = updated_config;
     let local_config_path = get_local_config_path(config_path, &secret_info.destination_file);
+    let file_path = get_local_config_path(&secret_info.destination_file, config_path);
+    let key = state_key(secret_info, &file_path);
 
-    print_diff(
+    // someone else has pushed since the last pull, so their changes are merged with ours
+    // instead of being overwritten
+    let config_to_push = match state.base(&key) {
+        Some(base) if base.is_changed(remote_config) => {
+            let display_path = make_path_relative(&file_path.to_string_lossy());
+            println!(
+                "{}",
+                format!(
+                    "{display_path} is changed on the remote since your last pull, merging your changes..."
+                )
+                .bright_yellow()
+            );
+
+            merge_interactively(
+                &base.content,
+                local_config_string,
+                remote_config,
+                &display_path,
+                ConfigKind::of(secret_info),
+                no_input,
+                reveal,
+            )?
+        }
+        _ => local_config_string.to_string(),
+    };
+
+    if config_to_push != *local_config_string {
+        std::fs::write(&file_path, &config_to_push)?;
+        println!(
+            "{} {}",
+            "Merged the remote changes into".cyan(),
+            make_path_relative(&file_path.to_string_lossy())
+        );
+    }
+
+    if !has_diff(remote_config, &config_to_push) {
+        state.set_base(key, remote_config);
+        state.save()?;
+
+        println!(
+            "The config file is already up to date with the remote. There are no changes to push."
+        );
+        return Ok(());
+    }
+
+    print_config_diff(
+        ConfigKind::of(secret_info),
         remote_config,
-        local_config_string,
+        &config_to_push,
         &local_config_path.to_string_lossy(),
+        reveal,
     );
 
-    let agree_to_update = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt("Confirm this change & push?")
-        .default(false)
-        .interact()?;
+    let agree_to_update = no_input
+        || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Confirm this change & push?")
+            .default(false)
+            .interact()?;
 
     let mut secrets_ref: HashMap<&str, &str> = HashMap::new();
-    secrets_ref.insert(&secret_info.name, local_config_string);
+    secrets_ref.insert(&secret_info.name, &config_to_push);
 
     if agree_to_update {
         let loader = new_spinner();
         loader.set_message("Updating secrets... ");
 
-        if secret_info.provider == "wukong" {
-            let (app, ns, path) = parse_wukong_src(&secret_info.src);
-            wk_client
-                .update_wukong_secrets(&app, &ns, &path, &secrets_ref)
-                .await?;
-        } else {
-            wk_client
-                .update_secret(vault_token, &secret_info.src, &secrets_ref)
-                .await?;
-        }
+        providers
+            .get(&secret_info.provider)?
+            .update_versioned(&secret_info.src, &secrets_ref, version)
+            .await?;
+
+        state.set_base(key, &config_to_push);
+        state.save()?;
 
         colored_println!("Successfully updated the secrets.");
     }
@@ -139,6 +227,7 @@ async fn update_secrets(
 }
 
 async fn select_config<'a>(
+    providers: &SecretProviders<'_>,
     available_config: &[(&'a SecretInfo, String, String, String)],
 ) -> (&'a SecretInfo, String, String, String) {
     let selection = Select::with_theme(&ColorfulTheme::default())
@@ -149,10 +238,9 @@ async fn select_config<'a>(
                     let local_config_path =
                         get_local_config_path(config_path, &secret_info.destination_file);
 
-                    let remote_display = if secret_info.provider == "wukong" {
-                        format!("wukong:{}#{}", secret_info.src, secret_info.name)
-                    } else {
-                        format!("vault:secret/{}#{}", secret_info.src, secret_info.name)
+                    let remote_display = match providers.get(&secret_info.provider) {
+                        Ok(provider) => provider.describe(&secret_info.src, &secret_info.name),
+                        Err(_) => secret_info.key.clone(),
                     };
 
                     format!(
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use dialoguer::{theme::ColorfulTheme, Confirm};
+
+use crate::{
+    auth::vault,
+    commands::Context,
+    config::Config,
+    error::{DevConfigError, WKCliError},
+    loader::new_spinner,
+    output::colored_println,
+    wukong_client::WKClient,
+};
+
+use super::{
+    diff::{has_diff, print_config_diff},
+    kind::ConfigKind,
+    utils::{find_secret_info, get_local_config_path},
+};
+use wukong_telemetry::*;
+use wukong_telemetry_macro::*;
+
+#[wukong_telemetry(command_event = "dev_config_restore")]
+pub async fn handle_config_restore(
+    context: Context,
+    key: &str,
+    version: u64,
+    no_input: bool,
+    reveal: bool,
+) -> Result<bool, WKCliError> {
+    let (config_path, secret_info) = find_secret_info(key)?;
+    // without the path, if it was given with it
+    let key = secret_info.name.as_str();
+    let local_config_path = get_local_config_path(&secret_info.destination_file, &config_path);
+
+    let mut config = Config::load_from_default_path()?;
+    let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
+    let vault_token = if secret_info.provider == "bunker" {
+        vault::get_token_or_login(&mut config).await?
+    } else {
+        String::new()
+    };
+    if secret_info.provider == "wukong" {
+        wk_client.check_and_refresh_tokens().await?;
+    }
+    let providers = wk_client.secret_providers(&vault_token);
+    let provider = providers.get(&secret_info.provider)?;
+
+    // the version is read first, so a provider not keeping the versions fails before
+    // anything else
+    let restored_config = provider
+        .fetch_version(&secret_info.src, version)
+        .await?
+        .data
+        .remove(key)
+        .ok_or_else(|| DevConfigError::SecretNotInVersion {
+            key: key.to_string(),
+            version,
+        })?;
+    let current_secrets = provider.fetch_versioned(&secret_info.src).await?;
+    let current_config = current_secrets.data.get(key).cloned().unwrap_or_default();
+
+    if !has_diff(&current_config, &restored_config) {
+        println!("\"{key}\" is the same in the version {version}. There is nothing to restore.");
+        return Ok(true);
+    }
+
+    print_config_diff(
+        ConfigKind::of(&secret_info),
+        &current_config,
+        &restored_config,
+        &local_config_path.to_string_lossy(),
+        reveal,
+    );
+
+    let agree_to_restore = no_input
+        || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Restore \"{key}\" to the version {version}?"))
+            .default(false)
+            .interact()?;
+
+    if !agree_to_restore {
+        return Ok(true);
+    }
+
+    let loader = new_spinner();
+    loader.set_message("Restoring the secret ...");
+
+    let mut secrets_ref: HashMap<&str, &str> = HashMap::new();
+    secrets_ref.insert(key, &restored_config);
+
+    // written on top of the version read above only, so a change made in the meantime is
+    // not overwritten
+    let updated = provider
+        .update_versioned(&secret_info.src, &secrets_ref, current_secrets.version)
+        .await;
+    loader.finish_and_clear();
+    updated?;
+
+    colored_println!("Restored \"{key}\" to the version {version}.");
+    println!("Run `wukong dev config pull` to update your local config.");
+
+    Ok(true)
+}
@@ -0,0 +1,316 @@
+use std::collections::{BTreeSet, HashMap};
+
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use owo_colors::OwoColorize;
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+use wukong_sdk::secret_extractors::SecretInfo;
+
+use crate::{
+    auth::vault,
+    commands::Context,
+    config::Config,
+    error::{DevConfigError, WKCliError},
+    loader::new_spinner,
+    wukong_client::WKClient,
+};
+
+use super::{
+    pull::pull_config_files,
+    template::SecretReference,
+    utils::{
+        extract_secret_infos, get_local_config_path, get_secret_config_files, make_path_relative,
+        secret_providers_for, vault_token_for, SecretsCache,
+    },
+};
+use wukong_telemetry::*;
+use wukong_telemetry_macro::*;
+
+/// The length of the generated values.
+const GENERATED_VALUE_LENGTH: usize = 32;
+
+#[wukong_telemetry(command_event = "dev_config_rotate")]
+pub async fn handle_config_rotate(
+    context: Context,
+    secret: &str,
+    value: Option<&str>,
+    also_wukong: &[String],
+    no_input: bool,
+    pull: bool,
+    reveal: bool,
+) -> Result<bool, WKCliError> {
+    let secret =
+        SecretReference::parse(secret).ok_or_else(|| DevConfigError::InvalidSecretReference {
+            reference: secret.to_string(),
+        })?;
+
+    // the copies kept by the Wukong API are only the ones named explicitly, a secret with
+    // the same key isn't necessarily the same secret
+    let wukong_copies = also_wukong
+        .iter()
+        .map(|path| parse_wukong_path(path))
+        .filter(
+            |path| !matches!(path, Ok(path) if secret.provider == "wukong" && path == secret.src),
+        )
+        .collect::<Result<BTreeSet<_>, _>>()?;
+
+    let secret_config_files = get_secret_config_files(None)?;
+    let extracted_infos = extract_secret_infos(secret_config_files)?;
+    // the templates are read once, the secrets are only fetched by the pull after rotating
+    let mut secrets_cache = SecretsCache::new();
+    let unnamed_wukong_copies = find_unnamed_wukong_copies(
+        &mut secrets_cache,
+        &extracted_infos,
+        &secret,
+        &wukong_copies,
+    );
+
+    let rotated = |(provider, src, name): &(String, String, String)| {
+        name == secret.name
+            && ((provider == secret.provider && src == secret.src)
+                || (provider == "wukong" && wukong_copies.contains(src)))
+    };
+    let referencing_infos = extracted_infos
+        .into_iter()
+        .map(|(config_path, infos)| {
+            let infos = infos
+                .into_iter()
+                .filter(|info| {
+                    secrets_cache
+                        .referenced_secrets(info, &config_path)
+                        .iter()
+                        .any(&rotated)
+                })
+                .collect::<Vec<_>>();
+            (config_path, infos)
+        })
+        .filter(|(_, infos)| !infos.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut config = Config::load_from_default_path()?;
+    let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
+    let vault_token = if secret.provider == "bunker" {
+        vault::get_token_or_login(&mut config).await?
+    } else {
+        vault_token_for(&mut secrets_cache, &referencing_infos, &mut config).await?
+    };
+    if secret.provider == "wukong" || !wukong_copies.is_empty() {
+        wk_client.check_and_refresh_tokens().await?;
+    }
+    let providers = secret_providers_for(
+        &mut secrets_cache,
+        &referencing_infos,
+        &mut wk_client,
+        &vault_token,
+    )
+    .await?;
+
+    let provider = providers.get(secret.provider)?;
+    let described = provider.describe(secret.src, secret.name);
+
+    if referencing_infos.is_empty() {
+        println!("No config files in this workspace are written from {described}.");
+    } else {
+        println!("The config files written from {described}:");
+        for (config_path, infos) in &referencing_infos {
+            for info in infos {
+                let destination_path = get_local_config_path(&info.destination_file, config_path);
+                println!(
+                    "\t{} → {}",
+                    make_path_relative(config_path),
+                    make_path_relative(&destination_path.to_string_lossy())
+                );
+            }
+        }
+    }
+
+    if !wukong_copies.is_empty() {
+        println!("The copies kept by the Wukong API are rotated as well:");
+        for src in &wukong_copies {
+            println!("\t{}", providers.get("wukong")?.describe(src, secret.name));
+        }
+    }
+
+    if !unnamed_wukong_copies.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "These secrets kept by the Wukong API have a \"{}\" key as well, they are only rotated when given with --also-wukong:",
+                secret.name
+            )
+            .bright_yellow()
+        );
+        for src in &unnamed_wukong_copies {
+            println!("\t{src}");
+        }
+    }
+
+    let agree_to_rotate = no_input
+        || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Rotate {described}?"))
+            .default(false)
+            .interact()?;
+
+    if !agree_to_rotate {
+        return Ok(true);
+    }
+
+    let generated = value.is_none();
+    let value = value.map_or_else(generate_value, str::to_string);
+
+    let loader = new_spinner();
+    loader.set_message("Rotating the secret ...");
+
+    let secrets = HashMap::from([(secret.name, value.as_str())]);
+    if let Err(err) = provider.update(secret.src, &secrets).await {
+        loader.finish_and_clear();
+        return Err(err.into());
+    }
+    // the secret is rotated already, so every copy is updated even if one of them fails
+    let mut copies_updated = Vec::with_capacity(wukong_copies.len());
+    for src in &wukong_copies {
+        copies_updated.push((src, providers.get("wukong")?.update(src, &secrets).await));
+    }
+    loader.finish_and_clear();
+
+    println!("Rotated {described}.");
+    let mut copy_error = None;
+    for (src, updated) in copies_updated {
+        let copy_described = providers.get("wukong")?.describe(src, secret.name);
+        match updated {
+            Ok(_) => println!("Rotated {copy_described}."),
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    format!("Failed to rotate {copy_described}: {err}").red()
+                );
+                copy_error.get_or_insert(err);
+            }
+        }
+    }
+
+    if generated {
+        if reveal {
+            println!("The new value is {}", value.bold());
+        } else if referencing_infos.is_empty() {
+            println!("The generated value isn't shown without --reveal.");
+        } else {
+            println!(
+                "The generated value isn't shown without --reveal, it's written to the config files pulled from the secret."
+            );
+        }
+    }
+
+    if !referencing_infos.is_empty() {
+        let agree_to_pull = pull
+            || (!no_input
+                && Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Pull the config files written from it?")
+                    .default(true)
+                    .interact()?);
+
+        if agree_to_pull {
+            let pulled = pull_config_files(
+                &providers,
+                &mut secrets_cache,
+                referencing_infos,
+                no_input,
+                reveal,
+            )
+            .await?;
+
+            return match copy_error {
+                Some(err) => Err(err.into()),
+                None => Ok(pulled),
+            };
+        }
+
+        println!("Run `wukong dev config pull` to update your local config.");
+    }
+
+    match copy_error {
+        Some(err) => Err(err.into()),
+        None => Ok(true),
+    }
+}
+
+/// The Wukong secret path given with `--also-wukong`, e.g. `my-app/prod/db`.
+fn parse_wukong_path(path: &str) -> Result<String, DevConfigError> {
+    let segments = path.splitn(3, '/').collect::<Vec<_>>();
+
+    if segments.len() == 3 && segments.iter().all(|segment| !segment.is_empty()) {
+        Ok(path.to_string())
+    } else {
+        Err(DevConfigError::InvalidWukongPath {
+            path: path.to_string(),
+        })
+    }
+}
+
+/// The paths of the `wukong` secrets with the same key as `secret`, referenced in the
+/// extracted entries but not given with `--also-wukong`.
+fn find_unnamed_wukong_copies(
+    secrets_cache: &mut SecretsCache,
+    extracted_infos: &[(String, Vec<SecretInfo>)],
+    secret: &SecretReference,
+    wukong_copies: &BTreeSet<String>,
+) -> BTreeSet<String> {
+    secrets_cache
+        .all_referenced_secrets(extracted_infos)
+        .into_iter()
+        .filter(|(provider, src, name)| {
+            provider == "wukong"
+                && name == secret.name
+                && !(secret.provider == "wukong" && src == secret.src)
+                && !wukong_copies.contains(src)
+        })
+        .map(|(_, src, _)| src)
+        .collect()
+}
+
+/// A random alphanumeric value, read from the OS random number generator.
+fn generate_value() -> String {
+    OsRng
+        .sample_iter(&Alphanumeric)
+        .take(GENERATED_VALUE_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_generate_value() {
+        let value = generate_value();
+
+        assert_eq!(value.len(), GENERATED_VALUE_LENGTH);
+        assert!(value.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(value, generate_value());
+    }
+
+    #[test]
+    fn test_parse_wukong_path() {
+        assert_eq!(
+            parse_wukong_path("my-app/prod/db").unwrap(),
+            "my-app/prod/db"
+        );
+        assert_eq!(
+            parse_wukong_path("my-app/prod/db/primary").unwrap(),
+            "my-app/prod/db/primary"
+        );
+
+        for path in [
+            "my-app",
+            "my-app/prod",
+            "my-app//db",
+            "/prod/db",
+            "my-app/prod/",
+        ] {
+            assert!(matches!(
+                parse_wukong_path(path),
+                Err(DevConfigError::InvalidWukongPath { .. })
+            ));
+        }
+    }
+}
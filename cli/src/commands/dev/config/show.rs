@@ -0,0 +1,54 @@
+use crate::{
+    auth::vault,
+    commands::Context,
+    config::Config,
+    error::{DevConfigError, WKCliError},
+    wukong_client::WKClient,
+};
+
+use super::utils::find_secret_info;
+use wukong_telemetry::*;
+use wukong_telemetry_macro::*;
+
+#[wukong_telemetry(command_event = "dev_config_show")]
+pub async fn handle_config_show(
+    context: Context,
+    key: &str,
+    version: Option<u64>,
+) -> Result<bool, WKCliError> {
+    let (_, secret_info) = find_secret_info(key)?;
+    // without the path, if it was given with it
+    let key = secret_info.name.as_str();
+
+    let mut config = Config::load_from_default_path()?;
+    let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
+    let vault_token = if secret_info.provider == "bunker" {
+        vault::get_token_or_login(&mut config).await?
+    } else {
+        String::new()
+    };
+    if secret_info.provider == "wukong" {
+        wk_client.check_and_refresh_tokens().await?;
+    }
+    let providers = wk_client.secret_providers(&vault_token);
+    let provider = providers.get(&secret_info.provider)?;
+
+    // only the providers keeping the versions of the secrets can read a previous one
+    let mut secrets = match version {
+        Some(version) => provider.fetch_version(&secret_info.src, version).await?,
+        None => provider.fetch_versioned(&secret_info.src).await?,
+    };
+
+    let content = secrets
+        .data
+        .remove(key)
+        .ok_or_else(|| DevConfigError::SecretNotInVersion {
+            key: key.to_string(),
+            version: secrets.version.or(version).unwrap_or_default(),
+        })?;
+
+    // printed as it is, so it can be redirected to a file
+    print!("{content}");
+
+    Ok(true)
+}
@@ -0,0 +1,162 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wukong_sdk::secret_extractors::SecretInfo;
+
+use crate::{config::DEV_CONFIG_STATE_FILE, error::WKCliError, utils::fs::write_private_file};
+
+/// The content of a dev config at the last pull or push, to detect the changes made on
+/// the remote since then, and to three-way merge them with the local changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigBase {
+    /// The SHA-256 hash of the content.
+    pub hash: String,
+    pub content: String,
+}
+
+impl ConfigBase {
+    pub fn new(content: &str) -> Self {
+        Self {
+            hash: content_hash(content),
+            content: content.to_string(),
+        }
+    }
+
+    /// Whether `remote` is changed since the base.
+    pub fn is_changed(&self, remote: &str) -> bool {
+        self.hash != content_hash(remote)
+    }
+}
+
+/// The bases of the dev configs, stored in the [DEV_CONFIG_STATE_FILE].
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConfigState {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    bases: BTreeMap<String, ConfigBase>,
+}
+
+impl ConfigState {
+    /// Load the state from the [DEV_CONFIG_STATE_FILE], it's empty if the file doesn't
+    /// exist yet or can't be read.
+    pub fn load() -> Self {
+        match DEV_CONFIG_STATE_FILE.as_ref() {
+            Some(path) => Self::load_from(Path::new(path)),
+            None => Self::default(),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let mut state = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| match serde_json::from_str::<Self>(&content) {
+                Ok(state) => Some(state),
+                Err(error) => {
+                    debug!("Ignoring the invalid dev config state: {:?}", error);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        state.path = Some(path.to_path_buf());
+
+        state
+    }
+
+    /// Save the state, the file is only readable by the user as it holds the secrets.
+    pub fn save(&self) -> Result<(), WKCliError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        write_private_file(path, content.as_bytes())?;
+
+        Ok(())
+    }
+
+    pub fn base(&self, key: &str) -> Option<&ConfigBase> {
+        self.bases.get(key)
+    }
+
+    pub fn set_base(&mut self, key: String, content: &str) {
+        self.bases.insert(key, ConfigBase::new(content));
+    }
+}
+
+/// The key of the base of a dev config, the remote secret and the local file it's
+/// written to, as the same secret can be pulled into several projects.
+pub fn state_key(info: &SecretInfo, local_config_path: &Path) -> String {
+    // the parent is resolved rather than the file, which doesn't exist before the first pull
+    let local_config_path = match (local_config_path.parent(), local_config_path.file_name()) {
+        (Some(dir), Some(file_name)) => dir
+            .canonicalize()
+            .map(|dir| dir.join(file_name))
+            .unwrap_or(local_config_path.to_path_buf()),
+        _ => local_config_path.to_path_buf(),
+    };
+
+    format!(
+        "{}:{}#{} {}",
+        info.provider,
+        info.src,
+        info.name,
+        local_config_path.to_string_lossy()
+    )
+}
+
+pub fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_config_state_save_and_load() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("dev-config-state.json");
+
+        let mut state = ConfigState::load_from(&path);
+        assert!(state.base("bunker:app/dev#dotenv .env").is_none());
+
+        state.set_base("bunker:app/dev#dotenv .env".to_string(), "A=1\n");
+        state.save().unwrap();
+
+        let state = ConfigState::load_from(&path);
+        let base = state.base("bunker:app/dev#dotenv .env").unwrap();
+        assert_eq!(base.content, "A=1\n");
+        assert!(!base.is_changed("A=1\n"));
+        assert!(base.is_changed("A=2\n"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_config_state_is_ignored() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("dev-config-state.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let state = ConfigState::load_from(&path);
+        assert!(state.bases.is_empty());
+
+        temp.close().unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+use std::{env::current_dir, io::ErrorKind, path::Path};
+
+use log::debug;
+use owo_colors::OwoColorize;
+use wukong_sdk::{
+    error::{VaultError, WKError},
+    secret_extractors::{SecretInfo, SecretProviders},
+};
+
+use crate::{
+    commands::Context,
+    config::Config,
+    error::{DevConfigError, WKCliError},
+    loader::new_spinner,
+    wukong_client::WKClient,
+};
+
+use super::{
+    diff::has_diff,
+    kind::{diff_entries, ConfigKind, KeyChange},
+    utils::{
+        extract_secret_infos, get_local_config_path, get_remote_config, get_secret_config_files,
+        get_workspace_secret_infos, group_by_application, make_path_relative, prefetch_secrets,
+        print_applications_summary, secret_providers_for, vault_token_for, SecretsCache,
+    },
+};
+use wukong_telemetry::*;
+use wukong_telemetry_macro::*;
+
+/// How a local config file compares with its secret on the remote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigStatus {
+    UpToDate,
+    Stale,
+    MissingLocally,
+    MissingRemote,
+}
+
+impl ConfigStatus {
+    fn new(kind: ConfigKind, remote_config: Option<&str>, local_config: Option<&str>) -> Self {
+        match (remote_config, local_config) {
+            (None, _) => Self::MissingRemote,
+            (Some(_), None) => Self::MissingLocally,
+            (Some(remote_config), Some(local_config))
+                if is_stale(kind, remote_config, local_config) =>
+            {
+                Self::Stale
+            }
+            (Some(_), Some(_)) => Self::UpToDate,
+        }
+    }
+
+    fn label(&self) -> String {
+        // padded before coloring, so the paths are aligned
+        match self {
+            Self::UpToDate => format!("{:<16}", "up-to-date").green().to_string(),
+            Self::Stale => format!("{:<16}", "stale").yellow().to_string(),
+            Self::MissingLocally => format!("{:<16}", "missing-locally").red().to_string(),
+            Self::MissingRemote => format!("{:<16}", "missing-remote").red().to_string(),
+        }
+    }
+}
+
+/// Whether the local config is missing a change from the remote. The keys only kept
+/// locally, e.g. the local overrides in a dotenv merged by a pull, aren't a change.
+fn is_stale(kind: ConfigKind, remote_config: &str, local_config: &str) -> bool {
+    match (kind.parse(remote_config), kind.parse(local_config)) {
+        (Some(remote_entries), Some(local_entries)) => {
+            diff_entries(&remote_entries, &local_entries)
+                .iter()
+                .any(|change| !matches!(change, KeyChange::Added { .. }))
+        }
+        _ => has_diff(remote_config, local_config),
+    }
+}
+
+#[wukong_telemetry(command_event = "dev_config_status")]
+pub async fn handle_config_status(
+    context: Context,
+    path: &Path,
+    all: bool,
+) -> Result<bool, WKCliError> {
+    if all {
+        if path != Path::new(".") {
+            return Err(WKCliError::DevConfigError(DevConfigError::PathWithAll {
+                path: path.to_string_lossy().to_string(),
+            }));
+        }
+
+        return status_all_applications(&context).await;
+    }
+
+    let path = path.try_exists().map(|value| match value {
+        true => {
+            if path.to_string_lossy() == "." {
+                current_dir()
+            } else {
+                Ok(path.to_path_buf())
+            }
+        }
+        false => Err(std::io::Error::new(
+            ErrorKind::NotFound,
+            format!("path '{}' does not exist", path.to_string_lossy()),
+        )),
+    })??;
+
+    let loader = new_spinner();
+    loader.set_message("🔍 Finding config with annotation");
+
+    let secret_config_files = get_secret_config_files(Some(path))?;
+    let extracted_infos = extract_secret_infos(secret_config_files)?;
+
+    loader.finish_and_clear();
+
+    if extracted_infos.is_empty() {
+        return Err(WKCliError::DevConfigError(DevConfigError::ConfigNotFound));
+    }
+
+    let mut config = Config::load_from_default_path()?;
+    let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
+    let mut secrets_cache = SecretsCache::new();
+    let vault_token = vault_token_for(&mut secrets_cache, &extracted_infos, &mut config).await?;
+    let providers = secret_providers_for(
+        &mut secrets_cache,
+        &extracted_infos,
+        &mut wk_client,
+        &vault_token,
+    )
+    .await?;
+
+    // the secrets are fetched once for each (provider, path)
+    let drifted = print_config_status(&providers, &mut secrets_cache, &extracted_infos).await?;
+
+    drift_summary(drifted)
+}
+
+/// Check every application in the workspace, with the secrets they share fetched once.
+async fn status_all_applications(context: &Context) -> Result<bool, WKCliError> {
+    let loader = new_spinner();
+    loader.set_message("🔍 Finding config with annotation");
+
+    let (repo_root, extracted_infos) = get_workspace_secret_infos()?;
+
+    loader.finish_and_clear();
+
+    let mut config = Config::load_from_default_path()?;
+    let mut wk_client = WKClient::for_channel(&config, &context.channel)?;
+    let mut secrets_cache = SecretsCache::new();
+    let vault_token = vault_token_for(&mut secrets_cache, &extracted_infos, &mut config).await?;
+    let providers = secret_providers_for(
+        &mut secrets_cache,
+        &extracted_infos,
+        &mut wk_client,
+        &vault_token,
+    )
+    .await?;
+
+    // the secrets of all the applications are fetched in parallel
+    prefetch_secrets(&providers, &mut secrets_cache, &extracted_infos).await;
+    let mut outcomes = Vec::new();
+    let mut total_drifted = 0;
+    let mut has_error = false;
+
+    for application in group_by_application(&repo_root, extracted_infos) {
+        println!();
+        println!("📦 {}", application.name.bold());
+
+        // a failing application is reported in the summary, the other ones are still checked
+        let drifted =
+            match print_config_status(&providers, &mut secrets_cache, &application.extracted_infos)
+                .await
+            {
+                Ok(drifted) => drifted,
+                Err(error) => {
+                    eprintln!("{}", error.to_string().red());
+                    outcomes.push((application.name, false, "failed".to_string()));
+                    has_error = true;
+                    continue;
+                }
+            };
+        total_drifted += drifted;
+
+        if drifted == 0 {
+            outcomes.push((application.name, true, "up-to-date".to_string()));
+        } else {
+            outcomes.push((application.name, false, format!("{drifted} drifted")));
+        }
+    }
+
+    print_applications_summary(&outcomes);
+
+    // the check failing is reported over the drift, as it's incomplete
+    if has_error {
+        return Ok(false);
+    }
+
+    drift_summary(total_drifted)
+}
+
+/// Print the status of each config file, returns how many are not up to date.
+async fn print_config_status(
+    providers: &SecretProviders<'_>,
+    secrets_cache: &mut SecretsCache,
+    extracted_infos: &[(String, Vec<SecretInfo>)],
+) -> Result<usize, WKCliError> {
+    let mut drifted = 0;
+
+    for (config_path, secret_infos) in extracted_infos {
+        println!();
+        println!("{}", make_path_relative(config_path).dimmed());
+
+        for info in secret_infos {
+            // a path not found on the remote is reported with the others instead of failing
+            let remote_config = match get_remote_config(providers, secrets_cache, info, config_path)
+                .await
+            {
+                Ok(remote_config) => remote_config,
+                Err(WKCliError::WKSdkError(WKError::VaultError(VaultError::SecretNotFound)))
+                | Err(WKCliError::DevConfigError(DevConfigError::TemplateSecretNotFound {
+                    ..
+                })) => None,
+                Err(error) => return Err(error),
+            };
+            let local_config_path = get_local_config_path(&info.destination_file, config_path);
+            let local_config = match std::fs::read_to_string(&local_config_path) {
+                Ok(local_config) => Some(local_config),
+                Err(error) => {
+                    debug!("Error: {:?}", error);
+                    None
+                }
+            };
+
+            let status = ConfigStatus::new(
+                ConfigKind::of(info),
+                remote_config.as_deref(),
+                local_config.as_deref(),
+            );
+            if status != ConfigStatus::UpToDate {
+                drifted += 1;
+            }
+
+            println!(
+                "\t{} {}",
+                status.label(),
+                make_path_relative(&local_config_path.to_string_lossy())
+            );
+        }
+    }
+
+    Ok(drifted)
+}
+
+/// The drift is an error of its own, so it exits with a different code than the other
+/// errors, e.g. for a CI check to tell a stale config from a failing run.
+fn drift_summary(drifted: usize) -> Result<bool, WKCliError> {
+    if drifted > 0 {
+        return Err(WKCliError::DevConfigError(DevConfigError::ConfigDrift {
+            drifted,
+        }));
+    }
+
+    println!();
+    println!("All the config files are up to date with the remote.");
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_config_status() {
+        assert_eq!(
+            ConfigStatus::new(ConfigKind::Text, Some("A=1\n"), Some("A=1\n")),
+            ConfigStatus::UpToDate
+        );
+        assert_eq!(
+            ConfigStatus::new(ConfigKind::Text, Some("A=1\n"), Some("A=2\n")),
+            ConfigStatus::Stale
+        );
+        assert_eq!(
+            ConfigStatus::new(ConfigKind::Text, Some("A=1\n"), None),
+            ConfigStatus::MissingLocally
+        );
+        assert_eq!(
+            ConfigStatus::new(ConfigKind::Text, None, Some("A=1\n")),
+            ConfigStatus::MissingRemote
+        );
+        assert_eq!(
+            ConfigStatus::new(ConfigKind::Text, None, None),
+            ConfigStatus::MissingRemote
+        );
+    }
+
+    #[test]
+    fn test_config_status_by_key() {
+        // the local-only keys are kept by a pull
+        assert_eq!(
+            ConfigStatus::new(
+                ConfigKind::Dotenv,
+                Some("A=1\nB=2\n"),
+                Some("# overrides\nA=1\nB=2\nLOCAL_ONLY=yes\n")
+            ),
+            ConfigStatus::UpToDate
+        );
+        assert_eq!(
+            ConfigStatus::new(ConfigKind::Dotenv, Some("A=1\nB=2\n"), Some("A=1\n")),
+            ConfigStatus::Stale
+        );
+        assert_eq!(
+            ConfigStatus::new(
+                ConfigKind::Dotenv,
+                Some("A=1\n"),
+                Some("A=2\nLOCAL_ONLY=yes\n")
+            ),
+            ConfigStatus::Stale
+        );
+        assert_eq!(
+            ConfigStatus::new(
+                ConfigKind::Json,
+                Some(r#"{"a": {"b": "1"}}"#),
+                Some(r#"{"a": {"b": "1", "c": "2"}}"#)
+            ),
+            ConfigStatus::UpToDate
+        );
+        // the text kinds are compared line by line
+        assert_eq!(
+            ConfigStatus::new(ConfigKind::Text, Some("A=1\n"), Some("A=1\nB=2\n")),
+            ConfigStatus::Stale
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use std::{borrow::Cow, path::Path};
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use wukong_sdk::secret_extractors::{SecretInfo, SecretProviders};
+
+use crate::error::{DevConfigError, WKCliError};
+
+use super::{
+    kind::ConfigKind,
+    utils::{fetch_secrets, get_local_config_path, SecretsCache},
+};
+
+/// A secret referenced in a template, e.g. `{{ bunker:mv/tech/app/dev#DB_PASS }}` or
+/// `{{ wukong:my-app/staging/dev#DB_PASS }}`. The other `{{ ... }}` are kept as they are.
+static REFERENCE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*([\w-]+):([^#\s}]+)#([^\s}]+)\s*\}\}").unwrap());
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SecretReference<'a> {
+    pub provider: &'a str,
+    /// The path, in the form of the `src` of the provider.
+    pub src: &'a str,
+    pub name: &'a str,
+}
+
+impl<'a> SecretReference<'a> {
+    /// Parse a secret written like in a template but without the braces, e.g.
+    /// `bunker:mv/tech/app/dev#DB_PASS`, or like in an annotation, e.g.
+    /// `vault:secret/mv/tech/app/dev#DB_PASS`.
+    pub fn parse(reference: &'a str) -> Option<Self> {
+        let (provider, path) = reference.trim().split_once(':')?;
+        let (src, name) = path.rsplit_once('#')?;
+        let (provider, src) = match (provider, src.strip_prefix("secret/")) {
+            ("vault", Some(src)) => ("bunker", src),
+            _ => (provider, src),
+        };
+
+        if provider.is_empty() || src.is_empty() || name.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            provider,
+            src,
+            name,
+        })
+    }
+
+    /// The `src` to fetch the secret from, for a reference in the template of `info`. A
+    /// `file` path is resolved from the directory of the `.wukong.toml`, like the `file`
+    /// entries in it, instead of from the current directory.
+    pub fn resolved_src(&self, info: &SecretInfo) -> String {
+        match self.provider {
+            "file" => info
+                .annotated_file
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(self.src)
+                .to_string_lossy()
+                .to_string(),
+            _ => self.src.to_string(),
+        }
+    }
+
+    fn from_captures(captures: &Captures<'a>) -> Self {
+        Self {
+            provider: captures.get(1).unwrap().as_str(),
+            src: captures.get(2).unwrap().as_str(),
+            name: captures.get(3).unwrap().as_str(),
+        }
+    }
+}
+
+/// The secrets referenced in the template, in order.
+pub fn references(template: &str) -> Vec<SecretReference> {
+    REFERENCE_REGEX
+        .captures_iter(template)
+        .map(|captures| SecretReference::from_captures(&captures))
+        .collect()
+}
+
+/// Replace the references in the template with the values of their secrets, from `lookup`,
+/// escaped for the `kind` of the rendered config.
+pub fn render<'v>(
+    template: &str,
+    kind: ConfigKind,
+    lookup: impl Fn(&SecretReference) -> Option<&'v str>,
+) -> Result<String, DevConfigError> {
+    let mut missing = None;
+    let rendered = REFERENCE_REGEX.replace_all(template, |captures: &Captures| {
+        match lookup(&SecretReference::from_captures(captures)) {
+            Some(value) => escape(kind, value).into_owned(),
+            None => {
+                missing.get_or_insert_with(|| captures[0].to_string());
+                String::new()
+            }
+        }
+    });
+
+    match missing {
+        Some(reference) => Err(DevConfigError::TemplateSecretNotFound { reference }),
+        None => Ok(rendered.into_owned()),
+    }
+}
+
+/// Escape a secret value for a double-quoted string of the Elixir, JSON and YAML configs,
+/// where the references are written in their templates, e.g.
+/// `password: "{{ bunker:mv/tech/app/dev#DB_PASS }}"`. The values are written as they are
+/// in the dotenv and text configs.
+fn escape(kind: ConfigKind, value: &str) -> Cow<str> {
+    if matches!(kind, ConfigKind::Dotenv | ConfigKind::Text) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut escaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // not interpolated in an Elixir string
+            '#' if kind == ConfigKind::Elixir && chars.peek() == Some(&'{') => {
+                escaped.push_str("\\#")
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+/// The template of a `template` entry, its `src` is relative to the annotated file.
+pub fn read_template(info: &SecretInfo, config_path: &str) -> Result<String, WKCliError> {
+    let template_path = get_local_config_path(&info.src, config_path);
+
+    std::fs::read_to_string(&template_path).map_err(|_| {
+        WKCliError::DevConfigError(DevConfigError::TemplateNotFound {
+            path: template_path.to_string_lossy().to_string(),
+        })
+    })
+}
+
+/// Render the template of a `template` entry, with the secrets it references fetched once
+/// for each (provider, path).
+pub async fn render_template(
+    providers: &SecretProviders<'_>,
+    secrets_cache: &mut SecretsCache,
+    info: &SecretInfo,
+    config_path: &str,
+) -> Result<String, WKCliError> {
+    let template = read_template(info, config_path)?;
+
+    for reference in references(&template) {
+        fetch_secrets(
+            providers,
+            secrets_cache,
+            reference.provider,
+            &reference.resolved_src(info),
+        )
+        .await?;
+    }
+
+    let secrets_cache: &SecretsCache = secrets_cache;
+    let rendered = render(&template, ConfigKind::of(info), |reference| {
+        secrets_cache
+            .secrets
+            .get(&(reference.provider.to_string(), reference.resolved_src(info)))
+            .and_then(|secrets| secrets.data.get(reference.name))
+            .map(String::as_str)
+    })?;
+
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    const TEMPLATE: &str = r#"import Config
+
+config :app, App.Repo,
+  username: "{{ bunker:mv/tech/app/dev#DB_USER }}",
+  password: "{{bunker:mv/tech/app/dev#DB_PASS}}",
+  api_key: "{{ wukong:my-app/staging/dev#API_KEY }}",
+  # not a secret
+  label: "{{ name }}"
+"#;
+
+    #[test]
+    fn test_references() {
+        assert_eq!(
+            references(TEMPLATE),
+            vec![
+                SecretReference {
+                    provider: "bunker",
+                    src: "mv/tech/app/dev",
+                    name: "DB_USER"
+                },
+                SecretReference {
+                    provider: "bunker",
+                    src: "mv/tech/app/dev",
+                    name: "DB_PASS"
+                },
+                SecretReference {
+                    provider: "wukong",
+                    src: "my-app/staging/dev",
+                    name: "API_KEY"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_secret_reference() {
+        assert_eq!(
+            SecretReference::parse("bunker:mv/tech/app/dev#DB_PASS"),
+            Some(SecretReference {
+                provider: "bunker",
+                src: "mv/tech/app/dev",
+                name: "DB_PASS"
+            })
+        );
+        assert_eq!(
+            SecretReference::parse("vault:secret/mv/tech/app/dev#DB_PASS"),
+            Some(SecretReference {
+                provider: "bunker",
+                src: "mv/tech/app/dev",
+                name: "DB_PASS"
+            })
+        );
+        assert_eq!(
+            SecretReference::parse("wukong:my-app/staging/dev#API_KEY"),
+            Some(SecretReference {
+                provider: "wukong",
+                src: "my-app/staging/dev",
+                name: "API_KEY"
+            })
+        );
+        assert_eq!(SecretReference::parse("DB_PASS"), None);
+        assert_eq!(SecretReference::parse("bunker:mv/tech/app/dev"), None);
+        assert_eq!(SecretReference::parse("bunker:#DB_PASS"), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let secrets = HashMap::from([
+            ("DB_USER", "admin"),
+            ("DB_PASS", "hunter2"),
+            ("API_KEY", "sk_live_123"),
+        ]);
+
+        assert_eq!(
+            render(TEMPLATE, ConfigKind::Elixir, |reference| {
+                secrets.get(reference.name).copied()
+            })
+            .unwrap(),
+            r#"import Config
+
+config :app, App.Repo,
+  username: "admin",
+  password: "hunter2",
+  api_key: "sk_live_123",
+  # not a secret
+  label: "{{ name }}"
+"#
+        );
+
+        let secrets = HashMap::from([("DB_USER", "admin")]);
+        assert!(matches!(
+            render(TEMPLATE, ConfigKind::Elixir, |reference| {
+                secrets.get(reference.name).copied()
+            }),
+            Err(DevConfigError::TemplateSecretNotFound { reference })
+                if reference == "{{bunker:mv/tech/app/dev#DB_PASS}}"
+        ));
+    }
+
+    #[test]
+    fn test_render_escapes_the_values() {
+        let value = "a \"quoted\" \\ #{value}\nline";
+        let render_value =
+            |template: &str, kind: ConfigKind| render(template, kind, |_| Some(value)).unwrap();
+
+        assert_eq!(
+            render_value(
+                "password: \"{{ bunker:app/dev#PASS }}\"",
+                ConfigKind::Elixir
+            ),
+            r#"password: "a \"quoted\" \\ \#{value}\nline""#
+        );
+        assert_eq!(
+            render_value(
+                "{\"password\": \"{{ bunker:app/dev#PASS }}\"}",
+                ConfigKind::Json
+            ),
+            r#"{"password": "a \"quoted\" \\ #{value}\nline"}"#
+        );
+        assert_eq!(
+            render_value("password: \"{{ bunker:app/dev#PASS }}\"", ConfigKind::Yaml),
+            r#"password: "a \"quoted\" \\ #{value}\nline""#
+        );
+        assert_eq!(
+            render_value("PASS={{ bunker:app/dev#PASS }}", ConfigKind::Dotenv),
+            format!("PASS={value}")
+        );
+    }
+
+    #[test]
+    fn test_resolved_src() {
+        let info = SecretInfo {
+            key: "rendered".to_string(),
+            provider: String::new(),
+            kind: "template".to_string(),
+            src: "templates/dev.secret.exs.tmpl".to_string(),
+            destination_file: "config/dev.secret.exs".to_string(),
+            name: String::new(),
+            annotated_file: "/app/.wukong.toml".into(),
+        };
+
+        assert_eq!(
+            SecretReference::parse("file:secrets/dev.json.age#DB_PASS")
+                .unwrap()
+                .resolved_src(&info),
+            "/app/secrets/dev.json.age"
+        );
+        assert_eq!(
+            SecretReference::parse("bunker:mv/tech/app/dev#DB_PASS")
+                .unwrap()
+                .resolved_src(&info),
+            "mv/tech/app/dev"
+        );
+    }
+}
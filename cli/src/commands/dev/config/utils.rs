@@ -1,15 +1,21 @@
+use futures::{stream, StreamExt};
 use ignore::{overrides::OverrideBuilder, WalkBuilder};
 use log::debug;
 use owo_colors::OwoColorize;
 use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
     env::current_dir,
     path::{Path, PathBuf},
 };
 use wukong_sdk::secret_extractors::{
-    ElixirConfigExtractor, SecretExtractor, SecretInfo, WKTomlConfigExtractor,
+    ElixirConfigExtractor, JsonConfigExtractor, SecretExtractor, SecretInfo, SecretProviders,
+    VersionedSecrets, WKTomlConfigExtractor, YamlConfigExtractor,
 };
 
-use super::diff::has_diff;
+use super::{
+    diff::has_diff,
+    template::{read_template, references, render_template, SecretReference},
+};
 use crate::{
     auth::vault,
     config::Config,
@@ -17,65 +23,202 @@ use crate::{
     wukong_client::WKClient,
 };
 
+/// The secrets fetched from the providers and the templates read during a command, so each
+/// of them is only fetched or parsed once, e.g. a template is needed to find the providers
+/// to log in to, then to fetch its secrets.
+#[derive(Debug, Default)]
+pub struct SecretsCache {
+    /// The secrets with the version read, by (provider, path).
+    pub secrets: HashMap<(String, String), VersionedSecrets>,
+    /// The (provider, path, key) of the secrets referenced in each template, by its path.
+    pub templates: HashMap<PathBuf, Vec<(String, String, String)>>,
+}
+
+impl SecretsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The (provider, path, key) of the secrets the entry is written from, the ones
+    /// referenced in its template for a `template` entry.
+    pub fn referenced_secrets(
+        &mut self,
+        info: &SecretInfo,
+        config_path: &str,
+    ) -> Vec<(String, String, String)> {
+        if info.kind != "template" {
+            return vec![(info.provider.clone(), info.src.clone(), info.name.clone())];
+        }
+
+        let template_path = get_local_config_path(&info.src, config_path);
+
+        self.templates
+            .entry(template_path)
+            .or_insert_with(|| {
+                read_template(info, config_path)
+                    .map(|template| {
+                        references(&template)
+                            .into_iter()
+                            .map(|reference| {
+                                (
+                                    reference.provider.to_string(),
+                                    reference.resolved_src(info),
+                                    reference.name.to_string(),
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            })
+            .clone()
+    }
+
+    /// The (provider, path, key) of the secrets every extracted entry is written from.
+    pub fn all_referenced_secrets(
+        &mut self,
+        extracted_infos: &[(String, Vec<SecretInfo>)],
+    ) -> Vec<(String, String, String)> {
+        let mut secrets = Vec::new();
+        for (config_path, infos) in extracted_infos {
+            for info in infos {
+                secrets.extend(self.referenced_secrets(info, config_path));
+            }
+        }
+
+        secrets
+    }
+}
+
+/// How many secrets are fetched at once when prefetching them, so a large workspace doesn't
+/// flood the remote.
+const PREFETCH_CONCURRENCY: usize = 8;
+
+/// Whether at least one extracted entry, or a template rendered by one, needs the
+/// `provider`.
+fn needs_provider(
+    secrets_cache: &mut SecretsCache,
+    extracted_infos: &[(String, Vec<SecretInfo>)],
+    provider: &str,
+) -> bool {
+    secrets_cache
+        .all_referenced_secrets(extracted_infos)
+        .iter()
+        .any(|(referenced_provider, _, _)| referenced_provider == provider)
+}
+
 /// Acquire a Vault (bunker) token only when at least one extracted entry
 /// actually needs it. Returns an empty string for the pure-`wukong` case so
 /// callers can pass it through unchanged.
 pub async fn vault_token_for(
+    secrets_cache: &mut SecretsCache,
     extracted_infos: &[(String, Vec<SecretInfo>)],
     config: &mut Config,
 ) -> Result<String, WKCliError> {
-    let needs_bunker = extracted_infos
-        .iter()
-        .any(|(_, infos)| infos.iter().any(|i| i.provider == "bunker"));
-    if needs_bunker {
+    if needs_provider(secrets_cache, extracted_infos, "bunker") {
         vault::get_token_or_login(config).await
     } else {
         Ok(String::new())
     }
 }
 
-/// Split a wukong-provider `src` (`APPLICATION/NAMESPACE/PATH...`) into its
-/// three components.
-///
-/// Relies on `WKTomlConfigExtractor` having already validated at least three
-/// non-empty segments; panics otherwise rather than forwarding empty strings
-/// to the backend.
-pub fn parse_wukong_src(src: &str) -> (String, String, String) {
-    let mut parts = src.splitn(3, '/');
-    let application = parts
-        .next()
-        .expect("parse_wukong_src: application segment missing")
-        .to_string();
-    let namespace = parts
-        .next()
-        .expect("parse_wukong_src: namespace segment missing")
-        .to_string();
-    let path = parts
-        .next()
-        .expect("parse_wukong_src: path segment missing")
-        .to_string();
-    (application, namespace, path)
+/// The providers serving the extracted entries. The Wukong tokens are refreshed first
+/// when an entry needs them, as the providers call the API with them directly.
+pub async fn secret_providers_for<'a>(
+    secrets_cache: &mut SecretsCache,
+    extracted_infos: &[(String, Vec<SecretInfo>)],
+    wk_client: &'a mut WKClient,
+    vault_token: &'a str,
+) -> Result<SecretProviders<'a>, WKCliError> {
+    if needs_provider(secrets_cache, extracted_infos, "wukong") {
+        wk_client.check_and_refresh_tokens().await?;
+    }
+
+    Ok(wk_client.secret_providers(vault_token))
+}
+
+/// The secrets at `src` of the `provider`, fetched once for each (provider, path).
+pub async fn fetch_secrets<'c>(
+    providers: &SecretProviders<'_>,
+    secrets_cache: &'c mut SecretsCache,
+    provider: &str,
+    src: &str,
+) -> Result<&'c HashMap<String, String>, WKCliError> {
+    let cache_key = (provider.to_string(), src.to_string());
+    if !secrets_cache.secrets.contains_key(&cache_key) {
+        let secrets = providers.get(provider)?.fetch_versioned(src).await?;
+        secrets_cache.secrets.insert(cache_key.clone(), secrets);
+    }
+
+    Ok(&secrets_cache.secrets[&cache_key].data)
+}
+
+/// The secrets of the extracted entries, and of the templates they render, fetched a few at
+/// a time once for each (provider, path). The ones failing to be fetched are left out, so
+/// the error is reported with the entry when it's fetched again.
+pub async fn prefetch_secrets(
+    providers: &SecretProviders<'_>,
+    secrets_cache: &mut SecretsCache,
+    extracted_infos: &[(String, Vec<SecretInfo>)],
+) {
+    let sources = secrets_cache
+        .all_referenced_secrets(extracted_infos)
+        .into_iter()
+        .map(|(provider, src, _)| (provider, src))
+        .filter(|cache_key| !secrets_cache.secrets.contains_key(cache_key))
+        .collect::<BTreeSet<_>>();
+
+    let fetched = stream::iter(sources)
+        .map(|cache_key| async move {
+            let secrets = match providers.get(&cache_key.0) {
+                Ok(provider) => provider.fetch_versioned(&cache_key.1).await,
+                Err(error) => Err(error),
+            };
+            (cache_key, secrets)
+        })
+        .buffer_unordered(PREFETCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    for (cache_key, secrets) in fetched {
+        match secrets {
+            Ok(secrets) => {
+                secrets_cache.secrets.insert(cache_key, secrets);
+            }
+            Err(error) => debug!("Error while prefetching {:?}: {:?}", cache_key, error),
+        }
+    }
+}
+
+/// The config of the entry on the remote, rendered from its template for the `template`
+/// kind. It's `None` if the secret is not found.
+pub async fn get_remote_config(
+    providers: &SecretProviders<'_>,
+    secrets_cache: &mut SecretsCache,
+    info: &SecretInfo,
+    config_path: &str,
+) -> Result<Option<String>, WKCliError> {
+    if info.kind == "template" {
+        let rendered = render_template(providers, secrets_cache, info, config_path).await?;
+        return Ok(Some(rendered));
+    }
+
+    let secrets = fetch_secrets(providers, secrets_cache, &info.provider, &info.src).await?;
+
+    Ok(secrets.get(&info.name).cloned())
 }
 
 pub async fn get_updated_configs<'a>(
-    wk_client: &mut WKClient,
-    vault_token: &str,
+    providers: &SecretProviders<'_>,
+    secrets_cache: &mut SecretsCache,
     config_files: &'a Vec<(String, Vec<SecretInfo>)>,
 ) -> Result<Vec<(&'a SecretInfo, String, String, String)>, WKCliError> {
-    // Comparing local vs remote ....
-    println!("{}", "comparing local config vs remote config...".cyan());
-
     let mut updated_configs = Vec::new();
 
     for config_file in config_files {
         let (config_path, secret_infos) = config_file;
         for info in secret_infos {
-            let remote_secrets = if info.provider == "wukong" {
-                let (app, ns, path) = parse_wukong_src(&info.src);
-                wk_client.get_wukong_secrets(&app, &ns, &path).await?.data
-            } else {
-                wk_client.get_secrets(vault_token, &info.src).await?.data
-            };
+            let remote_config =
+                get_remote_config(providers, secrets_cache, info, config_path).await?;
 
             let local_config = match get_local_config_as_string(&info.destination_file, config_path)
             {
@@ -95,12 +238,12 @@ pub async fn get_updated_configs<'a>(
                 }
             };
 
-            // Get only one key from hashmap. For the `wukong` provider, a
-            // missing key means the path is new — treat it as an empty remote
-            // and let `push` upsert. Bunker keeps the existing strict behavior.
-            let remote_config = match remote_secrets.get(&info.name) {
-                Some(config) => config.clone(),
-                None if info.provider == "wukong" => String::new(),
+            // For the providers creating the missing secrets, like `wukong`, a missing
+            // key means the path is new — treat it as an empty remote and let `push`
+            // upsert. Bunker keeps the existing strict behavior.
+            let remote_config = match remote_config {
+                Some(config) => config,
+                None if providers.get(&info.provider)?.creates_missing_secrets() => String::new(),
                 None => {
                     return Err(WKCliError::DevConfigError(
                         DevConfigError::InvalidSecretPath {
@@ -120,6 +263,63 @@ pub async fn get_updated_configs<'a>(
     Ok(updated_configs)
 }
 
+/// Find the annotation of the secret `key`, i.e. the name after `#` in the annotation, in
+/// the config files of the current directory. The key can be given with its path, e.g.
+/// `vault:secret/mv/tech/app/dev#dotenv`, when several secrets have the same name.
+pub fn find_secret_info(key: &str) -> Result<(String, SecretInfo), WKCliError> {
+    let secret_config_files = get_secret_config_files(None)?;
+    let extracted_infos = extract_secret_infos(secret_config_files)?;
+
+    let (provider, src, name) = match SecretReference::parse(key) {
+        Some(reference) => (
+            Some(reference.provider),
+            Some(reference.src),
+            reference.name,
+        ),
+        None => match key.rsplit_once('#') {
+            Some((src, name)) => (None, Some(src.strip_prefix("secret/").unwrap_or(src)), name),
+            None => (None, None, key),
+        },
+    };
+
+    let mut found = extracted_infos
+        .into_iter()
+        .flat_map(|(config_path, infos)| {
+            infos
+                .into_iter()
+                .map(move |info| (config_path.clone(), info))
+        })
+        .filter(|(_, info)| {
+            info.name == name
+                && (provider.is_none() || provider == Some(info.provider.as_str()))
+                && (src.is_none() || src == Some(info.src.as_str()))
+        })
+        .collect::<Vec<_>>();
+
+    // the same secret can be annotated in several config files, it's only ambiguous when
+    // the secrets are different
+    let secrets = found
+        .iter()
+        .map(|(_, info)| format!("{}:{}#{}", info.provider, info.src, info.name))
+        .collect::<BTreeSet<_>>();
+    if secrets.len() > 1 {
+        return Err(DevConfigError::AmbiguousSecretKey {
+            key: key.to_string(),
+            secrets: secrets.into_iter().collect(),
+        }
+        .into());
+    }
+
+    if found.is_empty() {
+        return Err(DevConfigError::SecretKeyNotFound {
+            key: key.to_string(),
+        }
+        .into());
+    }
+
+    Ok(found.remove(0))
+}
+
 pub fn get_local_config_path(destination_file: &str, config_path: &str) -> PathBuf {
     let path = PathBuf::from(config_path);
     path.parent().unwrap().join(destination_file)
@@ -141,6 +341,7 @@ pub fn get_secret_config_files(current_path: Option<PathBuf>) -> Result<Vec<Path
     let mut overrides = OverrideBuilder::new(current_path.clone());
     overrides.add("**/config/dev.exs").unwrap();
     overrides.add("**/.wukong.toml").unwrap();
+    overrides.add("**/*.{yaml,yml,json}").unwrap();
 
     let config_files = WalkBuilder::new(current_path)
         .overrides(overrides.build().unwrap())
@@ -153,6 +354,107 @@ pub fn get_secret_config_files(current_path: Option<PathBuf>) -> Result<Vec<Path
     Ok(config_files)
 }
 
+/// The content of the YAML or JSON file if it's an annotated config, as most of them
+/// aren't, e.g. `package.json`. It's read once, to be checked then extracted.
+fn read_annotated_config(path: &Path, marker: &str) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) if content.contains(marker) => Some(content),
+        Ok(_) => None,
+        Err(error) => {
+            debug!("Ignoring file {}: {:?}", path.to_string_lossy(), error);
+            None
+        }
+    }
+}
+
+/// The root of the git repository of the current directory, or the current directory when
+/// it's not in one.
+pub fn find_repo_root() -> Result<PathBuf, WKCliError> {
+    let current_path = current_dir()?;
+    let repo_root = current_path
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(&current_path)
+        .to_path_buf();
+
+    Ok(repo_root)
+}
+
+/// The config files under the root of the git repository, with the repo root.
+pub fn get_workspace_secret_infos() -> Result<(PathBuf, Vec<(String, Vec<SecretInfo>)>), WKCliError>
+{
+    let repo_root = find_repo_root()?;
+    let secret_config_files = get_secret_config_files(Some(repo_root.clone()))?;
+    let extracted_infos = extract_secret_infos(secret_config_files)?;
+
+    if extracted_infos.is_empty() {
+        return Err(WKCliError::DevConfigError(DevConfigError::ConfigNotFound));
+    }
+
+    Ok((repo_root, extracted_infos))
+}
+
+/// The config files of an application in the workspace, see [group_by_application].
+pub struct ApplicationSecretInfos {
+    /// The directory of the application relative to the repo root, e.g. `apps/api`.
+    pub name: String,
+    pub extracted_infos: Vec<(String, Vec<SecretInfo>)>,
+}
+
+/// Group the config files by the application they belong to, i.e. the directory of the
+/// config file, or the parent one for the files in a `config` directory like `dev.exs`.
+pub fn group_by_application(
+    repo_root: &Path,
+    extracted_infos: Vec<(String, Vec<SecretInfo>)>,
+) -> Vec<ApplicationSecretInfos> {
+    let mut applications: BTreeMap<String, Vec<(String, Vec<SecretInfo>)>> = BTreeMap::new();
+
+    for (config_path, infos) in extracted_infos {
+        let config_dir = Path::new(&config_path)
+            .parent()
+            .unwrap_or(Path::new(&config_path));
+        let application_dir = match config_dir.file_name() {
+            Some(name) if name == "config" => config_dir.parent().unwrap_or(config_dir),
+            _ => config_dir,
+        };
+        let name = match application_dir.strip_prefix(repo_root) {
+            Ok(name) if name.as_os_str().is_empty() => ".".to_string(),
+            Ok(name) => name.to_string_lossy().to_string(),
+            Err(_) => application_dir.to_string_lossy().to_string(),
+        };
+
+        applications
+            .entry(name)
+            .or_default()
+            .push((config_path, infos));
+    }
+
+    applications
+        .into_iter()
+        .map(|(name, extracted_infos)| ApplicationSecretInfos {
+            name,
+            extracted_infos,
+        })
+        .collect()
+}
+
+/// Print the outcome of each application, e.g. `up-to-date`, in green when it's `ok`.
+pub fn print_applications_summary(outcomes: &[(String, bool, String)]) {
+    println!();
+    println!("{}", "Summary".bold());
+
+    for (name, ok, outcome) in outcomes {
+        // padded before coloring, so the names are aligned
+        let label = format!("{outcome:<16}");
+        let label = if *ok {
+            label.green().to_string()
+        } else {
+            label.red().to_string()
+        };
+        println!("\t{label} {name}");
+    }
+}
+
 pub fn make_path_relative(path: &str) -> String {
     let current_dir = current_dir().unwrap();
     let path = Path::new(path);
@@ -185,6 +487,25 @@ pub fn extract_secret_infos(
                     ElixirConfigExtractor::extract(&file)?,
                 ));
             }
+            x if x.ends_with(".yaml") || x.ends_with(".yml") => {
+                if let Some(content) = read_annotated_config(&file, YamlConfigExtractor::ANNOTATION)
+                {
+                    extracted_infos.push((
+                        file.to_string_lossy().to_string(),
+                        YamlConfigExtractor::extract_from(&file, &content)?,
+                    ));
+                }
+            }
+            x if x.ends_with(".json") => {
+                if let Some(content) =
+                    read_annotated_config(&file, JsonConfigExtractor::SECRETS_KEY)
+                {
+                    extracted_infos.push((
+                        file.to_string_lossy().to_string(),
+                        JsonConfigExtractor::extract_from(&file, &content)?,
+                    ));
+                }
+            }
             x => {
                 debug!("Ignoring file: {}", x);
             }
@@ -200,17 +521,20 @@ mod test {
     use std::fs::File;
     use std::io::Write;
 
-    use assert_fs::prelude::{FileTouch, PathChild};
+    use assert_fs::prelude::{FileTouch, FileWriteStr, PathChild};
 
     use super::*;
 
     #[test]
     fn test_secret_config_files() {
-        // four files:
+        // seven files:
         // temp/config/dev.exs
         // temp/config/prod.exs
         // temp/app/config/dev.exs
         // temp/.wukong.toml
+        // temp/node-app/config/default.yaml (annotated)
+        // temp/node-app/config/default.json (annotated)
+        // temp/node-app/package.json
 
         let temp = assert_fs::TempDir::new().unwrap();
         let dev_config_file = temp.child("config/dev.exs");
@@ -221,16 +545,40 @@ mod test {
         another_dev_config_file.touch().unwrap();
         let wukong_toml_file = temp.child(".wukong.toml");
         wukong_toml_file.touch().unwrap();
+        let yaml_config_file = temp.child("node-app/config/default.yaml");
+        yaml_config_file
+            .write_str(
+                "# wukong.mindvalley.dev/config-secrets-location: vault:secret/node-app/dev#secrets.yaml\nsecrets_file: secrets.yaml\n",
+            )
+            .unwrap();
+        let json_config_file = temp.child("node-app/config/default.json");
+        json_config_file
+            .write_str(r#"{ "$wukongSecrets": [] }"#)
+            .unwrap();
+        let package_json_file = temp.child("node-app/package.json");
+        package_json_file.write_str("{}").unwrap();
 
         let files = get_secret_config_files(Some(temp.to_path_buf())).unwrap();
         let files_names = files
             .iter()
             .map(|f| f.to_string_lossy())
             .collect::<Vec<_>>();
-        assert_eq!(files.len(), 3);
+        // the YAML and JSON files are only checked for the annotation when they're extracted
+        assert_eq!(files.len(), 6);
         assert!(files_names.contains(&dev_config_file.path().to_string_lossy()));
         assert!(files_names.contains(&another_dev_config_file.path().to_string_lossy()));
         assert!(files_names.contains(&wukong_toml_file.path().to_string_lossy()));
+        assert!(files_names.contains(&yaml_config_file.path().to_string_lossy()));
+        assert!(files_names.contains(&json_config_file.path().to_string_lossy()));
+        assert!(files_names.contains(&package_json_file.path().to_string_lossy()));
+
+        let extracted_files = extract_secret_infos(files)
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(extracted_files.len(), 5);
+        assert!(!extracted_files.contains(&package_json_file.path().to_string_lossy().to_string()));
 
         temp.close().unwrap();
     }
@@ -305,6 +653,92 @@ dst = ".env"
         Ok(())
     }
 
+    #[test]
+    fn test_group_by_application() {
+        let extracted_infos = vec![
+            ("/repo/apps/web/.wukong.toml".to_string(), Vec::new()),
+            ("/repo/apps/api/config/dev.exs".to_string(), Vec::new()),
+            ("/repo/apps/api/.wukong.toml".to_string(), Vec::new()),
+            ("/repo/.wukong.toml".to_string(), Vec::new()),
+        ];
+
+        let applications = group_by_application(Path::new("/repo"), extracted_infos);
+
+        let names = applications
+            .iter()
+            .map(|application| application.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![".", "apps/api", "apps/web"]);
+
+        let api_config_paths = applications[1]
+            .extracted_infos
+            .iter()
+            .map(|(config_path, _)| config_path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            api_config_paths,
+            vec![
+                "/repo/apps/api/config/dev.exs",
+                "/repo/apps/api/.wukong.toml"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_referenced_secrets_of_a_template() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let config_path = dir.path().join(".wukong.toml");
+        let template_path = dir.path().join("dev.exs.tmpl");
+        std::fs::write(
+            &template_path,
+            "password: \"{{ bunker:mv/tech/app/dev#DB_PASS }}\"",
+        )?;
+
+        let info = SecretInfo {
+            key: "dev.exs".to_string(),
+            provider: "bunker".to_string(),
+            kind: "template".to_string(),
+            src: "dev.exs.tmpl".to_string(),
+            destination_file: "dev.exs".to_string(),
+            name: String::new(),
+            annotated_file: config_path.clone(),
+        };
+        let config_path = config_path.to_str().unwrap();
+
+        let mut secrets_cache = SecretsCache::new();
+        let expected = vec![(
+            "bunker".to_string(),
+            "mv/tech/app/dev".to_string(),
+            "DB_PASS".to_string(),
+        )];
+        assert_eq!(
+            secrets_cache.referenced_secrets(&info, config_path),
+            expected
+        );
+
+        // the template is parsed once in a command, a new one reads it again
+        std::fs::write(
+            &template_path,
+            "api_key: \"{{ wukong:my-app/staging/dev#API_KEY }}\"",
+        )?;
+        assert_eq!(
+            secrets_cache.referenced_secrets(&info, config_path),
+            expected
+        );
+        assert_eq!(
+            SecretsCache::new().referenced_secrets(&info, config_path),
+            vec![(
+                "wukong".to_string(),
+                "my-app/staging/dev".to_string(),
+                "API_KEY".to_string(),
+            )]
+        );
+
+        dir.close()?;
+
+        Ok(())
+    }
+
     #[test]
     fn test_get_local_config_as_string() -> Result<(), Box<dyn std::error::Error>> {
         let dir = assert_fs::TempDir::new().unwrap();
@@ -1,9 +1,19 @@
-use crate::{auth, config::Config, error::WKCliError, loader::new_spinner};
+use crate::{
+    auth::{self, LoginFlow},
+    config::Config,
+    error::WKCliError,
+    loader::new_spinner,
+};
 
-pub async fn handle_login(config: Option<Config>) -> Result<bool, WKCliError> {
-    let loader = new_spinner().with_message("Logging in to Google Cloud ...");
-    auth::google_cloud::get_token_or_login(config).await;
-    loader.finish_and_clear();
+pub async fn handle_login(config: Option<Config>, flow: LoginFlow) -> Result<bool, WKCliError> {
+    if flow == LoginFlow::Device {
+        // no spinner, it would get in the way of the printed code
+        auth::google_cloud::get_token_or_login_with(config, flow).await?;
+    } else {
+        let loader = new_spinner().with_message("Logging in to Google Cloud ...");
+        auth::google_cloud::get_token_or_login_with(config, flow).await?;
+        loader.finish_and_clear();
+    }
 
     println!("You are logged into Google Cloud. You can now use Wukong to manage your Google Cloud resources");
 
@@ -1,4 +1,4 @@
-use crate::error::WKCliError;
+use crate::{commands::login::LoginArgs, error::WKCliError};
 use clap::{Args, Subcommand};
 use std::str;
 
@@ -14,13 +14,13 @@ pub struct Google {
 #[derive(Debug, Subcommand)]
 pub enum GoogleSubcommand {
     /// Login to Google
-    Login,
+    Login(LoginArgs),
 }
 
 impl Google {
     pub async fn handle_command(&self) -> Result<bool, WKCliError> {
         match &self.subcommand {
-            GoogleSubcommand::Login => handle_login(None).await,
+            GoogleSubcommand::Login(args) => handle_login(None, args.flow()).await,
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    auth::{self, vault},
+    auth::{self, vault, LoginFlow},
     commands::login,
     config::Config,
     error::{ConfigError, WKCliError},
@@ -17,7 +17,7 @@ pub static TMP_CONFIG_FILE: Lazy<Option<String>> = Lazy::new(|| {
     });
 });
 
-pub async fn handle_init() -> Result<bool, WKCliError> {
+pub async fn handle_init(flow: LoginFlow) -> Result<bool, WKCliError> {
     println!("Welcome! This command will take you through the configuration of Wukong.\n");
     let mut config = match Config::load_from_default_path() {
         Ok(config) => config,
@@ -28,8 +28,8 @@ pub async fn handle_init() -> Result<bool, WKCliError> {
         },
     };
 
-    config = login::new_login_or_refresh_token(config).await?;
-    config = handle_gcloud_auth(config).await?;
+    config = login::new_login_or_refresh_token(config, flow).await?;
+    config = handle_gcloud_auth(config, flow).await?;
     config = handle_vault_auth(config).await?;
 
     config
@@ -77,7 +77,7 @@ async fn handle_vault_auth(mut config: Config) -> Result<Config, WKCliError> {
     Ok(config)
 }
 
-async fn handle_gcloud_auth(mut config: Config) -> Result<Config, WKCliError> {
+async fn handle_gcloud_auth(mut config: Config, flow: LoginFlow) -> Result<Config, WKCliError> {
     let agree_to_authenticate =
         inquire::Confirm::new("Do you want to authenticate against Google Cloud?")
             .with_render_config(inquire_render_config())
@@ -92,10 +92,14 @@ async fn handle_gcloud_auth(mut config: Config) -> Result<Config, WKCliError> {
         let tmp_config = Config::default()
             .with_path(TMP_CONFIG_FILE.to_owned().expect("Unable to get tmp path"));
 
-        let loader = new_spinner().with_message("Logging in to Google Cloud ...");
-
-        auth::google_cloud::get_token_or_login(Some(tmp_config.clone())).await;
-        loader.finish_and_clear();
+        if flow == LoginFlow::Device {
+            // no spinner, it would get in the way of the printed code
+            auth::google_cloud::get_token_or_login_with(Some(tmp_config.clone()), flow).await?;
+        } else {
+            let loader = new_spinner().with_message("Logging in to Google Cloud ...");
+            auth::google_cloud::get_token_or_login_with(Some(tmp_config.clone()), flow).await?;
+            loader.finish_and_clear();
+        }
         println!("You are logged into Google Cloud. You can now use Wukong to manage your Google Cloud resources");
 
         // Load the config again to get the latest token
@@ -1,24 +1,45 @@
 use crate::{
-    auth,
+    auth::{self, LoginFlow},
     config::Config,
     error::{AuthError, WKCliError},
     loader::new_spinner,
     output::colored_println,
     utils::inquire::inquire_render_config,
 };
+use clap::Args;
 use crossterm::style::Stylize;
 use log::debug;
 
-pub async fn handle_login() -> Result<bool, WKCliError> {
+#[derive(Debug, Args)]
+pub struct LoginArgs {
+    /// Login with a browser on another device instead of opening one here, e.g. over SSH
+    #[arg(long, visible_alias = "no-browser")]
+    pub device: bool,
+}
+
+impl LoginArgs {
+    pub fn flow(&self) -> LoginFlow {
+        if self.device {
+            LoginFlow::Device
+        } else {
+            LoginFlow::Browser
+        }
+    }
+}
+
+pub async fn handle_login(flow: LoginFlow) -> Result<bool, WKCliError> {
     let config = Config::load_from_default_path()?;
 
-    let new_config = new_login_or_refresh_token(config).await?;
+    let new_config = new_login_or_refresh_token(config, flow).await?;
     new_config.save_to_default_path()?;
 
     Ok(true)
 }
 
-pub async fn new_login_or_refresh_token(config: Config) -> Result<Config, WKCliError> {
+pub async fn new_login_or_refresh_token(
+    config: Config,
+    flow: LoginFlow,
+) -> Result<Config, WKCliError> {
     let mut login_selections = vec!["Login with a new account"];
     if let Some(ref okta_config) = config.auth.okta {
         login_selections.splice(..0, vec![okta_config.account.as_str()]);
@@ -33,7 +54,7 @@ pub async fn new_login_or_refresh_token(config: Config) -> Result<Config, WKCliE
 
     // "Log in with a new account" is selected
     let new_config = if selected_account == "Login with a new account" {
-        login_and_create_config(config).await?
+        login_and_create_config(config, flow).await?
     } else {
         // check access token expiry
         let mut current_config = config.clone();
@@ -57,7 +78,7 @@ pub async fn new_login_or_refresh_token(config: Config) -> Result<Config, WKCliE
                     match err {
                         WKCliError::AuthError(AuthError::OktaRefreshTokenExpired { .. }) => {
                             eprintln!("The refresh token is expired. You have to login again.");
-                            login_and_create_config(current_config).await?
+                            login_and_create_config(current_config, flow).await?
                         }
                         err => return Err(err),
                     }
@@ -75,8 +96,11 @@ pub async fn new_login_or_refresh_token(config: Config) -> Result<Config, WKCliE
     Ok(new_config)
 }
 
-async fn login_and_create_config(mut config: Config) -> Result<Config, WKCliError> {
-    let auth_info = auth::okta::login().await?;
+async fn login_and_create_config(
+    mut config: Config,
+    flow: LoginFlow,
+) -> Result<Config, WKCliError> {
+    let auth_info = auth::okta::login(flow).await?;
     let acc = auth_info.account.clone();
 
     config.auth.okta = Some(auth_info.into());
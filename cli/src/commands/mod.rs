@@ -10,20 +10,26 @@ use crate::{
     },
     config::{ApiChannel, Config},
     error::WKCliError,
-    update,
 };
 
 mod application;
+mod auth;
+mod cache;
 mod completion;
 mod config;
+mod debug;
 mod deployment;
 mod dev;
 mod google;
 mod init;
 mod login;
 mod skills;
+mod telemetry;
 mod test;
 mod tui;
+mod update;
+
+pub use completion::complete_dynamically;
 
 #[derive(Debug, Default)]
 pub struct Context {
@@ -49,6 +55,10 @@ pub struct ClapApp {
     /// Use the Canary channel API
     #[arg(long, global = true, default_value_t = true)]
     canary: bool,
+
+    /// Always fetch from the Wukong API instead of using the cached responses
+    #[arg(long, global = true)]
+    pub no_cache: bool,
 }
 
 #[derive(Debug)]
@@ -79,7 +89,7 @@ impl LogLevel for ErrorLevel {
 #[derive(Debug, Subcommand)]
 pub enum CommandGroup {
     /// Initialize Wukong's configurations
-    Init,
+    Init(login::LoginArgs),
     /// This command group contains the commands to interact with an application’s configurations
     Application(application::Application),
     /// This command group contains the commands to view and interact with the
@@ -90,14 +100,27 @@ pub enum CommandGroup {
     /// This command group contains the commands to view & interact with Wukong's configurations
     Config(config::Config),
     /// Login to start using wukong command
-    Login,
+    Login(login::LoginArgs),
+    /// This command group contains the commands to view and manage the logged in accounts
+    Auth(auth::Auth),
     /// This command group contains the commands to interact with Google services
     Google(google::Google),
+    /// This command group contains the commands to manage the cached API responses
+    Cache(cache::Cache),
+    /// This command group contains the commands to inspect and opt out of the telemetry
+    Telemetry(telemetry::Telemetry),
+    /// This command group contains the commands to troubleshoot wukong and report bugs
+    Debug(debug::Debug),
     /// Generate wukong cli completions for your shell to stdout
     Completion {
         #[arg(value_enum)]
         shell: Shell,
+        /// Also complete the values from the Wukong API, e.g. the build artifacts
+        #[arg(long)]
+        dynamic: bool,
     },
+    /// Update wukong to the latest release
+    Update(update::Update),
     /// This command group contains the commands to manage agent skills locally
     Skills(skills::Skills),
     /// Start TUI session
@@ -118,10 +141,15 @@ impl ClapApp {
         debug!("API channel: {:?}", channel);
 
         let command = match &self.command_group {
-            CommandGroup::Init => handle_init().await,
-            CommandGroup::Completion { shell } => handle_completion(*shell),
-            CommandGroup::Login => handle_login().await,
+            CommandGroup::Init(args) => handle_init(args.flow()).await,
+            CommandGroup::Completion { shell, dynamic } => handle_completion(*shell, *dynamic),
+            CommandGroup::Login(args) => handle_login(args.flow()).await,
+            CommandGroup::Auth(auth) => auth.handle_command().await,
             CommandGroup::Google(google) => google.handle_command().await,
+            CommandGroup::Cache(cache) => cache.handle_command(),
+            CommandGroup::Telemetry(telemetry) => telemetry.handle_command().await,
+            CommandGroup::Debug(debug) => debug.handle_command(),
+            CommandGroup::Update(update) => update.handle_command().await,
             CommandGroup::Application(application) => application.handle_command(self).await,
             CommandGroup::Deployment(deployment) => {
                 deployment.handle_command(get_context(self)?).await
@@ -136,7 +164,7 @@ impl ClapApp {
         // Check for CLI updates:
         // Disabled check_for_update in test and dev as snapshots keep changing with each run
         if cfg!(feature = "prod") {
-            update::check_for_update().await;
+            crate::update::check_for_update().await;
         }
 
         command
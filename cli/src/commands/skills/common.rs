@@ -1,5 +1,5 @@
 use std::{
-    fs,
+    env, fs,
     path::{Path, PathBuf},
 };
 
@@ -24,6 +24,68 @@ Archiving is reversible: run `wukong skills restore` to move a skill back into
 You can safely delete a subfolder here to remove an archived skill permanently.
 ";
 
+/// A skill installed in one of the global or project skill directories.
+#[derive(Debug, Clone)]
+pub struct InstalledSkill {
+    /// Where it is installed, e.g. `global:.claude` or `project:.agents`.
+    pub scope: String,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// List the skills installed globally and in the current project, i.e. the folders
+/// with a `SKILL.md` under `.claude/skills` and `.agents/skills`.
+pub fn installed_skills() -> std::io::Result<Vec<InstalledSkill>> {
+    let cwd = env::current_dir()?;
+    let home = dirs::home_dir();
+
+    let mut roots: Vec<(String, PathBuf)> = Vec::new();
+    if let Some(h) = home.as_ref() {
+        roots.push((
+            "global:.claude".to_string(),
+            h.join(".claude").join(SKILLS_DIR),
+        ));
+        roots.push((
+            "global:.agents".to_string(),
+            h.join(".agents").join(SKILLS_DIR),
+        ));
+    }
+    roots.push((
+        "project:.claude".to_string(),
+        cwd.join(".claude").join(SKILLS_DIR),
+    ));
+    roots.push((
+        "project:.agents".to_string(),
+        cwd.join(".agents").join(SKILLS_DIR),
+    ));
+
+    let mut skills = Vec::new();
+    for (scope, root) in roots {
+        let read = match fs::read_dir(&root) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        for dir_entry in read.flatten() {
+            let path = dir_entry.path();
+            if !path.is_dir() || !path.join("SKILL.md").is_file() {
+                continue;
+            }
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("<unknown>")
+                .to_string();
+            skills.push(InstalledSkill {
+                scope: scope.clone(),
+                name,
+                path,
+            });
+        }
+    }
+
+    Ok(skills)
+}
+
 /// Write an explanatory README into the archive folder if one isn't there yet.
 pub fn ensure_archive_readme(archive_root: &Path) -> std::io::Result<()> {
     let readme = archive_root.join(ARCHIVE_README);
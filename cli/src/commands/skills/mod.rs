@@ -10,16 +10,19 @@ mod restore;
 mod update;
 
 use clap::{Args, Subcommand};
+use clap_complete::engine::ArgValueCompleter;
 
 use crate::error::WKCliError;
 
+pub use self::common::installed_skills;
+
 use self::{
     add::handle_skills_add, archive::handle_skills_archive, find::handle_skills_find,
     init::handle_skills_init, list::handle_skills_list, publish::handle_skills_publish,
     remove::handle_skills_remove, restore::handle_skills_restore, update::handle_skills_update,
 };
 
-use super::Context;
+use super::{completion::candidates, Context};
 
 #[derive(Debug, Args)]
 pub struct Skills {
@@ -43,6 +46,7 @@ pub enum SkillsSubcommand {
     /// Install one or more skills from the registry locally
     Add {
         /// Skill name or keyword to search for
+        #[arg(add = ArgValueCompleter::new(candidates::skill_slugs))]
         name: Option<String>,
         /// Install globally (home directory) instead of the current project
         #[arg(long)]
@@ -52,7 +56,11 @@ pub enum SkillsSubcommand {
         project: bool,
     },
     /// Remove installed skills from local and global skill directories
-    Remove,
+    Remove {
+        /// The names of the skills to remove, instead of choosing them interactively
+        #[arg(add = ArgValueCompleter::new(candidates::installed_skills))]
+        names: Vec<String>,
+    },
     /// Update all outdated installed skills from the registry
     Update,
     /// Archive installed skills (move out of active folder, reversible)
@@ -73,7 +81,7 @@ impl Skills {
                 global,
                 project,
             } => handle_skills_add(context, name, *global, *project).await,
-            SkillsSubcommand::Remove => handle_skills_remove(context).await,
+            SkillsSubcommand::Remove { names } => handle_skills_remove(context, names).await,
             SkillsSubcommand::Update => handle_skills_update(context).await,
             SkillsSubcommand::Archive => handle_skills_archive(context).await,
             SkillsSubcommand::Restore => handle_skills_restore(context).await,
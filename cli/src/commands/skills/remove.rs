@@ -1,7 +1,8 @@
-use std::{env, fs, path::PathBuf};
+use std::{fs, path::PathBuf};
 
 use crossterm::style::Stylize;
 
+use super::common::installed_skills;
 use crate::{commands::Context, error::WKCliError, utils::inquire::inquire_render_config};
 
 use wukong_telemetry::*;
@@ -10,6 +11,7 @@ use wukong_telemetry_macro::*;
 #[derive(Debug, Clone)]
 struct SkillEntry {
     label: String,
+    name: String,
     path: PathBuf,
 }
 
@@ -20,68 +22,48 @@ impl std::fmt::Display for SkillEntry {
 }
 
 #[wukong_telemetry(command_event = "skills_remove")]
-pub async fn handle_skills_remove(context: Context) -> Result<bool, WKCliError> {
-    let cwd = env::current_dir()?;
-    let home = dirs::home_dir();
-
-    let mut roots: Vec<(String, PathBuf)> = Vec::new();
-    if let Some(h) = home.as_ref() {
-        roots.push((
-            "global:.claude".to_string(),
-            h.join(".claude").join("skills"),
-        ));
-        roots.push((
-            "global:.agents".to_string(),
-            h.join(".agents").join("skills"),
-        ));
-    }
-    roots.push((
-        "project:.claude".to_string(),
-        cwd.join(".claude").join("skills"),
-    ));
-    roots.push((
-        "project:.agents".to_string(),
-        cwd.join(".agents").join("skills"),
-    ));
-
-    let mut entries: Vec<SkillEntry> = Vec::new();
-    for (scope, root) in &roots {
-        if !root.is_dir() {
-            continue;
-        }
-        let read = match fs::read_dir(root) {
-            Ok(r) => r,
-            Err(_) => continue,
-        };
-        for dir_entry in read.flatten() {
-            let path = dir_entry.path();
-            if !path.is_dir() {
-                continue;
-            }
-            if !path.join("SKILL.md").is_file() {
-                continue;
-            }
-            let name = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("<unknown>")
-                .to_string();
-            entries.push(SkillEntry {
-                label: format!("[{}] {}", scope, name),
-                path,
-            });
-        }
-    }
+pub async fn handle_skills_remove(context: Context, names: &[String]) -> Result<bool, WKCliError> {
+    let entries: Vec<SkillEntry> = installed_skills()?
+        .into_iter()
+        .map(|skill| SkillEntry {
+            label: format!("[{}] {}", skill.scope, skill.name),
+            name: skill.name,
+            path: skill.path,
+        })
+        .collect();
 
     if entries.is_empty() {
         println!("No installed skills found.");
         return Ok(true);
     }
 
-    let selected = inquire::MultiSelect::new("Select skills to remove", entries.clone())
-        .with_render_config(inquire_render_config())
-        .with_help_message("↑↓ to move, space to select, ↵ to confirm, esc to cancel")
-        .prompt()?;
+    let selected = if names.is_empty() {
+        inquire::MultiSelect::new("Select skills to remove", entries.clone())
+            .with_render_config(inquire_render_config())
+            .with_help_message("↑↓ to move, space to select, ↵ to confirm, esc to cancel")
+            .prompt()?
+    } else {
+        let unknown_names: Vec<&String> = names
+            .iter()
+            .filter(|name| !entries.iter().any(|entry| &entry.name == *name))
+            .collect();
+        if !unknown_names.is_empty() {
+            for name in unknown_names {
+                println!(
+                    "  {} {}",
+                    "Not installed".yellow().bold(),
+                    name.clone().blue()
+                );
+            }
+            return Ok(false);
+        }
+
+        // a skill is removed from every directory it is installed in
+        entries
+            .into_iter()
+            .filter(|entry| names.contains(&entry.name))
+            .collect()
+    };
 
     if selected.is_empty() {
         println!("No skills selected.");
@@ -0,0 +1,15 @@
+use wukong_telemetry::Telemetry;
+
+use super::{save_telemetry_config, telemetry_file};
+use crate::error::WKCliError;
+
+pub fn handle_disable() -> Result<bool, WKCliError> {
+    save_telemetry_config(false)?;
+
+    // the queued events are never sent once it's disabled
+    let count = Telemetry::new(telemetry_file()).clear()?;
+
+    println!("Telemetry is disabled. Removed {count} queued events.");
+
+    Ok(true)
+}
@@ -0,0 +1,19 @@
+use owo_colors::OwoColorize;
+use wukong_telemetry::{is_disabled_by_env, TELEMETRY_ENV};
+
+use super::save_telemetry_config;
+use crate::error::WKCliError;
+
+pub fn handle_enable() -> Result<bool, WKCliError> {
+    save_telemetry_config(true)?;
+
+    println!("Telemetry is enabled.");
+    if is_disabled_by_env() {
+        println!(
+            "{}",
+            format!("It stays disabled while {TELEMETRY_ENV}=off is set.").yellow()
+        );
+    }
+
+    Ok(true)
+}
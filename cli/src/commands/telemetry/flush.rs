@@ -0,0 +1,22 @@
+use wukong_telemetry::{is_enabled, Telemetry};
+
+use super::telemetry_file;
+use crate::{error::WKCliError, loader::new_spinner};
+
+pub async fn handle_flush() -> Result<bool, WKCliError> {
+    if !is_enabled() {
+        println!("Telemetry is disabled, nothing is sent.");
+        return Ok(true);
+    }
+
+    let loader = new_spinner();
+    loader.set_message("Sending the queued events ...");
+    let sent = Telemetry::new(telemetry_file()).flush().await;
+    loader.finish_and_clear();
+
+    let count = sent?;
+
+    println!("Sent {count} queued events.");
+
+    Ok(true)
+}
@@ -0,0 +1,68 @@
+mod disable;
+mod enable;
+mod flush;
+mod show;
+mod status;
+
+use clap::{Args, Subcommand};
+use wukong_telemetry::TELEMETRY_FILE;
+
+use crate::{
+    config::{Config, TelemetryConfig},
+    error::WKCliError,
+};
+
+use self::{
+    disable::handle_disable, enable::handle_enable, flush::handle_flush, show::handle_show,
+    status::handle_status,
+};
+
+#[derive(Debug, Args)]
+pub struct Telemetry {
+    #[command(subcommand)]
+    pub subcommand: TelemetrySubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TelemetrySubcommand {
+    /// Show whether the telemetry is enabled, and how many events are queued
+    Status,
+    /// Print the queued events, which are not sent yet
+    Show,
+    /// Stop recording the usage events, and remove the queued ones
+    Disable,
+    /// Record the usage events again
+    Enable,
+    /// Send the queued events now, instead of waiting for the batch to be full
+    Flush,
+}
+
+impl Telemetry {
+    pub async fn handle_command(&self) -> Result<bool, WKCliError> {
+        match &self.subcommand {
+            TelemetrySubcommand::Status => handle_status(),
+            TelemetrySubcommand::Show => handle_show(),
+            TelemetrySubcommand::Disable => handle_disable(),
+            TelemetrySubcommand::Enable => handle_enable(),
+            TelemetrySubcommand::Flush => handle_flush().await,
+        }
+    }
+}
+
+fn telemetry_file() -> &'static str {
+    TELEMETRY_FILE
+        .as_ref()
+        .expect("Unable to identify user's home directory")
+}
+
+fn save_telemetry_config(enabled: bool) -> Result<(), WKCliError> {
+    let mut config = Config::load_from_default_path()?;
+    // the exporter settings are kept
+    config
+        .telemetry
+        .get_or_insert_with(TelemetryConfig::default)
+        .enabled = enabled;
+    config.save_to_default_path()?;
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use wukong_telemetry::Telemetry;
+
+use super::telemetry_file;
+use crate::error::WKCliError;
+
+pub fn handle_show() -> Result<bool, WKCliError> {
+    let events = Telemetry::new(telemetry_file()).events()?;
+
+    if events.is_empty() {
+        println!("There are no queued events.");
+        return Ok(true);
+    }
+
+    let events = serde_json::to_string_pretty(&events)
+        .expect("The telemetry events are always serializable");
+    println!("{events}");
+
+    Ok(true)
+}
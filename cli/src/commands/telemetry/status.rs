@@ -0,0 +1,34 @@
+use owo_colors::OwoColorize;
+use wukong_telemetry::{is_disabled_by_env, is_enabled, ExporterKind, Telemetry, TELEMETRY_ENV};
+
+use super::telemetry_file;
+use crate::{config::TelemetryConfig, error::WKCliError};
+
+pub fn handle_status() -> Result<bool, WKCliError> {
+    if is_disabled_by_env() {
+        println!("Telemetry is {} by {TELEMETRY_ENV}=off.", "disabled".red());
+    } else if !is_enabled() {
+        println!(
+            "Telemetry is {}. Run {} to enable it.",
+            "disabled".red(),
+            "wukong telemetry enable".yellow()
+        );
+    } else {
+        println!("Telemetry is {}.", "enabled".green());
+    }
+
+    let config = TelemetryConfig::load_from_default_path();
+    let exporter = match config.exporter {
+        ExporterKind::Honeycomb => "honeycomb",
+        ExporterKind::Otlp => "otlp",
+    };
+    match &config.endpoint {
+        Some(endpoint) => println!("Exporter: {exporter} ({endpoint})"),
+        None => println!("Exporter: {exporter}"),
+    }
+
+    let events = Telemetry::new(telemetry_file()).events()?;
+    println!("Queued events: {} ({})", events.len(), telemetry_file());
+
+    Ok(true)
+}
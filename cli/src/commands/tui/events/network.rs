@@ -395,7 +395,7 @@ async fn get_gcloud_logs(
 
     drop(app_ref);
 
-    let gcloud_access_token = auth::google_cloud::get_token_or_login(None).await;
+    let gcloud_access_token = auth::google_cloud::get_token_or_login(None).await?;
 
     if let Some(namespace) = namespace {
         if let Some(version) = version {
@@ -774,7 +774,7 @@ async fn get_database_metrics(
 
     drop(app_ref);
 
-    let gcloud_access_token = auth::google_cloud::get_token_or_login(None).await;
+    let gcloud_access_token = auth::google_cloud::get_token_or_login(None).await?;
     if let Some(namespace) = namespace {
         if let Some(version) = version {
             let application_resp = match wk_client
@@ -9,6 +9,7 @@ use crate::{
     application_config::ApplicationConfigs,
     config::{ApiChannel, Config},
     error::WKCliError,
+    wukong_client,
 };
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
@@ -89,6 +90,9 @@ impl<T> StatefulList<T> {
 }
 
 pub async fn handle_tui(channel: ApiChannel) -> Result<bool, WKCliError> {
+    // the TUI keeps refreshing the deployments, so it always needs the latest data
+    wukong_client::disable_cache();
+
     let application_config = ApplicationConfigs::load()?;
     let (sender, mut receiver) = tokio::sync::mpsc::channel::<NetworkEvent>(100);
 
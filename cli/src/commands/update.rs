@@ -0,0 +1,96 @@
+use clap::{crate_version, Args};
+use owo_colors::OwoColorize;
+
+use crate::{
+    error::WKCliError,
+    loader::new_spinner,
+    update::{
+        fetch_newer_releases, fetch_release_info, install_release, is_homebrew_install,
+        is_newer_than_current, release_notes, update_channel, update_message, GithubReleaseInfo,
+        GITHUB_API_URL,
+    },
+};
+
+#[derive(Debug, Args)]
+pub struct Update {
+    /// Only check whether a newer release is available, without installing it
+    #[arg(long)]
+    check: bool,
+
+    /// Install this release instead of the latest one, e.g. 2.1.5
+    #[arg(long)]
+    version: Option<String>,
+}
+
+impl Update {
+    pub async fn handle_command(&self) -> Result<bool, WKCliError> {
+        let current_version = crate_version!();
+
+        let loader = new_spinner();
+        loader.set_message("Fetching the release info ...");
+        // the newer releases on the configured channel, or only the requested one
+        let releases = match &self.version {
+            Some(version) => fetch_release_info(&GITHUB_API_URL, version.trim_start_matches('v'))
+                .await
+                .map(|release| vec![release]),
+            None => fetch_newer_releases(&GITHUB_API_URL, update_channel()).await,
+        };
+        loader.finish_and_clear();
+        let releases = releases?;
+
+        if self.check {
+            let newer_releases: Vec<GithubReleaseInfo> = releases
+                .into_iter()
+                .filter(|release| is_newer_than_current(&release.tag_name))
+                .collect();
+
+            if newer_releases.is_empty() {
+                println!("wukong {current_version} is up to date.");
+            } else {
+                print!("{}", update_message(&newer_releases));
+            }
+
+            return Ok(true);
+        }
+
+        let Some(release) = releases.first() else {
+            println!("wukong {current_version} is up to date.");
+            return Ok(true);
+        };
+
+        if release.tag_name.trim_start_matches('v') == current_version {
+            println!("wukong {current_version} is already installed.");
+            return Ok(true);
+        }
+
+        let binary_path = std::env::current_exe()?;
+        if is_homebrew_install(&binary_path) {
+            println!("wukong is installed with Homebrew, which should be the one updating it.");
+            println!(
+                "To upgrade, run: {}",
+                "brew update && brew upgrade wukong".yellow()
+            );
+            return Ok(true);
+        }
+
+        if is_newer_than_current(&release.tag_name) {
+            print!("{}", release_notes(&releases));
+        }
+
+        let loader = new_spinner();
+        loader.set_message(format!("Installing wukong {} ...", release.tag_name));
+        let installed = install_release(release, &binary_path).await;
+        loader.finish_and_clear();
+        installed?;
+
+        println!(
+            "{} {} {} {}",
+            "Updated wukong:".green(),
+            current_version.cyan(),
+            "→".cyan(),
+            release.tag_name.cyan(),
+        );
+
+        Ok(true)
+    }
+}
@@ -1,11 +1,17 @@
-use crate::{auth::google_cloud::GoogleCloudConfig, error::ConfigError};
+use crate::{
+    auth::{
+        ci,
+        credential_store::{self, CredentialStore},
+        google_cloud::GoogleCloudConfig,
+    },
+    error::ConfigError,
+    utils::fs::write_private_file,
+};
+use log::debug;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::{create_dir_all, File},
-    io::{self, Write},
-    path::Path,
-};
+use std::{collections::BTreeMap, fs::create_dir_all, io, path::Path};
+use wukong_telemetry::{ExporterConfig, ExporterKind};
 
 #[cfg(not(feature = "prod"))]
 static WUKONG_API_URL: &str = "http://localhost:4000/api";
@@ -45,12 +51,38 @@ pub static CONFIG_FILE: Lazy<Option<String>> = Lazy::new(|| {
     }
 });
 
+/// The directory of the cached API responses, next to the [CONFIG_FILE].
+///
+/// > `~/.config/wukong/cache`
+pub static CACHE_DIR: Lazy<Option<String>> = Lazy::new(|| {
+    CONFIG_FILE.as_ref().and_then(|config_file| {
+        std::path::Path::new(config_file)
+            .parent()
+            .map(|dir| dir.join("cache").to_string_lossy().to_string())
+    })
+});
+
+/// The file recording the content of the dev configs at the last pull, the base of the
+/// three-way merge of `wukong dev config push` and `pull`, next to the [CONFIG_FILE].
+///
+/// > `~/.config/wukong/dev-config-state.json`
+pub static DEV_CONFIG_STATE_FILE: Lazy<Option<String>> = Lazy::new(|| {
+    CONFIG_FILE.as_ref().and_then(|config_file| {
+        std::path::Path::new(config_file).parent().map(|dir| {
+            dir.join("dev-config-state.json")
+                .to_string_lossy()
+                .to_string()
+        })
+    })
+});
+
 /// The Wukong CLI configuration.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct Config {
     pub core: CoreConfig,
     pub auth: AuthConfig,
     pub update_check: Option<UpdateCheck>,
+    pub telemetry: Option<TelemetryConfig>,
     #[serde(skip)]
     config_path: Option<String>,
 }
@@ -67,8 +99,12 @@ pub struct CoreConfig {
     pub wukong_api_url: String,
 }
 
+/// The tokens (`api_token` here and the ones in [OktaConfig] and [GoogleCloudConfig])
+/// are kept in the credential store, not in `config.toml`. They are filled in when the
+/// config is loaded, and left empty in the file.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct VaultConfig {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub api_token: String,
     pub expiry_time: String,
 }
@@ -78,9 +114,12 @@ pub struct OktaConfig {
     pub client_id: String,
     pub account: String,
     pub subject: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub id_token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub access_token: String,
     pub expiry_time: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub refresh_token: String,
 }
 
@@ -91,11 +130,109 @@ pub struct AuthConfig {
     pub google_cloud: Option<GoogleCloudConfig>,
 }
 
+/// How wukong checks for a newer release after running a command.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct UpdateCheck {
+    #[serde(default = "default_update_check_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub channel: UpdateChannel,
+    /// The hours to wait between two checks.
+    #[serde(default = "default_update_check_interval_hours")]
+    pub interval_hours: u64,
+    /// Empty when it has never checked.
+    #[serde(default)]
     pub last_update_checked_at: String,
 }
 
+/// The releases to be notified of. `prerelease` also includes the releases marked as
+/// pre-releases on Github, e.g. `2.2.0-beta.1`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Prerelease,
+}
+
+fn default_update_check_enabled() -> bool {
+    true
+}
+
+fn default_update_check_interval_hours() -> u64 {
+    72
+}
+
+/// The usage events sent to the Wukong team, to see which commands are used and how the
+/// API performs. `WUKONG_TELEMETRY=off` also disables it, whatever the config says.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TelemetryConfig {
+    #[serde(default = "default_telemetry_enabled")]
+    pub enabled: bool,
+    /// `honeycomb`, or `otlp` to send the events as spans to an OpenTelemetry collector.
+    #[serde(default)]
+    pub exporter: ExporterKind,
+    /// The base URL of the exporter, e.g. `http://localhost:4318` for a local collector.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// Sent with every request of the exporter, e.g. the API key of the collector.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+fn default_telemetry_enabled() -> bool {
+    true
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_telemetry_enabled(),
+            exporter: ExporterKind::default(),
+            endpoint: None,
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+impl TelemetryConfig {
+    /// Read only the `telemetry` section of the config file, without touching the
+    /// credential store, as it's done on every run. The default is used if there is no
+    /// config file, or it can't be read.
+    pub fn load_from_default_path() -> Self {
+        #[derive(Deserialize)]
+        struct TelemetrySection {
+            telemetry: Option<TelemetryConfig>,
+        }
+
+        CONFIG_FILE
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str::<TelemetrySection>(&content).ok())
+            .and_then(|section| section.telemetry)
+            .unwrap_or_default()
+    }
+
+    pub fn exporter_config(&self) -> ExporterConfig {
+        ExporterConfig {
+            kind: self.exporter,
+            endpoint: self.endpoint.clone(),
+            headers: self.headers.clone(),
+        }
+    }
+}
+
+impl Default for UpdateCheck {
+    fn default() -> Self {
+        Self {
+            enabled: default_update_check_enabled(),
+            channel: UpdateChannel::default(),
+            interval_hours: default_update_check_interval_hours(),
+            last_update_checked_at: String::new(),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         let mut home_dir = dirs::home_dir().unwrap();
@@ -111,6 +248,7 @@ impl Default for Config {
                 google_cloud: None,
             },
             update_check: None,
+            telemetry: None,
             config_path: None,
         }
     }
@@ -126,23 +264,46 @@ impl Config {
 
     /// Load a configuration from default path.
     ///
+    /// When there is no config file but the CI credentials are set in the environment,
+    /// the default configuration is used instead.
+    ///
     /// # Errors
     ///
     /// This function may return typical file I/O errors.
     pub fn load_from_default_path() -> Result<Self, ConfigError> {
-        Self::load_from_path(
+        let result = Self::load_from_path(
             CONFIG_FILE
                 .as_ref()
                 .expect("Unable to identify user's home directory"),
-        )
+        );
+
+        match result {
+            Err(ConfigError::NotFound { path, .. }) if ci::has_credentials() => {
+                debug!(
+                    "No config file at {path}, using the default config with the CI credentials."
+                );
+                Ok(Self::default())
+            }
+            result => result,
+        }
     }
 
-    /// Load a configuration from file.
+    /// Load a configuration from file, with the tokens from the credential store.
+    ///
+    /// Tokens still stored in plaintext by an older version are moved to the
+    /// credential store, and removed from the file.
     ///
     /// # Errors
     ///
     /// This function may return typical file I/O errors.
     pub fn load_from_path(path: &'static str) -> Result<Self, ConfigError> {
+        Self::load_from_path_with_store(path, credential_store::for_config_path(path).as_ref())
+    }
+
+    fn load_from_path_with_store(
+        path: &'static str,
+        store: &dyn CredentialStore,
+    ) -> Result<Self, ConfigError> {
         let config_file_path = Path::new(path);
 
         let content = std::fs::read_to_string(
@@ -156,7 +317,16 @@ impl Config {
             _ => err.into(),
         })?;
 
-        let config = toml::from_str(&content).map_err(ConfigError::BadTomlData)?;
+        let mut config: Config = toml::from_str(&content).map_err(ConfigError::BadTomlData)?;
+
+        if !config.credentials().is_empty() {
+            debug!(
+                "Moving the plaintext tokens in {path} to the {} credential store.",
+                store.name()
+            );
+            config.save_to_path_with_store(path, store)?;
+        }
+        config.load_credentials(store)?;
 
         Ok(config)
     }
@@ -170,14 +340,122 @@ impl Config {
     ///
     /// This function may return typical file I/O errors.
     fn save_to_path(&self, path: &str) -> Result<(), ConfigError> {
+        self.save_to_path_with_store(path, credential_store::for_config_path(path).as_ref())
+    }
+
+    /// The tokens are written to `store`, and the rest of the config to `path`,
+    /// which is only readable by the current user.
+    fn save_to_path_with_store(
+        &self,
+        path: &str,
+        store: &dyn CredentialStore,
+    ) -> Result<(), ConfigError> {
+        for (key, value) in self.credentials() {
+            store.set(key, value)?;
+        }
+
         let config_file_path = Path::new(path);
-        let serialized = toml::to_string(self).map_err(ConfigError::SerializeTomlError)?;
+        let serialized = toml::to_string(&self.without_credentials())
+            .map_err(ConfigError::SerializeTomlError)?;
 
         if let Some(outdir) = config_file_path.parent() {
             create_dir_all(outdir)?;
         }
-        let mut file = File::create(path)?;
-        file.write_all(&serialized.into_bytes())?;
+        write_private_file(config_file_path, serialized.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// A copy of the config without the tokens, e.g. for displaying it.
+    pub fn without_credentials(&self) -> Self {
+        let mut config = self.clone();
+
+        if let Some(okta) = config.auth.okta.as_mut() {
+            okta.id_token.clear();
+            okta.access_token.clear();
+            okta.refresh_token.clear();
+        }
+        if let Some(vault) = config.auth.vault.as_mut() {
+            vault.api_token.clear();
+        }
+        if let Some(google_cloud) = config.auth.google_cloud.as_mut() {
+            google_cloud.access_token.clear();
+            google_cloud.refresh_token.clear();
+            google_cloud.id_token = None;
+        }
+
+        config
+    }
+
+    /// The non-empty tokens, keyed by their credential store key.
+    fn credentials(&self) -> Vec<(&'static str, &str)> {
+        let mut credentials = Vec::new();
+
+        if let Some(okta) = &self.auth.okta {
+            credentials.push((credential_store::OKTA_ID_TOKEN, okta.id_token.as_str()));
+            credentials.push((
+                credential_store::OKTA_ACCESS_TOKEN,
+                okta.access_token.as_str(),
+            ));
+            credentials.push((
+                credential_store::OKTA_REFRESH_TOKEN,
+                okta.refresh_token.as_str(),
+            ));
+        }
+        if let Some(vault) = &self.auth.vault {
+            credentials.push((credential_store::VAULT_API_TOKEN, vault.api_token.as_str()));
+        }
+        if let Some(google_cloud) = &self.auth.google_cloud {
+            credentials.push((
+                credential_store::GOOGLE_CLOUD_ACCESS_TOKEN,
+                google_cloud.access_token.as_str(),
+            ));
+            credentials.push((
+                credential_store::GOOGLE_CLOUD_REFRESH_TOKEN,
+                google_cloud.refresh_token.as_str(),
+            ));
+            if let Some(id_token) = &google_cloud.id_token {
+                credentials.push((credential_store::GOOGLE_CLOUD_ID_TOKEN, id_token.as_str()));
+            }
+        }
+
+        credentials.retain(|(_, value)| !value.is_empty());
+        credentials
+    }
+
+    /// Fill in the tokens of the configured providers from `store`.
+    fn load_credentials(&mut self, store: &dyn CredentialStore) -> Result<(), ConfigError> {
+        let load = |value: &mut String, key: &str| -> Result<(), ConfigError> {
+            if value.is_empty() {
+                *value = store.get(key)?.unwrap_or_default();
+            }
+            Ok(())
+        };
+
+        if let Some(okta) = self.auth.okta.as_mut() {
+            load(&mut okta.id_token, credential_store::OKTA_ID_TOKEN)?;
+            load(&mut okta.access_token, credential_store::OKTA_ACCESS_TOKEN)?;
+            load(
+                &mut okta.refresh_token,
+                credential_store::OKTA_REFRESH_TOKEN,
+            )?;
+        }
+        if let Some(vault) = self.auth.vault.as_mut() {
+            load(&mut vault.api_token, credential_store::VAULT_API_TOKEN)?;
+        }
+        if let Some(google_cloud) = self.auth.google_cloud.as_mut() {
+            load(
+                &mut google_cloud.access_token,
+                credential_store::GOOGLE_CLOUD_ACCESS_TOKEN,
+            )?;
+            load(
+                &mut google_cloud.refresh_token,
+                credential_store::GOOGLE_CLOUD_REFRESH_TOKEN,
+            )?;
+            if google_cloud.id_token.is_none() {
+                google_cloud.id_token = store.get(credential_store::GOOGLE_CLOUD_ID_TOKEN)?;
+            }
+        }
 
         Ok(())
     }
@@ -198,6 +476,32 @@ impl Config {
         }
     }
 
+    /// Remove the given tokens from the credential store, e.g. on logout.
+    ///
+    /// Saving the config never removes tokens from the store, so this has to be
+    /// called for the providers that were removed from the config.
+    pub fn delete_credentials(&self, keys: &[&str]) -> Result<(), ConfigError> {
+        let path = match &self.config_path {
+            Some(path) => path.as_str(),
+            None => CONFIG_FILE
+                .as_ref()
+                .expect("Unable to identify user's home directory"),
+        };
+
+        Self::delete_credentials_with_store(keys, credential_store::for_config_path(path).as_ref())
+    }
+
+    fn delete_credentials_with_store(
+        keys: &[&str],
+        store: &dyn CredentialStore,
+    ) -> Result<(), ConfigError> {
+        for key in keys {
+            store.delete(key)?;
+        }
+
+        Ok(())
+    }
+
     pub fn remove_config_from_path(&self) -> Result<(), ConfigError> {
         let config_path = self
             .config_path
@@ -220,6 +524,7 @@ mod test {
     use serial_test::serial;
 
     use super::*;
+    use crate::auth::credential_store::MemoryStore;
 
     #[test]
     #[serial]
@@ -239,6 +544,111 @@ mod test {
         std::fs::remove_file(path).unwrap();
     }
 
+    #[test]
+    #[serial]
+    fn save_config_file_without_tokens() {
+        let path = "./config_without_tokens.toml";
+        let store = MemoryStore::default();
+        let mut config = Config::default();
+        config.auth.vault = Some(VaultConfig {
+            api_token: "api_token".to_string(),
+            expiry_time: "2023-02-19T06:55:51.501915+00:00".to_string(),
+        });
+
+        config.save_to_path_with_store(path, &store).unwrap();
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(!content.contains("api_token"));
+        assert_eq!(
+            store.get(credential_store::VAULT_API_TOKEN).unwrap(),
+            Some("api_token".to_string())
+        );
+
+        let saved_config = Config::load_from_path_with_store(path, &store).unwrap();
+        assert_eq!(saved_config.auth.vault, config.auth.vault);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn migrate_plaintext_tokens_on_load() {
+        let path = "./config_with_plaintext_tokens.toml";
+        std::fs::write(
+            path,
+            r#"[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth.okta]
+client_id = "valid-okta-client-id"
+account = "test@email.com"
+subject = "subject"
+id_token = "id_token"
+access_token = "access_token"
+expiry_time = "2023-02-19T06:55:51.501915+00:00"
+refresh_token = "refresh_token"
+"#,
+        )
+        .unwrap();
+        let store = MemoryStore::default();
+
+        let config = Config::load_from_path_with_store(path, &store).unwrap();
+
+        let okta = config.auth.okta.unwrap();
+        assert_eq!(okta.access_token, "access_token");
+        assert_eq!(okta.refresh_token, "refresh_token");
+        assert_eq!(
+            store.get(credential_store::OKTA_ID_TOKEN).unwrap(),
+            Some("id_token".to_string())
+        );
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(
+            content,
+            r#"[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth.okta]
+client_id = "valid-okta-client-id"
+account = "test@email.com"
+subject = "subject"
+expiry_time = "2023-02-19T06:55:51.501915+00:00"
+"#
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn delete_credentials_from_store() {
+        let path = "./config_delete_credentials.toml";
+        let store = MemoryStore::default();
+        let mut config = Config::default();
+        config.auth.vault = Some(VaultConfig {
+            api_token: "api_token".to_string(),
+            expiry_time: "2023-02-19T06:55:51.501915+00:00".to_string(),
+        });
+        config.save_to_path_with_store(path, &store).unwrap();
+
+        config.auth.vault = None;
+        config.save_to_path_with_store(path, &store).unwrap();
+        Config::delete_credentials_with_store(credential_store::VAULT_KEYS, &store).unwrap();
+
+        assert_eq!(store.get(credential_store::VAULT_API_TOKEN).unwrap(), None);
+        let saved_config = Config::load_from_path_with_store(path, &store).unwrap();
+        assert_eq!(saved_config.auth.vault, None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
     #[test]
     #[serial]
     fn load_non_exist_file() {
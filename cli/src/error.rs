@@ -1,8 +1,10 @@
 use owo_colors::OwoColorize;
+use strum::IntoStaticStr;
 use thiserror::Error as ThisError;
-use wukong_sdk::error::{APIError, ExtractError, WKError};
+use wukong_sdk::error::{APIError, ExtractError, VaultError, WKError};
 
-#[derive(Debug, ThisError)]
+// the variant name is recorded as the error of the command in the telemetry
+#[derive(Debug, ThisError, IntoStaticStr)]
 pub enum WKCliError {
     #[error(transparent)]
     WKSdkError(#[from] WKError),
@@ -48,6 +50,12 @@ pub enum WKCliError {
     InquireError(#[from] inquire::error::InquireError),
     #[error(transparent)]
     TestError(#[from] TestError),
+    #[error(transparent)]
+    CredentialStoreError(#[from] CredentialStoreError),
+    #[error(transparent)]
+    TelemetryError(#[from] wukong_telemetry::TelemetryError),
+    #[error(transparent)]
+    UpdateError(#[from] UpdateError),
 }
 
 #[derive(Debug, ThisError)]
@@ -73,6 +81,30 @@ pub enum ApplicationInstanceError {
     VersionNotAvailable { version: String },
     #[error("This application is not available in k8s.")]
     ApplicationNotFound,
+    #[error("The instance `{name}` is not running, or not ready to connect to.")]
+    InstanceNotFound { name: String },
+}
+
+#[derive(Debug, ThisError)]
+pub enum UpdateError {
+    #[error("The release \"{version}\" is not found.")]
+    ReleaseNotFound { version: String },
+    #[error("There is no release of wukong for this platform.")]
+    UnsupportedPlatform,
+    #[error("The release \"{version}\" has no asset ending with \"{suffix}\".")]
+    AssetNotFound { version: String, suffix: String },
+    #[error("There is no published checksum for \"{asset}\".")]
+    ChecksumNotFound { asset: String },
+    #[error("The checksum of \"{asset}\" doesn't match, expected {expected} but got {actual}.")]
+    ChecksumMismatch {
+        asset: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("The wukong binary is not found in \"{asset}\".")]
+    BinaryNotFound { asset: String },
+    #[error("The new wukong binary doesn't run, so the previous one is restored.")]
+    BinaryNotRunnable,
 }
 
 #[derive(Debug, ThisError)]
@@ -91,6 +123,15 @@ pub enum AuthError {
     VaultAuthenticationFailed,
     #[error("Vault API Response Error: {message}")]
     VaultResponseError { code: String, message: String },
+    #[error("Failed to get an access token with the client credentials: {message}")]
+    ClientCredentialsError { message: String },
+    #[error("Failed to get an access token with the Google Cloud service account key \"{path}\": {message}")]
+    GoogleServiceAccountError { path: String, message: String },
+    #[error("Failed to revoke the {provider} token: {message}")]
+    RevokeTokenError {
+        provider: &'static str,
+        message: String,
+    },
 }
 
 #[derive(Debug, ThisError)]
@@ -105,6 +146,29 @@ pub enum DevConfigError {
         config_path: String,
         annotation: String,
     },
+    #[error("No config annotation found for the secret \"{key}\"")]
+    SecretKeyNotFound { key: String },
+    #[error("There are several secrets named \"{key}\": {}", .secrets.join(", "))]
+    AmbiguousSecretKey { key: String, secrets: Vec<String> },
+    #[error("The secret \"{key}\" is not in the version {version}")]
+    SecretNotInVersion { key: String, version: u64 },
+    #[error("{conflicts} merge conflict(s) in {config_path}, it's changed on the remote since the last pull")]
+    MergeConflict {
+        config_path: String,
+        conflicts: usize,
+    },
+    #[error("The template \"{path}\" is not found")]
+    TemplateNotFound { path: String },
+    #[error("The secret {reference} in the template is not found")]
+    TemplateSecretNotFound { reference: String },
+    #[error("\"{reference}\" is not a secret")]
+    InvalidSecretReference { reference: String },
+    #[error("The path \"{path}\" can't be given with --all, every application is under the root of the git repository")]
+    PathWithAll { path: String },
+    #[error("\"{path}\" is not a Wukong secret path")]
+    InvalidWukongPath { path: String },
+    #[error("{drifted} config file(s) are not up to date with the remote")]
+    ConfigDrift { drifted: usize },
 }
 
 #[derive(Debug, ThisError)]
@@ -125,6 +189,28 @@ pub enum ConfigError {
     BadTomlData(#[source] toml::de::Error),
     #[error("Failed to serialize configuration data into TOML.")]
     SerializeTomlError(#[source] toml::ser::Error),
+    #[error("\"{key}\" is not a valid config key.")]
+    InvalidKey { key: String },
+    #[error("\"{key}\" is not set.")]
+    KeyNotFound { key: String },
+    #[error("Invalid value for \"{key}\": {reason}.")]
+    InvalidValue { key: String, reason: String },
+    #[error("The updated config is not valid.")]
+    InvalidConfig(#[source] toml::de::Error),
+    #[error(transparent)]
+    CredentialStore(#[from] CredentialStoreError),
+    #[error(transparent)]
+    Io(#[from] ::std::io::Error),
+}
+
+#[derive(Debug, ThisError)]
+pub enum CredentialStoreError {
+    #[error("Unable to access the OS keyring.")]
+    Keyring(#[source] keyring::Error),
+    #[error("Unable to decrypt the credentials file at \"{path}\".")]
+    Decryption { path: String },
+    #[error("Failed to encrypt the credentials.")]
+    Encryption,
     #[error(transparent)]
     Io(#[from] ::std::io::Error),
 }
@@ -173,6 +259,15 @@ pub enum DeploymentError {
 }
 
 impl WKCliError {
+    /// The code the process exits with, `2` when `dev config status` finds the config files
+    /// not up to date, to tell it apart from the other errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            WKCliError::DevConfigError(DevConfigError::ConfigDrift { .. }) => 2,
+            _ => 1,
+        }
+    }
+
     /// Try to second-guess what the user was trying to do, depending on what
     /// went wrong.
     pub fn suggestion(&self) -> Option<String> {
@@ -207,9 +302,15 @@ If none of the above steps work for you, please contact the following people on
                     APIError::UnAuthorized => Some(format!(
                         "Your token might be invalid/expired. Run {} to authenticate with your okta account.", "wukong login".yellow())
                     ),
+                    APIError::ServerUnavailable { .. } => Some(
+                        String::from("The request was retried a few times already. Please try again in a few minutes."),
+                    ),
                     _ => None,
                 }
             },
+            WKCliError::WKSdkError(WKError::VaultError(VaultError::CheckAndSetMismatch)) => Some(String::from(
+                "Someone else has changed the secret in the meantime. Run the command again to apply it on top of their change.",
+            )),
             WKCliError::UnAuthenticated => Some(
                 format!("Your access token is invalid. Run {} to authenticate with your okta account.", "wukong login".yellow()),
             ),
@@ -226,6 +327,13 @@ If none of the above steps work for you, please contact the following people on
                 ConfigError::BadTomlData(_) => Some(
                     format!("Check if your `config.toml` file is in valid TOML format.\nThis usually happens when the config file has accidentally been modified or there is a breaking change to the cli config in the new version.\nYou may want to run {} to re-initialise configuration again.", "wukong init".yellow())
                 ),
+                ConfigError::InvalidKey { .. } | ConfigError::KeyNotFound { .. } => Some(format!(
+                    "Config keys are dotted paths, e.g. `core.wukong_api_url`.\nRun {} to see the current configuration.", "wukong config list".yellow()
+                )),
+                ConfigError::InvalidConfig(_) => Some(
+                    String::from("Check that every required field is present and has the correct type.")
+                ),
+                ConfigError::CredentialStore(error) => credential_store_suggestion(error),
                 _ => None,
             },
             WKCliError::ApplicationConfigError(error) => match error {
@@ -248,10 +356,72 @@ If so, run {} to initialise the application configuration."#, "wukong applicatio
                 DevConfigError::InvalidSecretPath { config_path, annotation } => Some(format!(
                     "Please check the {annotation} in the config file: {config_path}"
                 )),
+                DevConfigError::SecretKeyNotFound { .. } => Some(String::from(
+                    "The secret is the key after `#` in the annotation, e.g. `dotenv` in `vault:secret/mv/tech/app/dev#dotenv`.",
+                )),
+                DevConfigError::AmbiguousSecretKey { secrets, .. } => Some(format!(
+                    "Give the path of the secret, e.g. {}.",
+                    secrets[0].yellow()
+                )),
+                DevConfigError::MergeConflict { .. } => Some(format!(
+                    "Run the command again without {} to resolve the conflicts interactively.",
+                    "--no-input".yellow()
+                )),
+                DevConfigError::ConfigDrift { .. } => Some(String::from(
+                    "Run `wukong dev config diff` to see the changes, or `wukong dev config pull` to update them.",
+                )),
+                DevConfigError::InvalidSecretReference { .. } => Some(String::from(
+                    "The secret is the provider, the path and the key, e.g. `bunker:mv/tech/app/dev#DB_PASS` or `vault:secret/mv/tech/app/dev#DB_PASS`.",
+                )),
+                DevConfigError::InvalidWukongPath { .. } => Some(String::from(
+                    "The path is the application, the namespace and the path, e.g. `my-app/prod/db`.",
+                )),
                 _ => None,
             },
+            WKCliError::CredentialStoreError(error) => credential_store_suggestion(error),
             WKCliError::AuthError(AuthError::OktaRefreshTokenExpired { .. }) => Some(format!("Your refresh token is expired. Run {} to authenticate again.", "wukong login".yellow())),
+            WKCliError::AuthError(AuthError::ClientCredentialsError { .. }) => Some(String::from(
+                "Check the `WUKONG_CLIENT_ID` and `WUKONG_CLIENT_SECRET` environment variables, or unset them to use your okta account instead.",
+            )),
+            WKCliError::AuthError(AuthError::GoogleServiceAccountError { .. }) => Some(String::from(
+                "Check the `WUKONG_GOOGLE_CREDENTIALS` environment variable, or unset it to use your Google account instead.",
+            )),
+            WKCliError::UpdateError(error) => match error {
+                UpdateError::ReleaseNotFound { .. } => Some(String::from(
+                    "Check the version at https://github.com/mindvalley/wukong-cli/releases.",
+                )),
+                UpdateError::UnsupportedPlatform
+                | UpdateError::AssetNotFound { .. }
+                | UpdateError::ChecksumNotFound { .. } => Some(String::from(
+                    "Download the release manually from https://github.com/mindvalley/wukong-cli/releases.",
+                )),
+                UpdateError::ChecksumMismatch { .. } => Some(String::from(
+                    "The download might be corrupted. Please try again.",
+                )),
+                _ => None,
+            },
+            WKCliError::TelemetryError(wukong_telemetry::TelemetryError::FlushInProgress) => {
+                Some(String::from("Please try again once it's done."))
+            }
+            WKCliError::TelemetryError(wukong_telemetry::TelemetryError::ExporterNotConfigured) => {
+                Some(String::from(
+                    "Configure the exporter in the `telemetry` section of the config, e.g. an OTLP collector, to send them.",
+                ))
+            }
             _ => None,
         }
     }
 }
+
+fn credential_store_suggestion(error: &CredentialStoreError) -> Option<String> {
+    match error {
+        CredentialStoreError::Keyring(_) => Some(String::from(
+            "Set `WUKONG_CREDENTIAL_STORE=file` to store the credentials in an encrypted file instead.",
+        )),
+        CredentialStoreError::Decryption { path } => Some(format!(
+            "The credentials file was encrypted with a different key or passphrase. Remove \"{path}\" and run {} to authenticate again.",
+            "wukong login".yellow()
+        )),
+        _ => None,
+    }
+}
@@ -14,6 +14,12 @@ mod wukong_client;
 use app::App;
 use error::WKCliError;
 
+/// Answer the completion request when the CLI is run by a dynamic completion script,
+/// see `wukong completion <SHELL> --dynamic`.
+pub fn complete() {
+    commands::complete_dynamically();
+}
+
 pub async fn run() -> Result<bool, WKCliError> {
     let app = App::new()?;
 
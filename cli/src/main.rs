@@ -3,12 +3,20 @@
 use human_panic::setup_panic;
 use log::{error, info};
 use std::process;
-use wukong::{output::error::ErrorOutput, run};
+use wukong::{complete, output::error::ErrorOutput, run};
 
-#[tokio::main]
-async fn main() {
+fn main() {
     setup_panic!();
 
+    // it exits here when answering a completion request of the shell, and it has to be
+    // done outside of the async runtime, as the completions start their own runtime
+    complete();
+
+    start();
+}
+
+#[tokio::main]
+async fn start() {
     // TODO: make sure that the cursor re-appears when interrupting
     // tokio::spawn(async move {
     //     tokio::signal::ctrl_c().await.unwrap();
@@ -19,8 +27,9 @@ async fn main() {
 
     match run().await {
         Err(error) => {
+            let exit_code = error.exit_code();
             error!("{}", ErrorOutput(error));
-            process::exit(1);
+            process::exit(exit_code);
         }
         Ok(false) => {
             info!("wukong cli session ended.");
@@ -1,82 +1,230 @@
 use crate::{
-    config::{Config, UpdateCheck},
+    config::{Config, UpdateChannel, UpdateCheck},
+    error::{UpdateError, WKCliError},
     utils::compare_with_current_time,
 };
-use aion::*;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use clap::crate_version;
+use flate2::read::GzDecoder;
 use log::debug;
+use once_cell::sync::Lazy;
 use owo_colors::OwoColorize;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 const WUKONG_GITHUB_REPO: &str = "mindvalley/wukong-cli";
-const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// The base URL of the Github API, which can be pointed to a local server with
+/// `WUKONG_DEV_GITHUB_API_URL` in the development builds.
+pub static GITHUB_API_URL: Lazy<String> = Lazy::new(|| {
+    #[cfg(feature = "prod")]
+    return "https://api.github.com".to_string();
+
+    #[cfg(not(feature = "prod"))]
+    {
+        match std::env::var("WUKONG_DEV_GITHUB_API_URL") {
+            Ok(github_api_url) => github_api_url,
+            Err(_) => "https://api.github.com".to_string(),
+        }
+    }
+});
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct GithubLatestReleaseInfo {
+pub struct GithubReleaseInfo {
     pub tag_name: String,
     pub url: String,
     pub published_at: String,
     pub html_url: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub assets: Vec<GithubReleaseAsset>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GithubReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
 }
 
+const RELEASE_NOTES_LINES: usize = 5;
+
 // check_for_update checks whether this wukong has had a newer release on Github
 pub async fn check_for_update() {
-    let last_updated_at = get_last_update_checked_at();
+    let update_check = match Config::load_from_default_path() {
+        Ok(config) => config.update_check.unwrap_or_default(),
+        Err(e) => {
+            debug!("Error: {:?}", e);
+            return;
+        }
+    };
+
+    if !update_check.enabled {
+        debug!("The update check is disabled");
+        return;
+    }
 
-    if let Some(last_updated_at) = last_updated_at {
-        let last_update_checked_since = compare_with_current_time(&last_updated_at);
+    if !update_check.last_update_checked_at.is_empty() {
+        let last_update_checked_since =
+            compare_with_current_time(&update_check.last_update_checked_at);
+        let interval = Duration::hours(update_check.interval_hours as i64);
 
-        if last_update_checked_since >= -(72.hours()) {
+        if last_update_checked_since >= -interval {
             debug!("No need to check for update");
             return;
         }
     }
 
-    debug!("Checking for update");
+    debug!(
+        "Checking for update on the {:?} channel",
+        update_check.channel
+    );
 
-    if let Some(latest_release_info) = get_latest_release_info(GITHUB_API_URL).await {
-        update_last_update_checked_at();
-        print_update_message(latest_release_info);
+    match fetch_newer_releases(&GITHUB_API_URL, update_check.channel).await {
+        Ok(releases) => {
+            update_last_update_checked_at();
+            print_update_message(&releases);
+        }
+        Err(e) => {
+            debug!("Error: {:?}", e);
+        }
     }
 }
 
-fn print_update_message(latest_release_info: GithubLatestReleaseInfo) {
-    let current_version = crate_version!().to_string();
+fn print_update_message(releases: &[GithubReleaseInfo]) {
+    if releases.is_empty() {
+        debug!("No new release found");
+        return;
+    }
 
-    let has_update = version_greater_than(&latest_release_info.tag_name, &current_version);
+    debug!("New release found");
+    eprint!("{}", update_message(releases));
+}
 
-    if has_update {
-        debug!("New release found");
-        eprintln!(
-            "{} {} {} {}",
-            "A new release of wukong is available:".yellow(),
-            current_version.cyan(),
-            "→".cyan(),
-            latest_release_info.tag_name.cyan(),
-        );
+/// The message telling that there are newer `releases` (the newest first), with the
+/// summary of their release notes, and how to upgrade.
+pub fn update_message(releases: &[GithubReleaseInfo]) -> String {
+    let Some(latest_release) = releases.first() else {
+        return String::new();
+    };
 
-        eprintln!("To upgrade, run: brew update && brew upgrade wukong");
-        eprintln!("{}", latest_release_info.url.yellow());
+    let mut message = format!(
+        "{} {} {} {}\n",
+        "A new release of wukong is available:".yellow(),
+        crate_version!().cyan(),
+        "→".cyan(),
+        latest_release.tag_name.cyan(),
+    );
+    message.push_str(&release_notes(releases));
+
+    if releases.iter().any(is_breaking) {
+        message.push_str(&format!(
+            "{}\n",
+            "Some of the new releases have breaking changes, please read their release notes before upgrading."
+                .red()
+        ));
+    }
+
+    if is_homebrew_install(&std::env::current_exe().unwrap_or_default()) {
+        message.push_str("To upgrade, run: brew update && brew upgrade wukong\n");
     } else {
-        debug!("No new release found");
+        message.push_str("To upgrade, run: wukong update\n");
     }
+    message.push_str(&format!("{}\n", latest_release.html_url.yellow()));
+
+    message
 }
 
-fn get_last_update_checked_at() -> Option<String> {
-    let config = Config::load_from_default_path().map_err(|e| {
-        debug!("Error: {:?}", e);
-    });
+/// The summary of the release notes of `releases`, i.e. the first few changes listed
+/// in each of them, with the breaking releases flagged.
+pub fn release_notes(releases: &[GithubReleaseInfo]) -> String {
+    let mut notes = String::new();
+
+    for release in releases {
+        if is_breaking(release) {
+            notes.push_str(&format!(
+                "\n{} {}\n",
+                release.tag_name.cyan(),
+                "(breaking)".red().bold()
+            ));
+        } else {
+            notes.push_str(&format!("\n{}\n", release.tag_name.cyan()));
+        }
 
-    if let Ok(config) = config {
-        if let Some(config) = config.update_check {
-            return Some(config.last_update_checked_at);
+        for line in release_notes_summary(release.body.as_deref().unwrap_or_default()) {
+            notes.push_str(&format!("  {line}\n"));
         }
     }
+    notes.push('\n');
+
+    notes
+}
+
+// The changes are the list items of the release body, which is in markdown.
+fn release_notes_summary(body: &str) -> Vec<String> {
+    let changes: Vec<&str> = body
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("- ") || line.starts_with("* "))
+        .collect();
+
+    if changes.is_empty() {
+        return body
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| vec![line.to_string()])
+            .unwrap_or_default();
+    }
+
+    let mut summary: Vec<String> = changes
+        .iter()
+        .take(RELEASE_NOTES_LINES)
+        .map(|change| format!("- {}", &change[2..]))
+        .collect();
+    if changes.len() > RELEASE_NOTES_LINES {
+        summary.push(format!(
+            "... and {} more",
+            changes.len() - RELEASE_NOTES_LINES
+        ));
+    }
+
+    summary
+}
+
+/// Whether the release is marked as breaking, i.e. "breaking" in its name, or
+/// "breaking change" or "[breaking]" in its notes, like the 2.1.0 release which
+/// requires the `.wukong.toml` file. A new major version is always breaking.
+pub fn is_breaking(release: &GithubReleaseInfo) -> bool {
+    let name = release.name.as_deref().unwrap_or_default().to_lowercase();
+    let body = release.body.as_deref().unwrap_or_default().to_lowercase();
+
+    let new_major = match (
+        parse_version(&release.tag_name),
+        parse_version(crate_version!()),
+    ) {
+        (Ok(version), Ok(current_version)) => version.major > current_version.major,
+        _ => false,
+    };
 
-    None
+    new_major
+        || name.contains("breaking")
+        || body.contains("breaking change")
+        || body.contains("[breaking]")
 }
 
 fn update_last_update_checked_at() {
@@ -84,9 +232,8 @@ fn update_last_update_checked_at() {
 
     match config {
         Ok(mut config) => {
-            config.update_check = Some(UpdateCheck {
-                last_update_checked_at: Utc::now().to_rfc3339(),
-            });
+            let update_check = config.update_check.get_or_insert_with(UpdateCheck::default);
+            update_check.last_update_checked_at = Utc::now().to_rfc3339();
 
             let _ = config.save_to_default_path().map_err(|e| {
                 debug!("Error: {:?}", e);
@@ -100,7 +247,7 @@ fn update_last_update_checked_at() {
 
 fn version_greater_than(new_version: &str, current_version: &str) -> bool {
     if let (Ok(new_version), Ok(current_version)) =
-        (Version::parse(new_version), Version::parse(current_version))
+        (parse_version(new_version), parse_version(current_version))
     {
         new_version > current_version
     } else {
@@ -108,37 +255,287 @@ fn version_greater_than(new_version: &str, current_version: &str) -> bool {
     }
 }
 
-async fn get_latest_release_info(github_api_url: &str) -> Option<GithubLatestReleaseInfo> {
+// the tags might be prefixed with `v`
+fn parse_version(version: &str) -> Result<Version, semver::Error> {
+    Version::parse(version.trim_start_matches('v'))
+}
+
+/// The update channel in the config, `stable` if there is no config.
+pub fn update_channel() -> UpdateChannel {
+    Config::load_from_default_path()
+        .ok()
+        .and_then(|config| config.update_check)
+        .map(|update_check| update_check.channel)
+        .unwrap_or_default()
+}
+
+/// Fetch the releases on `channel` which are newer than the running wukong, the
+/// newest first.
+pub async fn fetch_newer_releases(
+    github_api_url: &str,
+    channel: UpdateChannel,
+) -> Result<Vec<GithubReleaseInfo>, WKCliError> {
     let client = Client::new();
 
     let url = format!(
-        "{}/repos/{}/releases/latest",
+        "{}/repos/{}/releases?per_page=100",
         github_api_url, WUKONG_GITHUB_REPO
     );
 
+    let releases: Vec<GithubReleaseInfo> = client
+        .get(&url)
+        .header("user-agent", "wukong-cli")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(newer_releases(releases, crate_version!(), channel))
+}
+
+fn newer_releases(
+    releases: Vec<GithubReleaseInfo>,
+    current_version: &str,
+    channel: UpdateChannel,
+) -> Vec<GithubReleaseInfo> {
+    let mut releases: Vec<(Version, GithubReleaseInfo)> = releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .filter(|release| version_greater_than(&release.tag_name, current_version))
+        .filter_map(|release| {
+            parse_version(&release.tag_name)
+                .ok()
+                .map(|version| (version, release))
+        })
+        .filter(|(version, release)| {
+            channel == UpdateChannel::Prerelease || (!release.prerelease && version.pre.is_empty())
+        })
+        .collect();
+
+    releases.sort_by(|(a, _), (b, _)| b.cmp(a));
+    releases.into_iter().map(|(_, release)| release).collect()
+}
+
+/// Fetch the release of `version` from Github.
+pub async fn fetch_release_info(
+    github_api_url: &str,
+    version: &str,
+) -> Result<GithubReleaseInfo, WKCliError> {
+    let client = Client::new();
+
+    let url = format!(
+        "{}/repos/{}/releases/tags/{}",
+        github_api_url, WUKONG_GITHUB_REPO, version
+    );
+
     let response = client
         .get(&url)
         .header("user-agent", "wukong-cli")
         .send()
-        .await
-        .map_err(|e| {
-            debug!("Error: {:?}", e);
+        .await?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(UpdateError::ReleaseNotFound {
+            version: version.to_string(),
+        }
+        .into());
+    }
+
+    Ok(response.error_for_status()?.json().await?)
+}
+
+/// Whether `version` is newer than the running wukong.
+pub fn is_newer_than_current(version: &str) -> bool {
+    version_greater_than(version, crate_version!())
+}
+
+/// Whether the binary at `path` is managed by Homebrew, which should be the one
+/// upgrading it, so that its records stay in sync.
+pub fn is_homebrew_install(path: &Path) -> bool {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    path.components()
+        .any(|component| component.as_os_str() == "Cellar")
+        || path.starts_with("/opt/homebrew")
+        || path.starts_with("/home/linuxbrew/.linuxbrew")
+}
+
+// The platform name in the release tarballs, see `.github/workflows/release.yml`.
+fn release_platform() -> Option<&'static str> {
+    if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        Some("macOS-arm")
+    } else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+        Some("macOS-x86")
+    } else if cfg!(all(
+        target_os = "linux",
+        target_arch = "x86_64",
+        target_env = "musl"
+    )) {
+        Some("linux-x86-musl")
+    } else if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some("linux-x86")
+    } else {
+        None
+    }
+}
+
+/// Download the release tarball for this platform, verify it against the SHA-256
+/// checksum published with it, and replace the binary at `binary_path` with the one
+/// inside. The previous binary is restored if the new one can't be put in place or
+/// doesn't run.
+pub async fn install_release(
+    release: &GithubReleaseInfo,
+    binary_path: &Path,
+) -> Result<(), WKCliError> {
+    let platform = release_platform().ok_or(UpdateError::UnsupportedPlatform)?;
+    let tarball = find_asset(release, &format!("-{platform}.tar.gz"))?;
+    let checksum = find_asset(release, &format!("-{platform}.tar.gz.sha256")).map_err(|_| {
+        UpdateError::ChecksumNotFound {
+            asset: tarball.name.clone(),
+        }
+    })?;
+
+    let client = Client::new();
+    let tarball_content = download(&client, &tarball.browser_download_url).await?;
+    let checksum_content = download(&client, &checksum.browser_download_url).await?;
+
+    verify_checksum(&tarball.name, &tarball_content, &checksum_content)?;
+    let binary = extract_binary(&tarball.name, &tarball_content)?;
+
+    replace_binary(binary_path, &binary)
+}
+
+fn find_asset<'a>(
+    release: &'a GithubReleaseInfo,
+    suffix: &str,
+) -> Result<&'a GithubReleaseAsset, UpdateError> {
+    release
+        .assets
+        .iter()
+        .find(|asset| asset.name.ends_with(suffix))
+        .ok_or_else(|| UpdateError::AssetNotFound {
+            version: release.tag_name.clone(),
+            suffix: suffix.to_string(),
+        })
+}
+
+async fn download(client: &Client, url: &str) -> Result<Vec<u8>, WKCliError> {
+    debug!("Downloading {url}");
+
+    let content = client
+        .get(url)
+        .header("user-agent", "wukong-cli")
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    Ok(content.to_vec())
+}
+
+// The checksum file is the output of `shasum -a 256 <tarball>`.
+fn verify_checksum(asset: &str, content: &[u8], checksum_file: &[u8]) -> Result<(), UpdateError> {
+    let expected = String::from_utf8_lossy(checksum_file)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let actual = hex::encode(Sha256::digest(content));
+
+    if expected != actual {
+        return Err(UpdateError::ChecksumMismatch {
+            asset: asset.to_string(),
+            expected,
+            actual,
         });
+    }
 
-    if let Ok(response) = response {
-        let github_release_info = response
-            .json::<GithubLatestReleaseInfo>()
-            .await
-            .map_err(|e| {
-                debug!("Error: {:?}", e);
-            });
+    Ok(())
+}
 
-        if let Ok(github_release_info) = github_release_info {
-            return Some(github_release_info);
+fn extract_binary(asset: &str, tarball: &[u8]) -> Result<Vec<u8>, WKCliError> {
+    let mut archive = tar::Archive::new(GzDecoder::new(tarball));
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let is_binary = entry.header().entry_type().is_file()
+            && entry
+                .path()?
+                .file_name()
+                .is_some_and(|name| name == "wukong");
+
+        if is_binary {
+            let mut binary = Vec::new();
+            entry.read_to_end(&mut binary)?;
+            return Ok(binary);
         }
     }
 
-    None
+    Err(UpdateError::BinaryNotFound {
+        asset: asset.to_string(),
+    }
+    .into())
+}
+
+// The new binary is written next to the current one, so that both renames stay on
+// the same filesystem and are atomic.
+fn replace_binary(binary_path: &Path, binary: &[u8]) -> Result<(), WKCliError> {
+    let new_path = sibling_path(binary_path, "new");
+    let backup_path = sibling_path(binary_path, "old");
+
+    fs::write(&new_path, binary)?;
+    if let Err(err) = copy_permissions(binary_path, &new_path) {
+        let _ = fs::remove_file(&new_path);
+        return Err(err.into());
+    }
+
+    if let Err(err) = fs::rename(binary_path, &backup_path) {
+        let _ = fs::remove_file(&new_path);
+        return Err(err.into());
+    }
+
+    if let Err(err) = fs::rename(&new_path, binary_path) {
+        debug!("Rolling back the update: {:?}", err);
+        fs::rename(&backup_path, binary_path)?;
+        let _ = fs::remove_file(&new_path);
+        return Err(err.into());
+    }
+
+    let runs = Command::new(binary_path)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !runs {
+        debug!("Rolling back the update: the new binary doesn't run");
+        fs::rename(&backup_path, binary_path)?;
+        return Err(UpdateError::BinaryNotRunnable.into());
+    }
+
+    let _ = fs::remove_file(&backup_path);
+
+    Ok(())
+}
+
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{extension}"));
+
+    path.with_file_name(file_name)
+}
+
+#[cfg(unix)]
+fn copy_permissions(from: &Path, to: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(from)?.permissions().mode();
+    fs::set_permissions(to, fs::Permissions::from_mode(mode | 0o755))
+}
+
+#[cfg(not(unix))]
+fn copy_permissions(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
 }
 
 #[cfg(test)]
@@ -157,7 +554,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_latest_release_info() {
+    async fn test_fetch_release_info() {
         let server = MockServer::start();
 
         let api_resp = r#"{
@@ -169,7 +566,7 @@ mod tests {
             "body": null
         }"#;
 
-        let url = format!("/repos/{}/releases/latest", WUKONG_GITHUB_REPO);
+        let url = format!("/repos/{}/releases/tags/1.2.0", WUKONG_GITHUB_REPO);
 
         let mock_server = server.mock(|when, then| {
             when.method(GET)
@@ -181,7 +578,7 @@ mod tests {
         });
         println!("{:?}", &server.base_url());
 
-        let release_info = get_latest_release_info(&server.base_url()).await;
+        let release_info = fetch_release_info(&server.base_url(), "1.2.0").await;
 
         mock_server.assert();
 
@@ -193,4 +590,283 @@ mod tests {
             "https://github.com/mindvalley/wukong-cli/releases/tag/1.2.0"
         );
     }
+
+    fn release(tag_name: &str, body: &str, prerelease: bool) -> GithubReleaseInfo {
+        GithubReleaseInfo {
+            tag_name: tag_name.to_string(),
+            url: format!("https://api.github.com/releases/{tag_name}"),
+            published_at: "2023-09-06T07:08:46Z".to_string(),
+            html_url: format!("https://github.com/mindvalley/wukong-cli/releases/tag/{tag_name}"),
+            name: Some(tag_name.to_string()),
+            body: Some(body.to_string()),
+            draft: false,
+            prerelease,
+            assets: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_newer_releases() {
+        let server = MockServer::start();
+
+        let api_resp = r#"[
+            { "url": "", "html_url": "", "tag_name": "99.2.0-beta.1", "published_at": "", "prerelease": true },
+            { "url": "", "html_url": "", "tag_name": "99.1.0", "published_at": "", "body": "- Add the update command" },
+            { "url": "", "html_url": "", "tag_name": "1.1.0", "published_at": "" }
+        ]"#;
+
+        let mock_server = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/repos/{}/releases", WUKONG_GITHUB_REPO))
+                .header("user-agent", "wukong-cli");
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(api_resp);
+        });
+
+        let releases = fetch_newer_releases(&server.base_url(), UpdateChannel::Stable)
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].tag_name, "99.1.0");
+        assert_eq!(
+            releases[0].body.as_deref(),
+            Some("- Add the update command")
+        );
+    }
+
+    #[test]
+    fn test_newer_releases_on_each_channel() {
+        let releases = || {
+            vec![
+                release("2.1.0", "", false),
+                release("2.3.0-beta.1", "", true),
+                release("2.2.0", "", false),
+                release("2.0.0", "", false),
+            ]
+        };
+
+        let tags = |releases: Vec<GithubReleaseInfo>| {
+            releases
+                .into_iter()
+                .map(|release| release.tag_name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            tags(newer_releases(releases(), "2.0.0", UpdateChannel::Stable)),
+            vec!["2.2.0", "2.1.0"]
+        );
+        assert_eq!(
+            tags(newer_releases(
+                releases(),
+                "2.0.0",
+                UpdateChannel::Prerelease
+            )),
+            vec!["2.3.0-beta.1", "2.2.0", "2.1.0"]
+        );
+    }
+
+    #[test]
+    fn test_release_notes_summary() {
+        let body = "## What's Changed\n\n- one\n- two\n* three\n- four\n- five\n- six\n- seven\n\n**Full Changelog**: https://github.com";
+
+        assert_eq!(
+            release_notes_summary(body),
+            vec![
+                "- one",
+                "- two",
+                "- three",
+                "- four",
+                "- five",
+                "... and 2 more"
+            ]
+        );
+        assert_eq!(
+            release_notes_summary("## Hotfix\n\nFix the login on Linux."),
+            vec!["Fix the login on Linux."]
+        );
+        assert!(release_notes_summary("").is_empty());
+    }
+
+    #[test]
+    fn test_is_breaking() {
+        assert!(is_breaking(&release(
+            "2.1.0",
+            "## BREAKING CHANGES\n\n- The CLI requires the `.wukong.toml` file",
+            false
+        )));
+        assert!(is_breaking(&release(
+            "2.2.0",
+            "- [breaking] Drop the pipeline command",
+            false
+        )));
+        assert!(is_breaking(&release("99.0.0", "- Fix the login", false)));
+        assert!(!is_breaking(&release("2.2.0", "- Fix the login", false)));
+    }
+
+    fn tarball_with_binary(binary: &[u8]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(binary.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "wukong-v9.9.9-platform/wukong", binary)
+            .unwrap();
+
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn release_with_assets(server: &MockServer, platform: &str) -> GithubReleaseInfo {
+        let tarball = format!("wukong-v9.9.9-{platform}.tar.gz");
+
+        GithubReleaseInfo {
+            tag_name: "9.9.9".to_string(),
+            url: server.url("/release"),
+            published_at: "2023-09-06T07:08:46Z".to_string(),
+            html_url: server.url("/release"),
+            name: None,
+            body: None,
+            draft: false,
+            prerelease: false,
+            assets: vec![
+                GithubReleaseAsset {
+                    name: tarball.clone(),
+                    browser_download_url: server.url(format!("/download/{tarball}")),
+                },
+                GithubReleaseAsset {
+                    name: format!("{tarball}.sha256"),
+                    browser_download_url: server.url(format!("/download/{tarball}.sha256")),
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_release_info_of_a_missing_version() {
+        let server = MockServer::start();
+
+        let mock_server = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/repos/{}/releases/tags/0.0.1", WUKONG_GITHUB_REPO));
+            then.status(404);
+        });
+
+        let result = fetch_release_info(&server.base_url(), "0.0.1").await;
+
+        mock_server.assert();
+        assert!(matches!(
+            result,
+            Err(WKCliError::UpdateError(UpdateError::ReleaseNotFound { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_is_homebrew_install() {
+        assert!(is_homebrew_install(Path::new(
+            "/usr/local/Cellar/wukong/2.1.5/bin/wukong"
+        )));
+        assert!(is_homebrew_install(Path::new("/opt/homebrew/bin/wukong")));
+        assert!(!is_homebrew_install(Path::new("/usr/local/bin/wukong")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_release_replaces_the_binary() {
+        let Some(platform) = release_platform() else {
+            return;
+        };
+        let server = MockServer::start();
+        let temp = assert_fs::TempDir::new().unwrap();
+        let binary_path = temp.path().join("wukong");
+        fs::write(&binary_path, "#!/bin/sh\necho 2.1.5\n").unwrap();
+        copy_permissions(&binary_path, &binary_path).unwrap();
+
+        let new_binary = b"#!/bin/sh\necho 9.9.9\n";
+        let tarball = tarball_with_binary(new_binary);
+        let checksum = format!(
+            "{}  wukong-v9.9.9-{platform}.tar.gz\n",
+            hex::encode(Sha256::digest(&tarball))
+        );
+        let release = release_with_assets(&server, platform);
+
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/download/wukong-v9.9.9-{platform}.tar.gz"));
+            then.status(200).body(&tarball);
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/download/wukong-v9.9.9-{platform}.tar.gz.sha256"));
+            then.status(200).body(&checksum);
+        });
+
+        install_release(&release, &binary_path).await.unwrap();
+
+        assert_eq!(fs::read(&binary_path).unwrap(), new_binary);
+        assert!(!sibling_path(&binary_path, "old").exists());
+        assert!(!sibling_path(&binary_path, "new").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_release_keeps_the_binary_when_the_checksum_mismatches() {
+        let Some(platform) = release_platform() else {
+            return;
+        };
+        let server = MockServer::start();
+        let temp = assert_fs::TempDir::new().unwrap();
+        let binary_path = temp.path().join("wukong");
+        let current_binary = b"#!/bin/sh\necho 2.1.5\n";
+        fs::write(&binary_path, current_binary).unwrap();
+
+        let release = release_with_assets(&server, platform);
+
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/download/wukong-v9.9.9-{platform}.tar.gz"));
+            then.status(200)
+                .body(tarball_with_binary(b"#!/bin/sh\necho 9.9.9\n"));
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/download/wukong-v9.9.9-{platform}.tar.gz.sha256"));
+            then.status(200)
+                .body(format!("{}  tampered.tar.gz\n", "0".repeat(64)));
+        });
+
+        let result = install_release(&release, &binary_path).await;
+
+        assert!(matches!(
+            result,
+            Err(WKCliError::UpdateError(
+                UpdateError::ChecksumMismatch { .. }
+            ))
+        ));
+        assert_eq!(fs::read(&binary_path).unwrap(), current_binary);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replace_binary_rolls_back_when_the_new_binary_does_not_run() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let binary_path = temp.path().join("wukong");
+        let current_binary = b"#!/bin/sh\necho 2.1.5\n";
+        fs::write(&binary_path, current_binary).unwrap();
+        copy_permissions(&binary_path, &binary_path).unwrap();
+
+        let result = replace_binary(&binary_path, b"#!/bin/sh\nexit 1\n");
+
+        assert!(matches!(
+            result,
+            Err(WKCliError::UpdateError(UpdateError::BinaryNotRunnable))
+        ));
+        assert_eq!(fs::read(&binary_path).unwrap(), current_binary);
+    }
 }
@@ -0,0 +1,49 @@
+use std::{fs::OpenOptions, io::Write, path::Path};
+
+/// Write `contents` to `path`, truncating the file, with `0600` permissions on unix.
+///
+/// Use it for any file that holds credentials. The permissions are also
+/// enforced on an existing file, in case it was created by an older version.
+pub fn write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(contents)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("secret");
+
+        std::fs::write(&path, "old").unwrap();
+        write_private_file(&path, b"new").unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+
+        temp.close().unwrap();
+    }
+}
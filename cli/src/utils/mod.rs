@@ -1,3 +1,4 @@
+pub mod fs;
 pub mod inquire;
 pub mod line;
 
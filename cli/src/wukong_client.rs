@@ -1,11 +1,16 @@
 use crate::{
-    auth,
+    auth::{self, ci::CiAuth},
     config::{self, ApiChannel, Config},
     error::WKCliError,
 };
+use async_trait::async_trait;
 use log::debug;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
 use wukong_sdk::{
+    error::WKError,
     graphql::{
         application_config_query, application_query, application_with_k8s_cluster_query,
         applications_query, appsignal::AppsignalIncidentState, appsignal_apps_query,
@@ -16,24 +21,46 @@ use wukong_sdk::{
         check_skill_updates, deploy_livebook, deployment::cd_pipeline_status_query,
         destroy_livebook, execute_cd_pipeline, github_workflow_templates_query,
         is_authorized_query, kubernetes_pods_query, livebook_resource_query, publish_skill,
-        skill_by_slug, skills_list, AppsignalTimeFrame,
+        skill_by_slug, skills_list, AppsignalTimeFrame, ResponseCache,
     },
-    services::{
-        gcloud::{DatabaseMetrics, LogEntries, LogEntriesOptions, TokenInfo},
-        vault::client::FetchSecretsData,
+    secret_extractors::{
+        BunkerProvider, SecretProvider, SecretProviders, SecretRevision, VersionedSecrets,
+        WukongProvider,
     },
+    services::gcloud::{DatabaseMetrics, LogEntries, LogEntriesOptions, TokenInfo},
     WKClient as WKSdkClient, WKConfig,
 };
 
 use wukong_telemetry::*;
 use wukong_telemetry_macro::*;
 
+// set by the `--no-cache` flag
+static CACHE_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Always request the Wukong API instead of using the cached responses.
+pub fn disable_cache() {
+    CACHE_DISABLED.store(true, Ordering::Relaxed);
+}
+
+// the cached responses are only shared between the sessions of the same user
+fn response_cache(profile: &str) -> Option<ResponseCache> {
+    if CACHE_DISABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    config::CACHE_DIR
+        .as_ref()
+        .map(|cache_dir| ResponseCache::new(cache_dir, profile))
+}
+
 pub struct WKClient {
     inner: WKSdkClient,
     // for telemetry
     sub: String,
     // for refresh tokens
     config: Config,
+    // the credentials from the environment, used instead of the okta tokens in CI
+    ci_auth: Option<CiAuth>,
 }
 
 impl From<config::ApiChannel> for wukong_sdk::ApiChannel {
@@ -46,24 +73,57 @@ impl From<config::ApiChannel> for wukong_sdk::ApiChannel {
 }
 impl WKClient {
     pub fn for_channel(config: &Config, channel: &ApiChannel) -> Result<Self, WKCliError> {
+        if let Some(ci_auth) = CiAuth::from_env() {
+            debug!("Using the CI credentials from the environment.");
+            let sub = ci_auth.subject();
+
+            // the access token is requested on the first API call
+            let mut inner = WKSdkClient::new(WKConfig {
+                api_url: config.core.wukong_api_url.clone(),
+                access_token: String::new(),
+                channel: channel.clone().into(),
+            });
+            inner.set_cache(response_cache(&sub));
+
+            return Ok(Self {
+                inner,
+                sub,
+                config: config.clone(),
+                ci_auth: Some(ci_auth),
+            });
+        }
+
         let auth_config = config
             .auth
             .okta
             .as_ref()
             .ok_or(WKCliError::UnAuthenticated)?;
 
+        let mut inner = WKSdkClient::new(WKConfig {
+            api_url: config.core.wukong_api_url.clone(),
+            access_token: auth_config.id_token.clone(),
+            channel: channel.clone().into(),
+        });
+        inner.set_cache(response_cache(&auth_config.subject));
+
         Ok(Self {
-            inner: WKSdkClient::new(WKConfig {
-                api_url: config.core.wukong_api_url.clone(),
-                access_token: auth_config.id_token.clone(),
-                channel: channel.clone().into(),
-            }),
+            inner,
             sub: auth_config.subject.clone(),
             config: config.clone(),
+            ci_auth: None,
         })
     }
 
-    async fn check_and_refresh_tokens(&mut self) -> Result<(), WKCliError> {
+    /// Refresh the tokens if they're expired, it's done before each API call of this
+    /// client. It's needed before calling the API outside of it, see
+    /// [WKClient::secret_providers].
+    pub async fn check_and_refresh_tokens(&mut self) -> Result<(), WKCliError> {
+        if let Some(ci_auth) = self.ci_auth.as_mut() {
+            let access_token = ci_auth.access_token().await?;
+            self.inner.set_access_token(access_token);
+            return Ok(());
+        }
+
         if auth::okta::need_tokens_refresh(&self.config)? {
             debug!("Access token expired. Refreshing tokens...");
 
@@ -297,15 +357,6 @@ impl WKClient {
         self.inner.fetch_access_token_info(access_token).await
     }
 
-    #[wukong_telemetry(api_event = "fetch_vault_secrets")]
-    pub async fn get_secrets(
-        &self,
-        api_token: &str,
-        path: &str,
-    ) -> Result<FetchSecretsData, WKCliError> {
-        self.inner.get_secrets(api_token, path).await
-    }
-
     pub async fn get_secret(
         &self,
         api_token: &str,
@@ -318,47 +369,20 @@ impl WKClient {
             .map_err(|err| err.into())
     }
 
-    #[wukong_telemetry(api_event = "update_vault_secrets")]
-    pub async fn update_secret(
-        &self,
-        api_token: &str,
-        path: &str,
-        data: &HashMap<&str, &str>,
-    ) -> Result<bool, WKCliError> {
-        self.inner.update_secret(api_token, path, data).await
-    }
-
-    #[wukong_telemetry(api_event = "fetch_wukong_secrets")]
-    pub async fn get_wukong_secrets(
-        &mut self,
-        application: &str,
-        namespace: &str,
-        path: &str,
-    ) -> Result<FetchSecretsData, WKCliError> {
-        self.check_and_refresh_tokens().await?;
-        let result: Result<FetchSecretsData, WKCliError> = self
-            .inner
-            .get_wukong_secrets(application, namespace, path)
-            .await
-            .map_err(WKCliError::from);
-        result
-    }
+    /// The providers of the dev config secrets, calling the APIs with this client.
+    pub fn secret_providers<'a>(&'a self, vault_token: &'a str) -> SecretProviders<'a> {
+        let mut providers = SecretProviders::new(&self.inner, vault_token);
+        // the API calls of the providers are recorded like the other ones of this client
+        providers.register(BunkerProviderWithTelemetry {
+            inner: BunkerProvider::new(&self.inner, vault_token),
+            sub: self.sub.clone(),
+        });
+        providers.register(WukongProviderWithTelemetry {
+            inner: WukongProvider::new(&self.inner),
+            sub: self.sub.clone(),
+        });
 
-    #[wukong_telemetry(api_event = "update_wukong_secrets")]
-    pub async fn update_wukong_secrets(
-        &mut self,
-        application: &str,
-        namespace: &str,
-        path: &str,
-        data: &HashMap<&str, &str>,
-    ) -> Result<bool, WKCliError> {
-        self.check_and_refresh_tokens().await?;
-        let result: Result<bool, WKCliError> = self
-            .inner
-            .update_wukong_secrets(application, namespace, path, data)
-            .await
-            .map_err(WKCliError::from);
-        result
+        providers
     }
 
     #[wukong_telemetry(api_event = "fetch_appsignal_average_error_rate")]
@@ -516,3 +540,167 @@ impl WKClient {
             .map_err(WKCliError::from)
     }
 }
+
+/// The `bunker` provider, with its calls to the Vault API recorded.
+struct BunkerProviderWithTelemetry<'a> {
+    inner: BunkerProvider<'a>,
+    // for telemetry
+    sub: String,
+}
+
+impl BunkerProviderWithTelemetry<'_> {
+    #[wukong_telemetry(api_event = "fetch_vault_secrets")]
+    async fn fetch_secrets(&self, src: &str) -> Result<VersionedSecrets, WKError> {
+        self.inner.fetch_versioned(src).await
+    }
+
+    #[wukong_telemetry(api_event = "update_vault_secrets")]
+    async fn update_secrets(
+        &self,
+        src: &str,
+        secrets: &HashMap<&str, &str>,
+    ) -> Result<(), WKError> {
+        self.inner.update(src, secrets).await
+    }
+
+    #[wukong_telemetry(api_event = "update_vault_secrets_with_cas")]
+    async fn update_secrets_with_cas(
+        &self,
+        src: &str,
+        secrets: &HashMap<&str, &str>,
+        version: u64,
+    ) -> Result<(), WKError> {
+        self.inner
+            .update_versioned(src, secrets, Some(version))
+            .await
+    }
+
+    #[wukong_telemetry(api_event = "fetch_vault_secrets_version")]
+    async fn fetch_secrets_version(
+        &self,
+        src: &str,
+        version: u64,
+    ) -> Result<VersionedSecrets, WKError> {
+        self.inner.fetch_version(src, version).await
+    }
+
+    #[wukong_telemetry(api_event = "fetch_vault_secret_metadata")]
+    async fn fetch_secret_history(&self, src: &str) -> Result<Vec<SecretRevision>, WKError> {
+        self.inner.history(src).await
+    }
+}
+
+#[async_trait]
+impl SecretProvider for BunkerProviderWithTelemetry<'_> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn fetch(&self, src: &str) -> Result<HashMap<String, String>, WKError> {
+        Ok(self.fetch_secrets(src).await?.data)
+    }
+
+    async fn update(&self, src: &str, secrets: &HashMap<&str, &str>) -> Result<(), WKError> {
+        self.update_secrets(src, secrets).await
+    }
+
+    async fn fetch_versioned(&self, src: &str) -> Result<VersionedSecrets, WKError> {
+        self.fetch_secrets(src).await
+    }
+
+    async fn update_versioned(
+        &self,
+        src: &str,
+        secrets: &HashMap<&str, &str>,
+        version: Option<u64>,
+    ) -> Result<(), WKError> {
+        match version {
+            Some(version) => self.update_secrets_with_cas(src, secrets, version).await,
+            None => self.update_secrets(src, secrets).await,
+        }
+    }
+
+    async fn fetch_version(&self, src: &str, version: u64) -> Result<VersionedSecrets, WKError> {
+        self.fetch_secrets_version(src, version).await
+    }
+
+    async fn history(&self, src: &str) -> Result<Vec<SecretRevision>, WKError> {
+        self.fetch_secret_history(src).await
+    }
+
+    fn creates_missing_secrets(&self) -> bool {
+        self.inner.creates_missing_secrets()
+    }
+
+    fn describe(&self, src: &str, name: &str) -> String {
+        self.inner.describe(src, name)
+    }
+}
+
+/// The `wukong` provider, with its calls to the Wukong API recorded.
+struct WukongProviderWithTelemetry<'a> {
+    inner: WukongProvider<'a>,
+    // for telemetry
+    sub: String,
+}
+
+impl WukongProviderWithTelemetry<'_> {
+    #[wukong_telemetry(api_event = "fetch_wukong_secrets")]
+    async fn fetch_secrets(&self, src: &str) -> Result<VersionedSecrets, WKError> {
+        self.inner.fetch_versioned(src).await
+    }
+
+    #[wukong_telemetry(api_event = "update_wukong_secrets")]
+    async fn update_secrets(
+        &self,
+        src: &str,
+        secrets: &HashMap<&str, &str>,
+        version: Option<u64>,
+    ) -> Result<(), WKError> {
+        self.inner.update_versioned(src, secrets, version).await
+    }
+}
+
+#[async_trait]
+impl SecretProvider for WukongProviderWithTelemetry<'_> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn fetch(&self, src: &str) -> Result<HashMap<String, String>, WKError> {
+        Ok(self.fetch_secrets(src).await?.data)
+    }
+
+    async fn update(&self, src: &str, secrets: &HashMap<&str, &str>) -> Result<(), WKError> {
+        self.update_secrets(src, secrets, None).await
+    }
+
+    async fn fetch_versioned(&self, src: &str) -> Result<VersionedSecrets, WKError> {
+        self.fetch_secrets(src).await
+    }
+
+    async fn update_versioned(
+        &self,
+        src: &str,
+        secrets: &HashMap<&str, &str>,
+        version: Option<u64>,
+    ) -> Result<(), WKError> {
+        self.update_secrets(src, secrets, version).await
+    }
+
+    async fn fetch_version(&self, src: &str, version: u64) -> Result<VersionedSecrets, WKError> {
+        self.inner.fetch_version(src, version).await
+    }
+
+    async fn history(&self, src: &str) -> Result<Vec<SecretRevision>, WKError> {
+        self.inner.history(src).await
+    }
+
+    fn creates_missing_secrets(&self) -> bool {
+        self.inner.creates_missing_secrets()
+    }
+
+    fn describe(&self, src: &str, name: &str) -> String {
+        self.inner.describe(src, name)
+    }
+}
@@ -0,0 +1,150 @@
+mod common;
+use assert_fs::prelude::*;
+use httpmock::{
+    Method::{GET, POST},
+    MockServer,
+};
+
+const VERIFY_TOKEN_URL: &str = "/v1/auth/token/lookup-self";
+const REVOKE_TOKEN_URL: &str = "/v1/auth/token/revoke-self";
+
+fn mock_wukong_config(temp: &assert_fs::TempDir) -> assert_fs::fixture::ChildPath {
+    let config_file = temp.child("config.toml");
+    config_file.touch().unwrap();
+
+    config_file
+        .write_str(
+            r#"
+[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth.vault]
+api_token = "valid_vault_api_token"
+expiry_time = "2027-06-09T08:51:19.032792+00:00"
+"#,
+        )
+        .unwrap();
+
+    config_file
+}
+
+#[test]
+fn test_wukong_auth_status_success() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let server = MockServer::start();
+    let config_file = mock_wukong_config(&temp);
+
+    let lookup_token_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path_contains(VERIFY_TOKEN_URL)
+            .header("X-Vault-Token", "valid_vault_api_token");
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(
+                r#"{
+                  "data": {
+                    "display_name": "okta-test@email.com",
+                    "policies": ["default", "dev"]
+                  }
+                }"#,
+            );
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("auth")
+        .arg("status")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let output = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+
+    lookup_token_mock.assert();
+    assert!(output.contains("Okta"));
+    assert!(output.contains("wukong login"));
+    assert!(output.contains("Vault (Bunker)"));
+    assert!(output.contains("okta-test@email.com"));
+    assert!(output.contains("expires"));
+    assert!(output.contains("default, dev"));
+    assert!(output.contains("Google Cloud"));
+    assert!(output.contains("wukong google login"));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_auth_logout_success() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let server = MockServer::start();
+    let config_file = mock_wukong_config(&temp);
+
+    let revoke_token_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path_contains(REVOKE_TOKEN_URL)
+            .header("X-Vault-Token", "valid_vault_api_token");
+        then.status(204);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("auth")
+        .arg("logout")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let output = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+
+    revoke_token_mock.assert();
+    assert!(output.contains("Not logged in to Okta."));
+    assert!(output.contains("Logged out from Vault (Bunker)."));
+    assert!(output.contains("Not logged in to Google Cloud."));
+
+    let config = std::fs::read_to_string(config_file.path()).unwrap();
+    assert!(!config.contains("[auth.vault]"));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_auth_logout_removes_the_tokens_when_revoking_fails() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let server = MockServer::start();
+    let config_file = mock_wukong_config(&temp);
+
+    let revoke_token_mock = server.mock(|when, then| {
+        when.method(POST).path_contains(REVOKE_TOKEN_URL);
+        then.status(403)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"errors": ["permission denied"]}"#);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("auth")
+        .arg("logout")
+        .arg("--provider")
+        .arg("vault")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let output = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+
+    revoke_token_mock.assert();
+    assert!(output.contains("they are removed from this machine only"));
+    assert!(output.contains("Logged out from Vault (Bunker)."));
+    assert!(!output.contains("Okta"));
+
+    let config = std::fs::read_to_string(config_file.path()).unwrap();
+    assert!(!config.contains("[auth.vault]"));
+
+    temp.close().unwrap();
+}
@@ -0,0 +1,43 @@
+use assert_fs::prelude::*;
+
+mod common;
+
+#[test]
+fn test_wukong_cache_help() {
+    let cmd = common::wukong_raw_command()
+        .arg("cache")
+        .arg("help")
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+}
+
+#[test]
+fn test_wukong_cache_clear_success() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let config_file = temp.child("config.toml");
+    let cache_entry = temp.child("cache/applications/0123456789abcdef.json");
+    cache_entry.write_str("{}").unwrap();
+
+    common::wukong_raw_command()
+        .arg("cache")
+        .arg("clear")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .assert()
+        .success();
+
+    assert!(!temp.child("cache").path().exists());
+
+    // clearing an empty cache also succeeds
+    common::wukong_raw_command()
+        .arg("cache")
+        .arg("clear")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .assert()
+        .success();
+
+    temp.close().unwrap();
+}
@@ -221,13 +221,13 @@ wukong_api_url = "https://new-wukong-api.com"
 client_id = "valid-okta-client-id"
 account = "test@email.com"
 subject = "subject"
-id_token = "id_token"
-access_token = "access_token"
 expiry_time = "2023-02-19T06:55:51.501915+00:00"
-refresh_token = "refresh_token"
 "#,
     );
 
+    // the tokens are moved to the credential store next to the config file
+    assert!(temp.child("credentials.enc").path().exists());
+
     temp.close().unwrap();
 }
 
@@ -270,3 +270,188 @@ refresh_token = "refresh_token"
 
     temp.close().unwrap();
 }
+
+#[test]
+fn test_wukong_config_set_success_with_dotted_key() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let config_file = temp.child("config.toml");
+    config_file.touch().unwrap();
+
+    config_file
+        .write_str(
+            r#"
+[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth.okta]
+client_id = "valid-okta-client-id"
+account = "test@email.com"
+subject = "subject"
+id_token = "id_token"
+access_token = "access_token"
+expiry_time = "2023-02-19T06:55:51.501915+00:00"
+refresh_token = "refresh_token"
+"#,
+        )
+        .unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("config")
+        .arg("set")
+        .arg("auth.okta.account")
+        .arg("new@email.com")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+
+    config_file.assert(
+        r#"[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth.okta]
+client_id = "valid-okta-client-id"
+account = "new@email.com"
+subject = "subject"
+expiry_time = "2023-02-19T06:55:51.501915+00:00"
+"#,
+    );
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_config_set_should_failed_with_invalid_url() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let config_file = temp.child("config.toml");
+    config_file.touch().unwrap();
+
+    let content = r#"[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth]
+"#;
+    config_file.write_str(content).unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("config")
+        .arg("set")
+        .arg("core.wukong_api_url")
+        .arg("not a url")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .assert()
+        .failure();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stderr).unwrap());
+
+    // the config file should be left untouched
+    config_file.assert(content);
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_config_unset_success() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let config_file = temp.child("config.toml");
+    config_file.touch().unwrap();
+
+    config_file
+        .write_str(
+            r#"
+[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth.okta]
+client_id = "valid-okta-client-id"
+account = "test@email.com"
+subject = "subject"
+id_token = "id_token"
+access_token = "access_token"
+expiry_time = "2023-02-19T06:55:51.501915+00:00"
+refresh_token = "refresh_token"
+"#,
+        )
+        .unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("config")
+        .arg("unset")
+        .arg("auth.okta")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+
+    config_file.assert(
+        r#"[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth]
+"#,
+    );
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_config_set_local_should_keep_secrets() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let application_config_file = temp.child(".wukong.toml");
+    application_config_file.touch().unwrap();
+
+    application_config_file
+        .write_str(
+            r#"
+[application]
+name = "valid-application"
+enable = true
+
+[[application.namespaces]]
+type = "prod"
+continuous_delivery = false
+
+[[secrets]]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/wukong-cli/development#dev.secrets.exs"
+dst = "config/dev.secrets.exs"
+"#,
+        )
+        .unwrap();
+
+    common::wukong_raw_command()
+        .arg("config")
+        .arg("set")
+        .arg("application.enable")
+        .arg("false")
+        .arg("--local")
+        .env("WUKONG_DEV_APP_CONFIG_FILE", application_config_file.path())
+        .assert()
+        .success();
+
+    let content = std::fs::read_to_string(application_config_file.path()).unwrap();
+    assert!(content.contains("enable = false"));
+    assert!(content.contains("[[secrets]]"));
+    assert!(content.contains("src = \"vault:secret/wukong-cli/development#dev.secrets.exs\""));
+
+    common::wukong_raw_command()
+        .arg("config")
+        .arg("set")
+        .arg("application.namespaces.0.type")
+        .arg("dev")
+        .arg("--local")
+        .env("WUKONG_DEV_APP_CONFIG_FILE", application_config_file.path())
+        .assert()
+        .failure();
+
+    temp.close().unwrap();
+}
@@ -0,0 +1,69 @@
+use assert_fs::prelude::*;
+use std::io::Read;
+
+mod common;
+
+#[test]
+fn test_wukong_debug_help() {
+    let cmd = common::wukong_raw_command()
+        .arg("debug")
+        .arg("help")
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+}
+
+#[test]
+fn test_wukong_debug_bundle() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let config_file = temp.child("config.toml");
+    config_file
+        .write_str(
+            r#"
+[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth.okta]
+account = "user@mindvalley.com"
+access_token = "okta-access-token"
+    "#,
+        )
+        .unwrap();
+    let debug_log_file = temp.child("debug_log");
+    debug_log_file
+        .write_str("[2024-05-06] [DEBUG] authorization: Bearer okta-access-token\n")
+        .unwrap();
+    let bundle_file = temp.child("bundle.zip");
+
+    let cmd = common::wukong_raw_command()
+        .arg("debug")
+        .arg("bundle")
+        .arg("--output")
+        .arg(bundle_file.path())
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .env("WUKONG_DEV_DEBUG_LOG_FILE", debug_log_file.path())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout).unwrap();
+    assert!(stdout.contains("Created the debug bundle at"));
+
+    let mut archive =
+        zip::ZipArchive::new(std::fs::File::open(bundle_file.path()).unwrap()).unwrap();
+    let mut content = String::new();
+    for index in 0..archive.len() {
+        archive
+            .by_index(index)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+    }
+    assert!(content.contains("wukong_api_url = \"https://wukong-api.com\""));
+    assert!(content.contains("Bearer [REDACTED]"));
+    assert!(!content.contains("okta-access-token"));
+
+    temp.close().unwrap();
+}
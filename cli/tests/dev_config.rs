@@ -2,9 +2,12 @@ mod common;
 use aion::*;
 use assert_fs::{
     fixture::ChildPath,
-    prelude::{FileTouch, FileWriteStr, PathChild},
+    prelude::{FileTouch, FileWriteStr, PathChild, PathCreateDir},
+};
+use httpmock::{
+    Method::{GET, POST},
+    MockServer,
 };
-use httpmock::{Method::GET, MockServer};
 use serial_test::serial;
 use std::env;
 
@@ -44,7 +47,7 @@ fn verify_token_mock(server: &MockServer) -> httpmock::Mock {
     })
 }
 
-fn get_secret_mock<'a>(server: &'a MockServer, custom_data: Option<&'a str>) -> httpmock::Mock<'a> {
+fn get_secret_mock<'a>(server: &'a MockServer, custom_data: Option<&str>) -> httpmock::Mock<'a> {
     let data = custom_data.unwrap_or(r#"
         {
           "b.secret.exs": "use Mix.Config\n\nconfig :application, Application.Repo,\n  adapter: Ecto.Adapters.Postgres,\n  username: System.get_env(\"DB_USER\"),\n  password: System.get_env(\"DB_PASS\"),\n  database: \"application_dev\",\n  hostname: \"localhost\",\n  pool_size: 100,\n  queue_target: 5",
@@ -177,6 +180,42 @@ dst = ".env"
     teardown(wk_temp, elixir_temp)
 }
 
+#[test]
+#[serial]
+fn test_wukong_dev_config_diff_with_reveal() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(&server, None);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+
+    let created_env_file = elixir_temp.child(".env");
+    created_env_file.write_str("test=false").unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("diff")
+        .arg("--reveal")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("|-test=true"));
+    assert!(stdout.contains("|+test=false"));
+
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
 #[test]
 #[serial]
 fn test_wukong_dev_config_diff_for_dev_exs_success() {
@@ -792,3 +831,1457 @@ config :phoenix, :json_library, Jason
 
     wk_temp.close().unwrap();
 }
+
+fn mock_wukong_toml(elixir_temp: &assert_fs::TempDir) {
+    let wukong_toml_config_file = elixir_temp.child(".wukong.toml");
+    wukong_toml_config_file.touch().unwrap();
+
+    wukong_toml_config_file
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/app/dev#dotenv"
+dst = ".env"
+    "#,
+        )
+        .unwrap();
+}
+
+// pull the `base` dotenv, then change it to `remote` on the bunker and to `local` locally
+fn pull_and_change_dotenv<'a>(
+    server: &'a MockServer,
+    wk_config_file: &ChildPath,
+    elixir_temp: &assert_fs::TempDir,
+    base: &str,
+    local: &str,
+    remote: &str,
+) -> httpmock::Mock<'a> {
+    let mut base_secret_mock = get_secret_mock(
+        server,
+        Some(&serde_json::json!({ "dotenv": base }).to_string()),
+    );
+
+    common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("pull")
+        .arg(elixir_temp.path().to_str().unwrap())
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    base_secret_mock.assert();
+    base_secret_mock.delete();
+
+    elixir_temp.child(".env").write_str(local).unwrap();
+
+    get_secret_mock(
+        server,
+        Some(&serde_json::json!({ "dotenv": remote }).to_string()),
+    )
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_push_merges_remote_changes_since_pull() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+
+    let secret_data_mock = pull_and_change_dotenv(
+        &server,
+        &wk_config_file,
+        &elixir_temp,
+        "A=1\nB=2\nC=3\n",
+        "A=local\nB=2\nC=3\n",
+        "A=1\nB=2\nC=remote\n",
+    );
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH")
+            .path_contains(FETCH_SECRETS_URL)
+            .body_contains(r#""dotenv":"A=local\nB=2\nC=remote\n""#)
+            .body_contains(r#""options":{"cas":2}"#);
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body("{}");
+    });
+
+    common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("push")
+        .arg("--no-input")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    verify_token_mock.assert_hits(2);
+    secret_data_mock.assert();
+    update_secret_mock.assert();
+
+    let local = std::fs::read_to_string(elixir_temp.child(".env").path()).unwrap();
+    assert_eq!(local, "A=local\nB=2\nC=remote\n");
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_push_fails_on_merge_conflicts_without_input() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+
+    let secret_data_mock = pull_and_change_dotenv(
+        &server,
+        &wk_config_file,
+        &elixir_temp,
+        "A=1\nB=2\nC=3\n",
+        "A=1\nB=2\nC=local\n",
+        "A=1\nB=2\nC=remote\n",
+    );
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH").path_contains(FETCH_SECRETS_URL);
+        then.status(200);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("push")
+        .arg("--no-input")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stderr).unwrap());
+
+    verify_token_mock.assert_hits(2);
+    secret_data_mock.assert();
+    update_secret_mock.assert_hits(0);
+
+    // nothing is overwritten
+    let local = std::fs::read_to_string(elixir_temp.child(".env").path()).unwrap();
+    assert_eq!(local, "A=1\nB=2\nC=local\n");
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_push_fails_when_the_remote_changes_meanwhile() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+    elixir_temp.child(".env").write_str("A=local\n").unwrap();
+
+    let secret_data_mock = get_secret_mock(&server, Some(r#"{ "dotenv": "A=1\n" }"#));
+    // someone else has pushed the version 3 after the version 2 was read
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH")
+            .path_contains(FETCH_SECRETS_URL)
+            .body_contains(r#""options":{"cas":2}"#);
+        then.status(400)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"errors":["check-and-set parameter did not match the current version"]}"#);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("push")
+        .arg("--no-input")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure();
+
+    let stderr = std::str::from_utf8(&cmd.get_output().stderr).unwrap();
+    assert!(stderr.contains("The secret has been changed since it was read."));
+
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+    update_secret_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+fn secret_version_mock<'a>(
+    server: &'a MockServer,
+    version: u64,
+    dotenv: &str,
+) -> httpmock::Mock<'a> {
+    let secret_api_resp = serde_json::json!({
+        "data": {
+            "data": { "dotenv": dotenv },
+            "metadata": {
+                "created_time": format!("2024-03-0{version}T02:24:06.945319214Z"),
+                "deletion_time": "",
+                "destroyed": false,
+                "version": version
+            }
+        }
+    });
+
+    server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/app/dev"))
+            .query_param("version", version.to_string());
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .json_body(secret_api_resp);
+    })
+}
+
+fn secret_metadata_mock(server: &MockServer) -> httpmock::Mock {
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/secret/metadata/mv/tech/app/dev");
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(
+                r#"{
+                  "data": {
+                    "cas_required": false,
+                    "created_time": "2024-03-01T02:24:06.945319214Z",
+                    "current_version": 2,
+                    "max_versions": 0,
+                    "oldest_version": 1,
+                    "updated_time": "2024-03-02T02:24:06.945319214Z",
+                    "versions": {
+                      "1": {
+                        "created_time": "2024-03-01T02:24:06.945319214Z",
+                        "deletion_time": "",
+                        "destroyed": false
+                      },
+                      "2": {
+                        "created_time": "2024-03-02T02:24:06.945319214Z",
+                        "deletion_time": "",
+                        "destroyed": false
+                      }
+                    }
+                  }
+                }"#,
+            );
+    })
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_history_success() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+
+    let metadata_mock = secret_metadata_mock(&server);
+    let version_1_mock = secret_version_mock(&server, 1, "A=1\n");
+    let version_2_mock = secret_version_mock(&server, 2, "A=2\n");
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("history")
+        .arg("dotenv")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+
+    verify_token_mock.assert();
+    metadata_mock.assert();
+    version_1_mock.assert();
+    version_2_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_show_version_success() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+
+    let version_1_mock = secret_version_mock(&server, 1, "A=1\n");
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("show")
+        .arg("dotenv")
+        .arg("--version")
+        .arg("1")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+    assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), "A=1\n");
+
+    verify_token_mock.assert();
+    version_1_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_restore_writes_on_top_of_the_current_version() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+
+    // defined first, as the mock of the latest version (2) matches any version
+    let version_1_mock = secret_version_mock(&server, 1, "A=1\n");
+    let current_secret_mock = get_secret_mock(&server, Some(r#"{ "dotenv": "A=2\n" }"#));
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH")
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/app/dev"))
+            .json_body(serde_json::json!({
+                "data": { "dotenv": "A=1\n" },
+                "options": { "cas": 2 }
+            }));
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"data": {"version": 3}}"#);
+    });
+
+    common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("restore")
+        .arg("dotenv")
+        .arg("--version")
+        .arg("1")
+        .arg("--no-input")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    verify_token_mock.assert();
+    current_secret_mock.assert();
+    version_1_mock.assert();
+    update_secret_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_show_ambiguous_key() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+
+    // another application with a `dotenv` secret
+    let other_app = elixir_temp.child("other-app");
+    other_app.create_dir_all().unwrap();
+    other_app
+        .child(".wukong.toml")
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/other-app/dev#dotenv"
+dst = ".env"
+    "#,
+        )
+        .unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("show")
+        .arg("dotenv")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure();
+
+    let stderr = std::str::from_utf8(&cmd.get_output().stderr).unwrap();
+    assert!(stderr.contains("There are several secrets named \"dotenv\""));
+    assert!(stderr.contains("bunker:mv/tech/app/dev#dotenv"));
+    assert!(stderr.contains("bunker:mv/tech/other-app/dev#dotenv"));
+    verify_token_mock.assert_hits(0);
+
+    // with its path, the secret is found
+    let version_1_mock = secret_version_mock(&server, 1, "A=1\n");
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("show")
+        .arg("vault:secret/mv/tech/app/dev#dotenv")
+        .arg("--version")
+        .arg("1")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::str::from_utf8(&cmd.get_output().stdout).unwrap(),
+        "A=1\n"
+    );
+    version_1_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+fn mock_dotenv_kind_wukong_toml(elixir_temp: &assert_fs::TempDir) {
+    let wukong_toml_config_file = elixir_temp.child(".wukong.toml");
+    wukong_toml_config_file.touch().unwrap();
+
+    wukong_toml_config_file
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "dotenv"
+src = "vault:secret/mv/tech/app/dev#dotenv"
+dst = ".env"
+    "#,
+        )
+        .unwrap();
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_diff_for_dotenv_kind_success() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(&server, Some(r#"{ "dotenv": "A=1\nB=2\nC=3\n" }"#));
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_dotenv_kind_wukong_toml(&elixir_temp);
+
+    let created_env_file = elixir_temp.child(".env");
+    created_env_file.write_str("A=1\nB=20\nD=4\n").unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("diff")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_diff_for_dotenv_kind_with_reveal() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(&server, Some(r#"{ "dotenv": "A=1\nB=2\nC=3\n" }"#));
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_dotenv_kind_wukong_toml(&elixir_temp);
+
+    let created_env_file = elixir_temp.child(".env");
+    created_env_file.write_str("A=1\nB=20\nD=4\n").unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("diff")
+        .arg("--reveal")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("~ B = 2 → 20"));
+    assert!(stdout.contains("- C = 3"));
+    assert!(stdout.contains("+ D = 4"));
+    assert!(!stdout.contains("A = "));
+
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_pull_for_dotenv_kind_keeps_local_only_keys() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(&server, Some(r#"{ "dotenv": "A=1\nB=2\n" }"#));
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_dotenv_kind_wukong_toml(&elixir_temp);
+
+    let env_file = elixir_temp.child(".env");
+    env_file.write_str("A=0\nLOCAL_ONLY=yes\n").unwrap();
+
+    common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("pull")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let local = std::fs::read_to_string(env_file.path()).unwrap();
+    assert_eq!(local, "A=1\nB=2\nLOCAL_ONLY=yes\n");
+
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_status_up_to_date() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(&server, None);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_wukong_toml(&elixir_temp);
+
+    let env_file = elixir_temp.child(".env");
+    env_file.write_str("test=true").unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("status")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_status_drift_detected() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(&server, None);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+
+    elixir_temp
+        .child(".wukong.toml")
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/app/dev#dotenv"
+dst = ".env"
+
+[secrets.missing]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/app/dev#b.secret.exs"
+dst = "missing.env"
+
+[secrets.removed]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/app/dev#nope"
+dst = "nope.env"
+    "#,
+        )
+        .unwrap();
+
+    let env_file = elixir_temp.child(".env");
+    env_file.write_str("test=false").unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("status")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure()
+        .code(2);
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+    assert!(std::str::from_utf8(&output.stderr)
+        .unwrap()
+        .contains("3 config file(s) are not up to date with the remote"));
+
+    // nothing is written
+    assert_eq!(
+        std::fs::read_to_string(env_file.path()).unwrap(),
+        "test=false"
+    );
+    assert!(!elixir_temp.child("missing.env").path().exists());
+
+    // the secrets of the same path are fetched once
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_status_reports_a_path_not_found_and_keeps_local_only_keys() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let not_found_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path_contains(format!("{FETCH_SECRETS_URL}/mv/tech/gone/dev"));
+        then.status(404)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"errors": []}"#);
+    });
+    let secret_data_mock = get_secret_mock(&server, None);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+
+    elixir_temp
+        .child(".wukong.toml")
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "dotenv"
+src = "vault:secret/mv/tech/app/dev#dotenv"
+dst = ".env"
+
+[secrets.gone]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/gone/dev#dotenv"
+dst = "gone.env"
+    "#,
+        )
+        .unwrap();
+
+    // the local-only keys are kept by a pull, so they aren't a drift
+    elixir_temp
+        .child(".env")
+        .write_str("test=true\nLOCAL_ONLY=yes\n")
+        .unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("status")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure()
+        .code(2);
+
+    let output = cmd.get_output();
+    let stdout = std::str::from_utf8(&output.stdout).unwrap();
+    assert!(stdout.contains("up-to-date"));
+    assert!(stdout.contains("missing-remote"));
+    assert!(!stdout.contains("stale"));
+    assert!(std::str::from_utf8(&output.stderr)
+        .unwrap()
+        .contains("1 config file(s) are not up to date with the remote"));
+
+    verify_token_mock.assert();
+    not_found_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+fn mock_template_wukong_toml(elixir_temp: &assert_fs::TempDir, template: &str, dst: &str) {
+    elixir_temp
+        .child(".wukong.toml")
+        .write_str(&format!(
+            r#"
+[[secrets]]
+
+[secrets.rendered]
+kind = "template"
+src = "templates/rendered.tmpl"
+dst = "{dst}"
+    "#
+        ))
+        .unwrap();
+
+    elixir_temp
+        .child("templates/rendered.tmpl")
+        .write_str(template)
+        .unwrap();
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_pull_renders_template() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(
+        &server,
+        Some(r#"{ "DB_USER": "admin", "DB_PASS": "hunter2" }"#),
+    );
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_template_wukong_toml(
+        &elixir_temp,
+        "import Config\n\nconfig :app, App.Repo,\n  username: \"{{ bunker:mv/tech/app/dev#DB_USER }}\",\n  password: \"{{ bunker:mv/tech/app/dev#DB_PASS }}\"\n",
+        "config/dev.secret.exs",
+    );
+
+    common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("pull")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::fs::read_to_string(elixir_temp.child("config/dev.secret.exs").path()).unwrap(),
+        "import Config\n\nconfig :app, App.Repo,\n  username: \"admin\",\n  password: \"hunter2\"\n"
+    );
+
+    // the secrets of the same path are fetched once
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_diff_against_rendered_template() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(
+        &server,
+        Some(r#"{ "DB_USER": "admin", "DB_PASS": "hunter2" }"#),
+    );
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_template_wukong_toml(
+        &elixir_temp,
+        "DB_USER={{ bunker:mv/tech/app/dev#DB_USER }}\nDB_PASS={{ bunker:mv/tech/app/dev#DB_PASS }}\nPORT=4000\n",
+        ".env",
+    );
+    elixir_temp
+        .child(".env")
+        .write_str("DB_USER=admin\nDB_PASS=old\nPORT=4000\n")
+        .unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("diff")
+        .arg("--reveal")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("~ DB_PASS = hunter2 → old"));
+    assert!(!stdout.contains("DB_USER"));
+    assert!(!stdout.contains("PORT"));
+
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_rotate_lists_the_config_files_written_from_it() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_template_wukong_toml(
+        &elixir_temp,
+        "DB_USER={{ bunker:mv/tech/app/dev#DB_USER }}\nDB_PASS={{ bunker:mv/tech/app/dev#DB_PASS }}\n",
+        ".env",
+    );
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH")
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/app/dev"))
+            .json_body(serde_json::json!({
+                "data": { "DB_PASS": "n3w-p4ss" }
+            }));
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"data": {"version": 3}}"#);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("rotate")
+        .arg("bunker:mv/tech/app/dev#DB_PASS")
+        .arg("--value")
+        .arg("n3w-p4ss")
+        .arg("--no-input")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("The config files written from vault:secret/mv/tech/app/dev#DB_PASS:"));
+    assert!(stdout.contains(".wukong.toml → .env"));
+    assert!(stdout.contains("Rotated vault:secret/mv/tech/app/dev#DB_PASS."));
+    assert!(!stdout.contains("n3w-p4ss"));
+
+    verify_token_mock.assert();
+    update_secret_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_rotate_hides_the_generated_value_unless_revealed() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_template_wukong_toml(
+        &elixir_temp,
+        "DB_PASS={{ bunker:mv/tech/app/dev#DB_PASS }}\n",
+        ".env",
+    );
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH")
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/app/dev"))
+            .body_contains("DB_PASS");
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"data": {"version": 3}}"#);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("rotate")
+        .arg("bunker:mv/tech/app/dev#DB_PASS")
+        .arg("--no-input")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("Rotated vault:secret/mv/tech/app/dev#DB_PASS."));
+    assert!(stdout.contains("The generated value isn't shown without --reveal"));
+    assert!(!stdout.contains("The new value is"));
+    assert!(stdout.contains("Run `wukong dev config pull`"));
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("rotate")
+        .arg("bunker:mv/tech/app/dev#DB_PASS")
+        .arg("--no-input")
+        .arg("--reveal")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    let value = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("The new value is "))
+        .unwrap();
+    assert_eq!(value.len(), 32);
+    assert!(value.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    verify_token_mock.assert_hits(2);
+    update_secret_mock.assert_hits(2);
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_rotate_pulls_the_config_files_written_from_it() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_template_wukong_toml(
+        &elixir_temp,
+        "DB_USER={{ bunker:mv/tech/app/dev#DB_USER }}\nDB_PASS={{ bunker:mv/tech/app/dev#DB_PASS }}\n",
+        ".env",
+    );
+    elixir_temp
+        .child(".env")
+        .write_str("DB_USER=admin\nDB_PASS=old\n")
+        .unwrap();
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH")
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/app/dev"))
+            .json_body(serde_json::json!({
+                "data": { "DB_PASS": "n3w-p4ss" }
+            }));
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"data": {"version": 3}}"#);
+    });
+    let secret_data_mock = get_secret_mock(
+        &server,
+        Some(r#"{ "DB_USER": "admin", "DB_PASS": "n3w-p4ss" }"#),
+    );
+
+    common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("rotate")
+        .arg("bunker:mv/tech/app/dev#DB_PASS")
+        .arg("--value")
+        .arg("n3w-p4ss")
+        .arg("--no-input")
+        .arg("--pull")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::fs::read_to_string(elixir_temp.child(".env").path()).unwrap(),
+        "DB_USER=admin\nDB_PASS=n3w-p4ss\n"
+    );
+
+    verify_token_mock.assert();
+    update_secret_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_rotate_also_wukong() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_template_wukong_toml(
+        &elixir_temp,
+        "DB_PASS={{ bunker:mv/tech/app/dev#DB_PASS }}\nAPI_DB_PASS={{ wukong:my-app/prod/db#DB_PASS }}\nOTHER_DB_PASS={{ wukong:other-app/prod/db#DB_PASS }}\n",
+        ".env",
+    );
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH")
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/app/dev"))
+            .json_body(serde_json::json!({
+                "data": { "DB_PASS": "n3w-p4ss" }
+            }));
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"data": {"version": 3}}"#);
+    });
+    let update_wukong_secret_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/")
+            .body_contains("updateApplicationSecrets")
+            .body_contains(r#""application":"my-app""#)
+            .body_contains(r#""value":"n3w-p4ss""#);
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"data": {"updateApplicationSecrets": true}}"#);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("rotate")
+        .arg("bunker:mv/tech/app/dev#DB_PASS")
+        .arg("--value")
+        .arg("n3w-p4ss")
+        .arg("--also-wukong")
+        .arg("my-app/prod/db")
+        .arg("--no-input")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("Rotated vault:secret/mv/tech/app/dev#DB_PASS."));
+    assert!(stdout.contains("Rotated wukong:my-app/prod/db#DB_PASS."));
+    // the other secret with the same key is listed, but not rotated
+    assert!(stdout.contains("they are only rotated when given with --also-wukong"));
+    assert!(stdout.contains("\tother-app/prod/db"));
+    assert!(!stdout.contains("Rotated wukong:other-app/prod/db#DB_PASS."));
+
+    verify_token_mock.assert();
+    update_secret_mock.assert();
+    update_wukong_secret_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_rotate_reports_each_wukong_copy() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_template_wukong_toml(
+        &elixir_temp,
+        "DB_PASS={{ bunker:mv/tech/app/dev#DB_PASS }}\n",
+        ".env",
+    );
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH")
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/app/dev"));
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"data": {"version": 3}}"#);
+    });
+    let failed_wukong_secret_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/")
+            .body_contains(r#""application":"other-app""#);
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(
+                r#"{
+                  "data": null,
+                  "errors": [{ "message": "Not allowed to update the secrets", "path": ["updateApplicationSecrets"] }]
+                }"#,
+            );
+    });
+    let update_wukong_secret_mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/")
+            .body_contains(r#""application":"my-app""#);
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"data": {"updateApplicationSecrets": true}}"#);
+    });
+
+    // a failing copy doesn't stop the other ones from being rotated
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("rotate")
+        .arg("bunker:mv/tech/app/dev#DB_PASS")
+        .arg("--also-wukong")
+        .arg("other-app/prod/db")
+        .arg("--also-wukong")
+        .arg("my-app/prod/db")
+        .arg("--no-input")
+        .arg("--reveal")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    let stderr = std::str::from_utf8(&cmd.get_output().stderr)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("Rotated vault:secret/mv/tech/app/dev#DB_PASS."));
+    assert!(stdout.contains("Rotated wukong:my-app/prod/db#DB_PASS."));
+    assert!(stdout.contains("The new value is "));
+    assert!(stderr.contains("Failed to rotate wukong:other-app/prod/db#DB_PASS"));
+
+    verify_token_mock.assert();
+    update_secret_mock.assert();
+    failed_wukong_secret_mock.assert();
+    update_wukong_secret_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_rotate_with_invalid_wukong_path() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    let update_secret_mock = server.mock(|when, then| {
+        when.method("PATCH").path_contains(FETCH_SECRETS_URL);
+        then.status(200);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("rotate")
+        .arg("bunker:mv/tech/app/dev#DB_PASS")
+        .arg("--also-wukong")
+        .arg("my-app/prod")
+        .arg("--no-input")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure();
+
+    let stderr = std::str::from_utf8(&cmd.get_output().stderr)
+        .unwrap()
+        .to_string();
+    assert!(stderr.contains("\"my-app/prod\" is not a Wukong secret path"));
+
+    // nothing is rotated
+    update_secret_mock.assert_hits(0);
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_rotate_with_invalid_secret() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("rotate")
+        .arg("DB_PASS")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure();
+
+    let stderr = std::str::from_utf8(&cmd.get_output().stderr)
+        .unwrap()
+        .to_string();
+    assert!(stderr.contains("\"DB_PASS\" is not a secret"));
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_status_all_applications() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let secret_data_mock = get_secret_mock(&server, None);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+
+    elixir_temp.child(".git").create_dir_all().unwrap();
+    elixir_temp
+        .child("apps/api/.wukong.toml")
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/app/dev#dotenv"
+dst = ".env"
+    "#,
+        )
+        .unwrap();
+    elixir_temp
+        .child("apps/api/.env")
+        .write_str("test=true")
+        .unwrap();
+    elixir_temp
+        .child("apps/web/.wukong.toml")
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.missing]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/app/dev#b.secret.exs"
+dst = "missing.env"
+    "#,
+        )
+        .unwrap();
+
+    // from an application, every one in the repo is checked
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("status")
+        .arg("--all")
+        .current_dir(elixir_temp.child("apps/api").path())
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure()
+        .code(2);
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("apps/api"));
+    assert!(stdout.contains("apps/web"));
+    assert!(stdout.contains("Summary"));
+    assert!(stdout.contains("1 drifted"));
+    assert!(std::str::from_utf8(&cmd.get_output().stderr)
+        .unwrap()
+        .contains("1 config file(s) are not up to date with the remote"));
+
+    // the secrets of the same path are fetched once across the applications
+    verify_token_mock.assert();
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+/// Two applications in a git repository, `apps/api` up to date and `apps/web` written from a
+/// secret the token isn't allowed to read.
+fn mock_applications_with_a_failing_one(elixir_temp: &assert_fs::TempDir) {
+    elixir_temp.child(".git").create_dir_all().unwrap();
+    elixir_temp
+        .child("apps/api/.wukong.toml")
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/app/dev#dotenv"
+dst = ".env"
+    "#,
+        )
+        .unwrap();
+    elixir_temp
+        .child("apps/api/.env")
+        .write_str("test=true")
+        .unwrap();
+    elixir_temp
+        .child("apps/web/.wukong.toml")
+        .write_str(
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "generic"
+src = "vault:secret/mv/tech/forbidden/dev#dotenv"
+dst = ".env"
+    "#,
+        )
+        .unwrap();
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_status_all_applications_keeps_going_when_one_fails() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let forbidden_secret_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/forbidden/dev"));
+        then.status(403)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"errors": ["permission denied"]}"#);
+    });
+    let secret_data_mock = get_secret_mock(&server, None);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_applications_with_a_failing_one(&elixir_temp);
+
+    // the check is incomplete, so it fails rather than reporting a drift
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("status")
+        .arg("--all")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure()
+        .code(1);
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("Summary"));
+    assert!(stdout.contains("up-to-date"));
+    assert!(stdout.contains("apps/api"));
+    assert!(stdout.contains("failed"));
+    assert!(stdout.contains("apps/web"));
+
+    verify_token_mock.assert();
+    // fetched again when its application is checked, to report the error
+    forbidden_secret_mock.assert_hits(2);
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_diff_all_applications_keeps_going_when_one_fails() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let verify_token_mock = verify_token_mock(&server);
+    let forbidden_secret_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path(format!("{FETCH_SECRETS_URL}/mv/tech/forbidden/dev"));
+        then.status(403)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(r#"{"errors": ["permission denied"]}"#);
+    });
+    let secret_data_mock = get_secret_mock(&server, None);
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+    mock_applications_with_a_failing_one(&elixir_temp);
+
+    let cmd = common::wukong_raw_command()
+        .arg("dev")
+        .arg("config")
+        .arg("diff")
+        .arg("--all")
+        .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+        .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+        .assert()
+        .failure()
+        .code(1);
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout)
+        .unwrap()
+        .to_string();
+    assert!(stdout.contains("Summary"));
+    assert!(stdout.contains("up-to-date"));
+    assert!(stdout.contains("apps/api"));
+    assert!(stdout.contains("failed"));
+    assert!(stdout.contains("apps/web"));
+
+    verify_token_mock.assert();
+    forbidden_secret_mock.assert_hits(2);
+    secret_data_mock.assert();
+
+    teardown(wk_temp, elixir_temp)
+}
+
+#[test]
+#[serial]
+fn test_wukong_dev_config_all_applications_with_a_path() {
+    let (wk_temp, elixir_temp) = setup();
+    let server = MockServer::start();
+
+    let wk_config_file = mock_wukong_config(&wk_temp, server.base_url());
+
+    for subcommand in ["pull", "status"] {
+        let cmd = common::wukong_raw_command()
+            .arg("dev")
+            .arg("config")
+            .arg(subcommand)
+            .arg("apps/api")
+            .arg("--all")
+            .env("WUKONG_DEV_CONFIG_FILE", wk_config_file.path())
+            .env("WUKONG_DEV_VAULT_API_URL", server.base_url())
+            .assert()
+            .failure()
+            .code(1);
+
+        let stderr = std::str::from_utf8(&cmd.get_output().stderr)
+            .unwrap()
+            .to_string();
+        assert!(stderr.contains("The path \"apps/api\" can't be given with --all"));
+    }
+
+    teardown(wk_temp, elixir_temp)
+}
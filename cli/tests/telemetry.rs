@@ -0,0 +1,178 @@
+use assert_fs::prelude::*;
+use httpmock::prelude::*;
+
+mod common;
+
+const TELEMETRY_EVENTS: &str = r#"[
+  {
+    "timestamp": "2023-09-06T07:08:46.000Z",
+    "actor": "subject",
+    "application": "wukong",
+    "event": "command",
+    "cmd_name": "deployment_list",
+    "cmd_run_mode": "non-interactive",
+    "version": "2.1.5"
+  }
+]"#;
+
+#[test]
+fn test_wukong_telemetry_help() {
+    let cmd = common::wukong_raw_command()
+        .arg("telemetry")
+        .arg("help")
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+}
+
+#[test]
+fn test_wukong_telemetry_show_queued_events() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let telemetry_file = temp.child("telemetry.json");
+    telemetry_file.write_str(TELEMETRY_EVENTS).unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("telemetry")
+        .arg("show")
+        .env("WUKONG_DEV_TELEMETRY_FILE", telemetry_file.path())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout).unwrap();
+    assert!(stdout.contains(r#""cmd_name": "deployment_list""#));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_telemetry_status_when_disabled_by_env() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let telemetry_file = temp.child("telemetry.json");
+    telemetry_file.write_str(TELEMETRY_EVENTS).unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("telemetry")
+        .arg("status")
+        .env("WUKONG_TELEMETRY", "off")
+        .env("WUKONG_DEV_CONFIG_FILE", temp.child("config.toml").path())
+        .env("WUKONG_DEV_TELEMETRY_FILE", telemetry_file.path())
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout).unwrap();
+    assert!(stdout.contains("WUKONG_TELEMETRY=off"));
+    assert!(stdout.contains("Queued events: 1"));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_telemetry_disable_and_enable() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let config_file = temp.child("config.toml");
+    config_file
+        .write_str(
+            r#"
+[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth]
+    "#,
+        )
+        .unwrap();
+    let telemetry_file = temp.child("telemetry.json");
+    telemetry_file.write_str(TELEMETRY_EVENTS).unwrap();
+
+    common::wukong_raw_command()
+        .arg("telemetry")
+        .arg("disable")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .env("WUKONG_DEV_TELEMETRY_FILE", telemetry_file.path())
+        .assert()
+        .success();
+
+    let config = std::fs::read_to_string(config_file.path()).unwrap();
+    assert!(config.contains("[telemetry]\nenabled = false"));
+    let events = std::fs::read_to_string(telemetry_file.path()).unwrap();
+    assert!(events.is_empty());
+
+    let cmd = common::wukong_raw_command()
+        .arg("telemetry")
+        .arg("status")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .env("WUKONG_DEV_TELEMETRY_FILE", telemetry_file.path())
+        .assert()
+        .success();
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout).unwrap();
+    assert!(stdout.contains("wukong telemetry enable"));
+
+    common::wukong_raw_command()
+        .arg("telemetry")
+        .arg("enable")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .env("WUKONG_DEV_TELEMETRY_FILE", telemetry_file.path())
+        .assert()
+        .success();
+
+    let config = std::fs::read_to_string(config_file.path()).unwrap();
+    assert!(config.contains("[telemetry]\nenabled = true"));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_telemetry_flush_to_otlp_collector() {
+    let server = MockServer::start();
+    let collector = server.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/traces")
+            .header("x-api-key", "collector-key")
+            .body_contains("\"name\":\"deployment_list\"");
+        then.status(200).body("{}");
+    });
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let config_file = temp.child("config.toml");
+    config_file
+        .write_str(&format!(
+            r#"
+[core]
+wukong_api_url = "https://wukong-api.com"
+
+[auth]
+
+[telemetry]
+enabled = true
+exporter = "otlp"
+endpoint = "{}"
+
+[telemetry.headers]
+x-api-key = "collector-key"
+    "#,
+            server.base_url()
+        ))
+        .unwrap();
+    let telemetry_file = temp.child("telemetry.json");
+    telemetry_file.write_str(TELEMETRY_EVENTS).unwrap();
+
+    let cmd = common::wukong_raw_command()
+        .arg("telemetry")
+        .arg("flush")
+        .env("WUKONG_DEV_CONFIG_FILE", config_file.path())
+        .env("WUKONG_DEV_TELEMETRY_FILE", telemetry_file.path())
+        .env_remove("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .env_remove("OTEL_EXPORTER_OTLP_HEADERS")
+        .assert()
+        .success();
+
+    collector.assert();
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout).unwrap();
+    assert!(stdout.contains("Sent 1 queued events."));
+    let events = std::fs::read_to_string(telemetry_file.path()).unwrap();
+    assert!(events.is_empty());
+
+    temp.close().unwrap();
+}
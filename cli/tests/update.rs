@@ -0,0 +1,94 @@
+use httpmock::prelude::*;
+
+mod common;
+
+#[test]
+fn test_wukong_update_help() {
+    let cmd = common::wukong_raw_command()
+        .arg("update")
+        .arg("--help")
+        .assert()
+        .success();
+
+    let output = cmd.get_output();
+
+    insta::assert_snapshot!(std::str::from_utf8(&output.stdout).unwrap());
+}
+
+#[test]
+fn test_wukong_update_check_with_a_newer_release() {
+    let server = MockServer::start();
+    // without a config, the stable channel is used
+    let temp = assert_fs::TempDir::new().unwrap();
+    let config_file = temp.path().join("config.toml");
+
+    let api_resp = r#"[
+        {
+            "url": "https://api.github.com/repos/mindvalley/wukong-cli/releases/2",
+            "html_url": "https://github.com/mindvalley/wukong-cli/releases/tag/99.1.0-beta.1",
+            "tag_name": "99.1.0-beta.1",
+            "published_at": "2023-09-07T07:08:46Z",
+            "prerelease": true,
+            "assets": []
+        },
+        {
+            "url": "https://api.github.com/repos/mindvalley/wukong-cli/releases/1",
+            "html_url": "https://github.com/mindvalley/wukong-cli/releases/tag/99.0.0",
+            "body": "- Add the update command",
+            "tag_name": "99.0.0",
+            "published_at": "2023-09-06T07:08:46Z",
+            "assets": []
+        }
+    ]"#;
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/repos/mindvalley/wukong-cli/releases");
+        then.status(200)
+            .header("content-type", "application/json; charset=UTF-8")
+            .body(api_resp);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("update")
+        .arg("--check")
+        .env("WUKONG_DEV_CONFIG_FILE", &config_file)
+        .env("WUKONG_DEV_GITHUB_API_URL", server.base_url())
+        .assert()
+        .success();
+
+    mock.assert();
+
+    let stdout = std::str::from_utf8(&cmd.get_output().stdout).unwrap();
+    assert!(stdout.contains(env!("CARGO_PKG_VERSION")));
+    assert!(stdout.contains("99.0.0"));
+    assert!(stdout.contains("- Add the update command"));
+    // the pre-releases are only shown on the prerelease channel
+    assert!(!stdout.contains("99.1.0-beta.1"));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_wukong_update_with_a_missing_version() {
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/repos/mindvalley/wukong-cli/releases/tags/0.0.1");
+        then.status(404);
+    });
+
+    let cmd = common::wukong_raw_command()
+        .arg("update")
+        .arg("--version")
+        .arg("0.0.1")
+        .env("WUKONG_DEV_GITHUB_API_URL", server.base_url())
+        .assert()
+        .failure();
+
+    mock.assert();
+
+    let stderr = std::str::from_utf8(&cmd.get_output().stderr).unwrap();
+    assert!(stderr.contains("The release \"0.0.1\" is not found."));
+}
@@ -34,6 +34,8 @@ pub enum WKError {
     GCloudError(#[from] GCloudError),
     #[error(transparent)]
     ExtractError(#[from] ExtractError),
+    #[error(transparent)]
+    SecretProviderError(#[from] SecretProviderError),
     #[error("Operation timeout")]
     Timeout,
 }
@@ -60,6 +62,8 @@ pub enum APIError {
     UnAuthorized,
     #[error("API Error: Request to {domain} timed out.")]
     Timeout { domain: String },
+    #[error("API Error: The Wukong API is unavailable (HTTP {status}).")]
+    ServerUnavailable { status: u16 },
     #[error("Failed to get data from GraphQL response.")]
     MissingResponseData,
     #[error("The selected build number is the same as the current deployed version. So there is no changelog.")]
@@ -170,6 +174,8 @@ pub enum VaultError {
     ApiTokenInvalid,
     #[error("Permission denied.")]
     PermissionDenied,
+    #[error("The secret has been changed since it was read.")]
+    CheckAndSetMismatch,
     #[error(transparent)]
     ConfigError(#[from] ConfigError),
 }
@@ -194,4 +200,35 @@ pub enum GCloudError {
 pub enum ExtractError {
     #[error("Bad TOML data.")]
     BadTomlData(#[from] toml::de::Error),
+    #[error("Bad JSON data.")]
+    BadJsonData(#[from] serde_json::Error),
+}
+
+#[derive(Debug, ThisError)]
+pub enum SecretProviderError {
+    #[error("Unknown secret provider \"{provider}\".")]
+    UnknownProvider { provider: String },
+    #[error("The \"{provider}\" provider doesn't keep the versions of the secrets.")]
+    HistoryNotSupported { provider: String },
+    #[error("Invalid secret source \"{src}\", it should be {expected}.")]
+    InvalidSource { src: String, expected: &'static str },
+    #[error("Unsupported encrypted file \"{path}\", it should end with .age, .gpg or .asc.")]
+    UnsupportedEncryption { path: String },
+    #[error("No recipients found in \"{path}\" to encrypt the secrets for.")]
+    RecipientsNotFound { path: String },
+    #[error("Failed to run {command}: {message}")]
+    CommandFailed {
+        command: &'static str,
+        message: String,
+    },
+    #[error(
+        "Bad secrets file \"{path}\", it should be a JSON object of the secret names and values."
+    )]
+    BadSecretsFile {
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error(transparent)]
+    Io(#[from] ::std::io::Error),
 }
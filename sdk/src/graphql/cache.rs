@@ -0,0 +1,248 @@
+use crate::ApiChannel;
+use log::debug;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The read queries whose responses can be cached, each with its own time to live.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachedQuery {
+    Applications,
+    ApplicationConfig,
+    GithubWorkflowTemplates,
+    Skills,
+    CdPipelines,
+}
+
+impl CachedQuery {
+    /// The name of the GraphQL field, also used as the directory of its entries.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Applications => "applications",
+            Self::ApplicationConfig => "applicationConfig",
+            Self::GithubWorkflowTemplates => "githubWorkflowTemplates",
+            Self::Skills => "skills",
+            Self::CdPipelines => "cdPipelines",
+        }
+    }
+
+    /// How long a cached response is used before it is fetched again.
+    pub fn ttl(&self) -> Duration {
+        match self {
+            Self::Applications => Duration::from_secs(60 * 60),
+            Self::ApplicationConfig => Duration::from_secs(15 * 60),
+            Self::GithubWorkflowTemplates => Duration::from_secs(24 * 60 * 60),
+            Self::Skills => Duration::from_secs(10 * 60),
+            // the pipelines show the deployed versions, so they go stale quickly
+            Self::CdPipelines => Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry<T> {
+    key: String,
+    cached_at: u64,
+    data: T,
+}
+
+/// An on-disk cache of the responses of the [CachedQuery] queries.
+///
+/// The entries are keyed by the API channel and URL, the `profile` (the user the
+/// requests are made as) and the query variables, so that switching the account or
+/// the channel never returns someone else's response. Any error reading or writing
+/// the cache is ignored, and the request goes to the Wukong API instead.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    profile: String,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, profile: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            profile: profile.into(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Remove all the cached responses in `dir`, of every profile.
+    pub fn clear(dir: impl AsRef<Path>) -> io::Result<()> {
+        match fs::remove_dir_all(dir) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn key<V: Serialize>(
+        &self,
+        channel: &ApiChannel,
+        api_url: &str,
+        variables: &V,
+    ) -> Option<String> {
+        let variables = serde_json::to_string(variables).ok()?;
+        Some(format!(
+            "{channel:?}|{api_url}|{}|{variables}",
+            self.profile
+        ))
+    }
+
+    pub(crate) fn get<T: DeserializeOwned>(&self, query: CachedQuery, key: &str) -> Option<T> {
+        let content = fs::read_to_string(self.entry_path(query, key)).ok()?;
+        let entry: CacheEntry<T> = match serde_json::from_str(&content) {
+            Ok(entry) => entry,
+            Err(err) => {
+                debug!(
+                    "ignoring the invalid cache entry of {}: {err}",
+                    query.name()
+                );
+                return None;
+            }
+        };
+
+        // the file name is a hash, so make sure it's not a collision
+        if entry.key != key || now().saturating_sub(entry.cached_at) >= query.ttl().as_secs() {
+            return None;
+        }
+
+        debug!("using the cached response of {}", query.name());
+        Some(entry.data)
+    }
+
+    pub(crate) fn set<T: Serialize>(&self, query: CachedQuery, key: &str, data: &T) {
+        let entry = CacheEntry {
+            key: key.to_string(),
+            cached_at: now(),
+            data,
+        };
+
+        if let Err(err) = self.write_entry(query, key, &entry) {
+            debug!("unable to cache the response of {}: {err}", query.name());
+        }
+    }
+
+    /// Drop the cached responses of `query`, for every profile and variables.
+    pub(crate) fn invalidate(&self, query: CachedQuery) {
+        if let Err(err) = Self::clear(self.dir.join(query.name())) {
+            debug!("unable to invalidate the cache of {}: {err}", query.name());
+        }
+    }
+
+    fn write_entry<T: Serialize>(
+        &self,
+        query: CachedQuery,
+        key: &str,
+        entry: &CacheEntry<T>,
+    ) -> io::Result<()> {
+        let path = self.entry_path(query, key);
+        fs::create_dir_all(self.dir.join(query.name()))?;
+
+        // write to a temporary file first, so a concurrent read never sees half an entry
+        let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp_path, serde_json::to_vec(entry)?)?;
+        fs::rename(tmp_path, path)
+    }
+
+    fn entry_path(&self, query: CachedQuery, key: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        self.dir
+            .join(query.name())
+            .join(format!("{:016x}.json", hasher.finish()))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn test_entries_are_keyed_by_profile_channel_and_variables() {
+        let dir = TempDir::new().unwrap();
+        let alice = ResponseCache::new(dir.path(), "alice");
+        let bob = ResponseCache::new(dir.path(), "bob");
+
+        let key = alice
+            .key(&ApiChannel::Stable, "http://localhost", &"app-a")
+            .unwrap();
+        alice.set(CachedQuery::CdPipelines, &key, &vec!["prod"]);
+
+        assert_eq!(
+            alice.get::<Vec<String>>(CachedQuery::CdPipelines, &key),
+            Some(vec!["prod".to_string()])
+        );
+
+        for other_key in [
+            bob.key(&ApiChannel::Stable, "http://localhost", &"app-a"),
+            alice.key(&ApiChannel::Canary, "http://localhost", &"app-a"),
+            alice.key(&ApiChannel::Stable, "http://localhost", &"app-b"),
+        ] {
+            assert_eq!(
+                alice.get::<Vec<String>>(CachedQuery::CdPipelines, &other_key.unwrap()),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_expired_entries_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let cache = ResponseCache::new(dir.path(), "alice");
+        let key = cache
+            .key(&ApiChannel::Stable, "http://localhost", &())
+            .unwrap();
+
+        let expired_entry = CacheEntry {
+            key: key.clone(),
+            cached_at: now() - CachedQuery::Applications.ttl().as_secs(),
+            data: vec!["app-a"],
+        };
+        cache
+            .write_entry(CachedQuery::Applications, &key, &expired_entry)
+            .unwrap();
+
+        assert_eq!(
+            cache.get::<Vec<String>>(CachedQuery::Applications, &key),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invalidate_and_clear() {
+        let dir = TempDir::new().unwrap();
+        let cache = ResponseCache::new(dir.path().join("cache"), "alice");
+        let key = cache
+            .key(&ApiChannel::Stable, "http://localhost", &())
+            .unwrap();
+        cache.set(CachedQuery::Applications, &key, &1);
+        cache.set(CachedQuery::CdPipelines, &key, &2);
+
+        cache.invalidate(CachedQuery::CdPipelines);
+        assert_eq!(cache.get::<i32>(CachedQuery::CdPipelines, &key), None);
+        assert_eq!(cache.get::<i32>(CachedQuery::Applications, &key), Some(1));
+
+        ResponseCache::clear(cache.dir()).unwrap();
+        assert_eq!(cache.get::<i32>(CachedQuery::Applications, &key), None);
+        // clearing an empty cache is fine
+        ResponseCache::clear(cache.dir()).unwrap();
+    }
+}
@@ -1,11 +1,13 @@
 pub mod application;
 pub mod application_secret;
 pub mod appsignal;
+pub mod cache;
 pub mod changelog;
 pub mod deployment;
 pub mod deployment_github;
 pub mod github;
 pub mod kubernetes;
+pub mod retry;
 pub mod skill;
 
 use self::{
@@ -29,6 +31,7 @@ pub use self::{
         AppsignalAverageLatencyQuery, AppsignalAverageThroughputQuery, AppsignalDeployMarkersQuery,
         AppsignalExceptionIncidentsQuery, AppsignalTimeFrame,
     },
+    cache::{CachedQuery, ResponseCache},
     changelog::{changelogs_query, ChangelogsQuery},
     deployment::{
         cd_pipeline_for_rollback_query, cd_pipeline_query, cd_pipelines_query, execute_cd_pipeline,
@@ -41,6 +44,7 @@ pub use self::{
         livebook_resource_query, DeployLivebook, DestroyLivebook, IsAuthorizedQuery,
         KubernetesPodsQuery, LivebookResourceQuery,
     },
+    retry::RetryPolicy,
     skill::{
         check_skill_updates, publish_skill, skill_by_slug, skills_list, CheckSkillUpdates,
         PublishSkill, SkillBySlug, SkillsList,
@@ -51,12 +55,13 @@ use crate::{
     services::vault::client::FetchSecretsData,
     ApiChannel, WKClient,
 };
-use graphql_client::{GraphQLQuery, Response};
+use graphql_client::{GraphQLQuery, QueryBody, Response};
 use log::debug;
-use reqwest::header;
+use reqwest::{header, StatusCode};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::{thread, time};
+use std::time::Instant;
 
 // Check if the error is a timeout error.
 // For Timeout errors, we get the domain and return it as part of the Timeout error.
@@ -81,6 +86,7 @@ fn get_timeout_error(error_code: &str) -> Option<APIError> {
 pub struct GQLClientBuilder<'a> {
     token: &'a str,
     channel: &'a ApiChannel,
+    retry_policy: RetryPolicy,
 }
 
 impl<'a> Default for GQLClientBuilder<'a> {
@@ -88,6 +94,7 @@ impl<'a> Default for GQLClientBuilder<'a> {
         Self {
             token: Default::default(),
             channel: &ApiChannel::Stable,
+            retry_policy: RetryPolicy::default(),
         }
     }
 }
@@ -101,6 +108,10 @@ impl<'a> GQLClientBuilder<'a> {
         self.channel = channel;
         self
     }
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
 
     pub fn build(self) -> Result<GQLClient, APIError> {
         let mut headers = header::HeaderMap::new();
@@ -127,6 +138,7 @@ impl<'a> GQLClientBuilder<'a> {
         Ok(GQLClient {
             inner: reqwest_client,
             error_handler: setup_error_handler(self.channel),
+            retry_policy: self.retry_policy,
         })
     }
 }
@@ -134,9 +146,21 @@ impl<'a> GQLClientBuilder<'a> {
 pub struct GQLClient {
     inner: reqwest::Client,
     error_handler: Box<dyn ErrorHandler>,
+    retry_policy: RetryPolicy,
+}
+
+/// Why a GraphQL request failed, and whether it is worth retrying.
+enum RequestFailure {
+    Retryable { reason: String, error: APIError },
+    Fatal(APIError),
 }
 
 impl GQLClient {
+    /// Send the query, retrying the transport errors, the 502/503/504 responses and the
+    /// timeout errors of the Wukong API according to the [RetryPolicy].
+    ///
+    /// Mutations are only retried when they can't have reached the server, or when the
+    /// Wukong API tells they timed out, so that e.g. a deployment isn't executed twice.
     async fn post_graphql<Q, U>(
         &self,
         url: U,
@@ -147,68 +171,133 @@ impl GQLClient {
         U: reqwest::IntoUrl + Clone + Debug,
         Q::ResponseData: Debug,
     {
-        let mut retry_count = 0;
         let body = Q::build_query(variables);
+        let is_mutation = body.query.trim_start().starts_with("mutation");
         debug!("url: {:?}", &url);
         debug!("query: \n{}", body.query);
 
-        debug!("reqwest client: {:#?}", self.inner);
-        let request = self.inner.post(url.clone()).json(&body);
-        debug!("request: {:#?}", request);
+        let started_at = Instant::now();
+        let mut retry_count = 0;
 
-        let response: Response<Q::ResponseData> =
-            request
-                .send()
-                .await?
-                .json()
+        loop {
+            let (reason, error) = match self
+                .send_graphql::<Q, U>(url.clone(), &body, is_mutation)
                 .await
-                .map_err(|err: reqwest::Error| {
-                    debug!("response: {:#?}", err);
-                    APIError::MissingResponseData
-                })?;
-
-        debug!("response: {:#?}", response);
+            {
+                Ok(data) => return Ok(data),
+                Err(RequestFailure::Fatal(error)) => return Err(error),
+                Err(RequestFailure::Retryable { reason, error }) => (reason, error),
+            };
+
+            match self
+                .retry_policy
+                .next_delay(retry_count, started_at.elapsed())
+            {
+                Some(delay) => {
+                    retry_count += 1;
+                    eprintln!(
+                        "... {reason}, retrying the request {retry_count}/{}",
+                        self.retry_policy.max_retries
+                    );
+                    debug!("retrying in {delay:?}");
+
+                    tokio::time::sleep(delay).await;
+                }
+                None => return Err(error),
+            }
+        }
+    }
 
-        // We use <= 3 so it does one extra loop where the last response is checked
-        // in order to return an APIError::Timeout if it was a timeout error in the
-        // case of it failing all 3 retries.
-        while response.errors.is_some() && retry_count <= 3 {
-            if let Some(errors) = response.errors.clone() {
-                let first_error = errors[0].clone();
-
-                let first_error_code = self.error_handler.extract_error_code(&first_error);
-                match get_timeout_error(first_error_code) {
-                    Some(APIError::Timeout { domain }) => {
-                        if retry_count == 3 {
-                            return Err(APIError::Timeout { domain });
-                        }
-                        retry_count += 1;
-                        eprintln!(
-                            "... request to {domain} timed out, retrying the request {}/3",
-                            retry_count
-                        );
-
-                        thread::sleep(time::Duration::from_secs(5));
-
-                        let request = self.inner.post(url.clone()).json(&body);
-                        debug!("request: {:#?}", request);
-
-                        let response: Response<Q::ResponseData> =
-                            request.send().await?.json().await?;
-                        debug!("response: {:#?}", response);
-                    }
-                    _ => {
-                        return Err(self.error_handler.handle_error(&first_error));
-                    }
+    async fn send_graphql<Q, U>(
+        &self,
+        url: U,
+        body: &QueryBody<Q::Variables>,
+        is_mutation: bool,
+    ) -> Result<Q::ResponseData, RequestFailure>
+    where
+        Q: GraphQLQuery,
+        U: reqwest::IntoUrl,
+        Q::ResponseData: Debug,
+    {
+        let request = self
+            .inner
+            .post(url)
+            .json(body)
+            .build()
+            .map_err(|err| RequestFailure::Fatal(err.into()))?;
+        // not the headers, which have the access token
+        debug!("request: {} {}", request.method(), request.url());
+
+        let response = self.inner.execute(request).await.map_err(|err| {
+            debug!("request error: {:#?}", err);
+            // a mutation which failed after connecting might have been applied already
+            if err.is_connect() || (!is_mutation && (err.is_timeout() || err.is_request())) {
+                RequestFailure::Retryable {
+                    reason: "unable to reach the Wukong API".to_string(),
+                    error: err.into(),
                 }
+            } else {
+                RequestFailure::Fatal(err.into())
+            }
+        })?;
+
+        let status = response.status();
+        if matches!(
+            status,
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+        ) {
+            let error = APIError::ServerUnavailable {
+                status: status.as_u16(),
+            };
+
+            // the gateway timed out waiting for the response, not before sending the request
+            if is_mutation && status == StatusCode::GATEWAY_TIMEOUT {
+                return Err(RequestFailure::Fatal(error));
             }
+
+            return Err(RequestFailure::Retryable {
+                reason: format!("the Wukong API responded with {status}"),
+                error,
+            });
         }
 
-        if let Some(data) = response.data {
-            Ok(data)
-        } else {
-            Err(APIError::MissingResponseData)
+        let bytes = response.bytes().await.map_err(|err| {
+            debug!("response error: {:#?}", err);
+            if is_mutation {
+                RequestFailure::Fatal(err.into())
+            } else {
+                RequestFailure::Retryable {
+                    reason: "unable to read the response of the Wukong API".to_string(),
+                    error: err.into(),
+                }
+            }
+        })?;
+
+        let response: Response<Q::ResponseData> =
+            serde_json::from_slice(&bytes).map_err(|err| {
+                debug!("response: {:#?}", err);
+                RequestFailure::Fatal(APIError::MissingResponseData)
+            })?;
+
+        debug!("response: {:#?}", response);
+
+        if let Some(first_error) = response.errors.as_ref().and_then(|errors| errors.first()) {
+            let first_error_code = self.error_handler.extract_error_code(first_error);
+
+            return match get_timeout_error(first_error_code) {
+                Some(APIError::Timeout { domain }) => Err(RequestFailure::Retryable {
+                    reason: format!("request to {domain} timed out"),
+                    error: APIError::Timeout { domain },
+                }),
+                _ => Err(RequestFailure::Fatal(
+                    self.error_handler.handle_error(first_error),
+                )),
+            };
         }
+
+        response
+            .data
+            .ok_or(RequestFailure::Fatal(APIError::MissingResponseData))
     }
 }
 
@@ -216,12 +305,11 @@ impl GQLClient {
 impl WKClient {
     /// Fetch supported applications from Wukong API Proxy.
     pub async fn fetch_applications(&self) -> Result<applications_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
-
-        gql_client
-            .post_graphql::<ApplicationsQuery, _>(&self.api_url, applications_query::Variables)
-            .await
-            .map_err(|err| err.into())
+        self.post_cached_graphql::<ApplicationsQuery>(
+            CachedQuery::Applications,
+            applications_query::Variables,
+        )
+        .await
     }
 
     /// Fetch the application info from Wukong API Proxy.
@@ -229,7 +317,7 @@ impl WKClient {
         &self,
         name: &str,
     ) -> Result<application_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<ApplicationQuery, _>(
@@ -251,17 +339,13 @@ impl WKClient {
         &self,
         application: &str,
     ) -> Result<cd_pipelines_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
-
-        gql_client
-            .post_graphql::<CdPipelinesQuery, _>(
-                &self.api_url,
-                cd_pipelines_query::Variables {
-                    application: application.to_string(),
-                },
-            )
-            .await
-            .map_err(|err| err.into())
+        self.post_cached_graphql::<CdPipelinesQuery>(
+            CachedQuery::CdPipelines,
+            cd_pipelines_query::Variables {
+                application: application.to_string(),
+            },
+        )
+        .await
     }
 
     /// Fetch CD pipeline from Wukong API Proxy.
@@ -275,7 +359,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<cd_pipeline_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<CdPipelineQuery, _>(
@@ -300,7 +384,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<cd_pipeline_status_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<CdPipelineStatusQuery, _>(
@@ -326,7 +410,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<cd_pipeline_github_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<CdPipelineGithubQuery, _>(
@@ -356,7 +440,7 @@ impl WKClient {
         version: &str,
         build_artifact_name: &str,
     ) -> Result<changelogs_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<ChangelogsQuery, _>(
@@ -390,9 +474,9 @@ impl WKClient {
         changelogs: Option<String>,
         send_to_slack: bool,
     ) -> Result<execute_cd_pipeline::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
-        gql_client
+        let response = gql_client
             .post_graphql::<ExecuteCdPipeline, _>(
                 &self.api_url,
                 execute_cd_pipeline::Variables {
@@ -405,8 +489,12 @@ impl WKClient {
                     send_to_slack,
                 },
             )
-            .await
-            .map_err(|err| err.into())
+            .await;
+
+        // even a failed request might have started the deployment
+        self.invalidate_cache(CachedQuery::CdPipelines);
+
+        response.map_err(|err| err.into())
     }
 
     /// Fetch previous CD pipeline build from Wukong API Proxy.
@@ -421,7 +509,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<cd_pipeline_for_rollback_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<CdPipelineForRollbackQuery, _>(
@@ -449,7 +537,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<is_authorized_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<IsAuthorizedQuery, _>(
@@ -471,7 +559,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<kubernetes_pods_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<KubernetesPodsQuery, _>(
@@ -493,7 +581,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<livebook_resource_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<LivebookResourceQuery, _>(
@@ -518,7 +606,7 @@ impl WKClient {
         name: &str,
         port: i64,
     ) -> Result<deploy_livebook::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<DeployLivebook, _>(
@@ -542,7 +630,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<destroy_livebook::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<DestroyLivebook, _>(
@@ -564,7 +652,7 @@ impl WKClient {
         namespace: &str,
         version: &str,
     ) -> Result<application_with_k8s_cluster_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<ApplicationWithK8sClusterQuery, _>(
@@ -587,7 +675,7 @@ impl WKClient {
         until: &str,
         timeframe: AppsignalTimeFrame,
     ) -> Result<appsignal_average_error_rate_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<AppsignalAverageErrorRateQuery, _>(
@@ -612,7 +700,7 @@ impl WKClient {
         until: &str,
         timeframe: AppsignalTimeFrame,
     ) -> Result<appsignal_average_latency_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<AppsignalAverageLatencyQuery, _>(
@@ -637,7 +725,7 @@ impl WKClient {
         until: &str,
         timeframe: AppsignalTimeFrame,
     ) -> Result<appsignal_average_throughput_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<AppsignalAverageThroughputQuery, _>(
@@ -657,7 +745,7 @@ impl WKClient {
     pub async fn fetch_appsignal_apps(
         &self,
     ) -> Result<appsignal_apps_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<AppsignalAppsQuery, _>(
@@ -672,17 +760,13 @@ impl WKClient {
         &self,
         name: &str,
     ) -> Result<application_config_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
-
-        gql_client
-            .post_graphql::<ApplicationConfigQuery, _>(
-                &self.api_url,
-                application_config_query::Variables {
-                    name: name.to_string(),
-                },
-            )
-            .await
-            .map_err(|err| err.into())
+        self.post_cached_graphql::<ApplicationConfigQuery>(
+            CachedQuery::ApplicationConfig,
+            application_config_query::Variables {
+                name: name.to_string(),
+            },
+        )
+        .await
     }
 
     /// Fetch the deploy markers from Appsignal
@@ -692,7 +776,7 @@ impl WKClient {
         app_id: &str,
         limit: Option<i64>,
     ) -> Result<appsignal_deploy_markers_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<AppsignalDeployMarkersQuery, _>(
@@ -714,7 +798,7 @@ impl WKClient {
         marker: Option<String>,
         state: Option<AppsignalIncidentState>,
     ) -> Result<appsignal_exception_incidents_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<AppsignalExceptionIncidentsQuery, _>(
@@ -734,15 +818,11 @@ impl WKClient {
     pub async fn fetch_github_workflow_templates(
         &self,
     ) -> Result<github_workflow_templates_query::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
-
-        gql_client
-            .post_graphql::<GithubWorkflowTemplatesQuery, _>(
-                &self.api_url,
-                github_workflow_templates_query::Variables {},
-            )
-            .await
-            .map_err(|err| err.into())
+        self.post_cached_graphql::<GithubWorkflowTemplatesQuery>(
+            CachedQuery::GithubWorkflowTemplates,
+            github_workflow_templates_query::Variables {},
+        )
+        .await
     }
 
     /// Fetch all secrets stored under (`application`, `namespace`, `path`)
@@ -754,7 +834,7 @@ impl WKClient {
         namespace: &str,
         path: &str,
     ) -> Result<FetchSecretsData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         let response = gql_client
             .post_graphql::<ApplicationSecretsQuery, _>(
@@ -773,7 +853,10 @@ impl WKClient {
             .map(|s| (s.key, s.value))
             .collect::<HashMap<String, String>>();
 
-        Ok(FetchSecretsData { data })
+        Ok(FetchSecretsData {
+            data,
+            metadata: None,
+        })
     }
 
     /// Upsert secrets at (`application`, `namespace`, `path`) on the Wukong
@@ -785,7 +868,7 @@ impl WKClient {
         path: &str,
         data: &HashMap<&str, &str>,
     ) -> Result<bool, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         let secrets = data
             .iter()
@@ -821,9 +904,9 @@ impl WKClient {
         content: &str,
         commit_message: Option<String>,
     ) -> Result<publish_skill::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
-        gql_client
+        let response = gql_client
             .post_graphql::<PublishSkill, _>(
                 &self.api_url,
                 publish_skill::Variables {
@@ -832,8 +915,11 @@ impl WKClient {
                     commit_message,
                 },
             )
-            .await
-            .map_err(|err| err.into())
+            .await?;
+
+        self.invalidate_cache(CachedQuery::Skills);
+
+        Ok(response)
     }
 
     /// List all skills from the registry, optionally filtered by a keyword query.
@@ -841,22 +927,18 @@ impl WKClient {
         &self,
         query: Option<&str>,
     ) -> Result<skills_list::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
-
-        gql_client
-            .post_graphql::<SkillsList, _>(
-                &self.api_url,
-                skills_list::Variables {
-                    query: query.map(|s| s.to_string()),
-                },
-            )
-            .await
-            .map_err(|err| err.into())
+        self.post_cached_graphql::<SkillsList>(
+            CachedQuery::Skills,
+            skills_list::Variables {
+                query: query.map(|s| s.to_string()),
+            },
+        )
+        .await
     }
 
     /// Fetch the raw SKILL.md content for a single skill from the registry.
     pub async fn fetch_skill(&self, slug: &str) -> Result<skill_by_slug::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<SkillBySlug, _>(
@@ -874,7 +956,7 @@ impl WKClient {
         &self,
         skills: Vec<check_skill_updates::SkillUpdateCheckInput>,
     ) -> Result<check_skill_updates::ResponseData, WKError> {
-        let gql_client = setup_gql_client(&self.access_token, &self.channel)?;
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
 
         gql_client
             .post_graphql::<CheckSkillUpdates, _>(
@@ -886,10 +968,58 @@ impl WKClient {
     }
 }
 
-fn setup_gql_client(access_token: &str, channel: &ApiChannel) -> Result<GQLClient, WKError> {
+impl WKClient {
+    /// Send a read query, using the cached response instead when the cache is enabled
+    /// with [WKClient::set_cache] and the response hasn't expired yet.
+    async fn post_cached_graphql<Q>(
+        &self,
+        query: CachedQuery,
+        variables: Q::Variables,
+    ) -> Result<Q::ResponseData, WKError>
+    where
+        Q: GraphQLQuery,
+        Q::ResponseData: Debug + Serialize,
+    {
+        let cache_key = self.cache.as_ref().and_then(|cache| {
+            cache
+                .key(&self.channel, &self.api_url, &variables)
+                .map(|key| (cache, key))
+        });
+
+        if let Some((cache, key)) = &cache_key {
+            if let Some(data) = cache.get(query, key) {
+                return Ok(data);
+            }
+        }
+
+        let gql_client = setup_gql_client(&self.access_token, &self.channel, &self.retry_policy)?;
+        let data = gql_client
+            .post_graphql::<Q, _>(&self.api_url, variables)
+            .await?;
+
+        if let Some((cache, key)) = &cache_key {
+            cache.set(query, key, &data);
+        }
+
+        Ok(data)
+    }
+
+    fn invalidate_cache(&self, query: CachedQuery) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(query);
+        }
+    }
+}
+
+fn setup_gql_client(
+    access_token: &str,
+    channel: &ApiChannel,
+    retry_policy: &RetryPolicy,
+) -> Result<GQLClient, WKError> {
     GQLClientBuilder::default()
         .with_token(access_token)
         .with_channel(channel)
+        .with_retry_policy(retry_policy.clone())
         .build()
         .map_err(|err| err.into())
 }
@@ -1008,9 +1138,33 @@ fn setup_error_handler(_channel: &ApiChannel) -> Box<dyn ErrorHandler> {
 
 #[cfg(test)]
 mod test {
-    use crate::{graphql::GQLClientBuilder, ApiChannel};
+    use crate::{
+        error::{APIError, WKError},
+        graphql::{GQLClientBuilder, ResponseCache, RetryPolicy},
+        ApiChannel, WKClient, WKConfig,
+    };
     use graphql_client::Error;
+    use httpmock::prelude::*;
     use serde_json::json;
+    use std::time::Duration;
+
+    fn setup_wk_client(api_url: &str) -> WKClient {
+        let mut wk_client = WKClient::new(WKConfig {
+            api_url: api_url.to_string(),
+            access_token: "test_access_token".to_string(),
+            channel: ApiChannel::Stable,
+        });
+        wk_client.set_retry_policy(RetryPolicy {
+            max_retries: 2,
+            initial_interval: Duration::from_millis(200),
+            multiplier: 1.0,
+            max_interval: Duration::from_millis(200),
+            max_elapsed_time: Duration::from_secs(10),
+            randomization_factor: 0.0,
+        });
+
+        wk_client
+    }
 
     #[test]
     fn test_stable_error_handler_extract_error_code() {
@@ -1079,4 +1233,217 @@ mod test {
             "application_not_found"
         );
     }
+
+    #[tokio::test]
+    async fn test_retry_on_server_unavailable_until_max_retries() {
+        let server = MockServer::start();
+        let wk_client = setup_wk_client(&server.base_url());
+
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(503).body("Service Unavailable");
+        });
+
+        let response = wk_client.fetch_applications().await;
+
+        mock.assert_hits(3);
+        match response {
+            Err(WKError::APIError(APIError::ServerUnavailable { status: 503 })) => {}
+            _ => panic!("it should be returning APIError::ServerUnavailable"),
+        };
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_from_flaky_responses() {
+        let server = MockServer::start();
+        let wk_client = setup_wk_client(&server.base_url());
+
+        let mut flaky_mock = server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(502).body("Bad Gateway");
+        });
+
+        // the proxy recovers after the first request fails
+        let recover = async {
+            while flaky_mock.hits_async().await == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            flaky_mock.delete_async().await;
+
+            server
+                .mock_async(|when, then| {
+                    when.method(POST).path("/");
+                    then.status(200)
+                        .header("content-type", "application/json; charset=UTF-8")
+                        .body(r#"{"data": {"applications": [{"name": "valid-application"}]}}"#);
+                })
+                .await
+        };
+
+        let (response, mock) = tokio::join!(wk_client.fetch_applications(), recover);
+
+        mock.assert();
+        assert_eq!(response.unwrap().applications.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_timeout_error_code() {
+        let server = MockServer::start();
+        let wk_client = setup_wk_client(&server.base_url());
+
+        let api_resp = r#"
+{
+  "data": null,
+  "errors": [
+    {
+      "message": "Request to GitHub timed out",
+      "path": ["applications"],
+      "extensions": {
+        "code": "github_timeout"
+      }
+    }
+  ]
+}"#;
+
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(api_resp);
+        });
+
+        let response = wk_client.fetch_applications().await;
+
+        mock.assert_hits(3);
+        match response {
+            Err(WKError::APIError(APIError::Timeout { domain })) => assert_eq!(domain, "github"),
+            _ => panic!("it should be returning APIError::Timeout"),
+        };
+    }
+
+    #[tokio::test]
+    async fn test_mutation_is_not_retried_on_gateway_timeout() {
+        let server = MockServer::start();
+        let wk_client = setup_wk_client(&server.base_url());
+
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(504).body("Gateway Timeout");
+        });
+
+        let response = wk_client
+            .deploy_cd_pipeline_build(
+                "valid-application",
+                "prod",
+                "green",
+                "main-build-1234",
+                None,
+                false,
+            )
+            .await;
+
+        mock.assert_hits(1);
+        match response {
+            Err(WKError::APIError(APIError::ServerUnavailable { status: 504 })) => {}
+            _ => panic!("it should be returning APIError::ServerUnavailable"),
+        };
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_connect_error() {
+        // nothing is listening on the port once the listener is dropped
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let wk_client = setup_wk_client(&format!("http://127.0.0.1:{port}"));
+
+        let started_at = std::time::Instant::now();
+        let response = wk_client.fetch_applications().await;
+
+        // 2 retries, 200ms apart
+        assert!(started_at.elapsed() >= Duration::from_millis(400));
+        match response {
+            Err(WKError::APIError(APIError::ReqwestError(error))) => assert!(error.is_connect()),
+            _ => panic!("it should be returning APIError::ReqwestError"),
+        };
+    }
+
+    #[tokio::test]
+    async fn test_cached_response_is_reused_until_invalidated_by_a_mutation() {
+        let server = MockServer::start();
+        let cache_dir = assert_fs::TempDir::new().unwrap();
+        let mut wk_client = setup_wk_client(&server.base_url());
+        wk_client.set_cache(Some(ResponseCache::new(cache_dir.path(), "user-1")));
+
+        let cd_pipelines_mock = server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("CdPipelinesQuery");
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(
+                    r#"{"data": {"cdPipelines": [{"deployedRef": null, "enabled": true, "environment": "prod", "lastDeployment": null, "name": "pipeline-green", "status": null, "version": "green", "buildArtifact": "main-build-1"}]}}"#,
+                );
+        });
+        let execute_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/")
+                .body_contains("ExecuteCdPipeline");
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(r#"{"data": {"executeCdPipeline": {"url": "https://deployment.url"}}}"#);
+        });
+
+        wk_client
+            .fetch_cd_pipelines("valid-application")
+            .await
+            .unwrap();
+        let response = wk_client.fetch_cd_pipelines("valid-application").await;
+        cd_pipelines_mock.assert_hits(1);
+        assert_eq!(response.unwrap().cd_pipelines.len(), 1);
+
+        // a different application is a different entry
+        wk_client
+            .fetch_cd_pipelines("other-application")
+            .await
+            .unwrap();
+        cd_pipelines_mock.assert_hits(2);
+
+        wk_client
+            .deploy_cd_pipeline_build(
+                "valid-application",
+                "prod",
+                "green",
+                "main-build-2",
+                None,
+                false,
+            )
+            .await
+            .unwrap();
+        execute_mock.assert();
+
+        wk_client
+            .fetch_cd_pipelines("valid-application")
+            .await
+            .unwrap();
+        cd_pipelines_mock.assert_hits(3);
+    }
+
+    #[tokio::test]
+    async fn test_response_is_not_cached_without_a_cache() {
+        let server = MockServer::start();
+        let wk_client = setup_wk_client(&server.base_url());
+
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(r#"{"data": {"applications": [{"name": "valid-application"}]}}"#);
+        });
+
+        wk_client.fetch_applications().await.unwrap();
+        wk_client.fetch_applications().await.unwrap();
+
+        mock.assert_hits(2);
+    }
 }
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+/// How the failed GraphQL requests are retried.
+///
+/// The delay before each retry grows exponentially from `initial_interval` by `multiplier`,
+/// up to `max_interval`, and is randomized by `randomization_factor` so that clients failing
+/// at the same time don't retry at the same time. It stops retrying after `max_retries`, or
+/// when the next retry would start after `max_elapsed_time`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_interval: Duration,
+    pub multiplier: f64,
+    pub max_interval: Duration,
+    pub max_elapsed_time: Duration,
+    /// `0.5` means the delay is anywhere between 50% and 150% of the computed interval.
+    pub randomization_factor: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            initial_interval: Duration::from_millis(500),
+            multiplier: 2.0,
+            max_interval: Duration::from_secs(8),
+            max_elapsed_time: Duration::from_secs(30),
+            randomization_factor: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn no_retry() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// The delay before the next retry, after `retry_count` retries and `elapsed` time
+    /// since the first attempt. `None` when it shouldn't retry anymore.
+    pub fn next_delay(&self, retry_count: u32, elapsed: Duration) -> Option<Duration> {
+        if retry_count >= self.max_retries {
+            return None;
+        }
+
+        let interval = (self.initial_interval.as_secs_f64()
+            * self.multiplier.powi(retry_count as i32))
+        .min(self.max_interval.as_secs_f64());
+        let delta = interval * self.randomization_factor.clamp(0.0, 1.0);
+        let delay = Duration::from_secs_f64(interval - delta + rand::random::<f64>() * 2.0 * delta);
+
+        if elapsed + delay > self.max_elapsed_time {
+            return None;
+        }
+
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn policy_without_jitter() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 10,
+            initial_interval: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_interval: Duration::from_secs(5),
+            max_elapsed_time: Duration::from_secs(60),
+            randomization_factor: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_delay_grows_exponentially_up_to_max_interval() {
+        let policy = policy_without_jitter();
+
+        let delays: Vec<Duration> = (0..5)
+            .map(|retry_count| policy.next_delay(retry_count, Duration::ZERO).unwrap())
+            .collect();
+
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(4),
+                Duration::from_secs(5),
+                Duration::from_secs(5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stop_retrying_after_max_retries_or_max_elapsed_time() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            ..policy_without_jitter()
+        };
+        assert!(policy.next_delay(1, Duration::ZERO).is_some());
+        assert!(policy.next_delay(2, Duration::ZERO).is_none());
+
+        let policy = policy_without_jitter();
+        assert!(policy.next_delay(0, Duration::from_secs(59)).is_none());
+
+        assert!(RetryPolicy::no_retry()
+            .next_delay(0, Duration::ZERO)
+            .is_none());
+    }
+
+    #[test]
+    fn test_delay_is_randomized_within_the_randomization_factor() {
+        let policy = RetryPolicy {
+            randomization_factor: 0.5,
+            ..policy_without_jitter()
+        };
+
+        for _ in 0..100 {
+            let delay = policy.next_delay(1, Duration::ZERO).unwrap();
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(3));
+        }
+    }
+}
@@ -9,6 +9,8 @@ mod utils;
 
 pub use utils::secret_extractors;
 
+use graphql::{ResponseCache, RetryPolicy};
+
 #[derive(Debug, Default)]
 pub enum ApiChannel {
     Canary,
@@ -26,6 +28,8 @@ pub struct WKClient {
     pub(crate) api_url: String,
     pub(crate) access_token: String,
     pub(crate) channel: ApiChannel,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) cache: Option<ResponseCache>,
 }
 
 impl WKClient {
@@ -34,6 +38,8 @@ impl WKClient {
             api_url: config.api_url,
             access_token: config.access_token,
             channel: config.channel,
+            retry_policy: RetryPolicy::default(),
+            cache: None,
         }
     }
 
@@ -48,4 +54,15 @@ impl WKClient {
     pub fn set_channel(&mut self, channel: ApiChannel) {
         self.channel = channel;
     }
+
+    /// Set how the failed requests to the Wukong API are retried.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Cache the responses of the read queries which rarely change, see [ResponseCache].
+    /// The cache is disabled by default.
+    pub fn set_cache(&mut self, cache: Option<ResponseCache>) {
+        self.cache = cache;
+    }
 }
@@ -20,6 +20,57 @@ pub struct Login {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FetchSecretsData {
     pub data: HashMap<String, String>,
+    /// The KV v2 metadata of the version read, it's `None` for the wukong provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<SecretVersionMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretVersionMetadata {
+    pub version: u64,
+    pub created_time: String,
+    #[serde(default)]
+    pub deletion_time: String,
+    #[serde(default)]
+    pub destroyed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretVersion {
+    pub created_time: String,
+    #[serde(default)]
+    pub deletion_time: String,
+    #[serde(default)]
+    pub destroyed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecretMetadata {
+    pub current_version: u64,
+    pub oldest_version: u64,
+    pub created_time: String,
+    pub updated_time: String,
+    /// The versions kept, by the version number.
+    pub versions: HashMap<String, SecretVersion>,
+}
+
+impl SecretMetadata {
+    /// The versions kept, the latest first.
+    pub fn sorted_versions(&self) -> Vec<(u64, &SecretVersion)> {
+        let mut versions: Vec<(u64, &SecretVersion)> = self
+            .versions
+            .iter()
+            .filter_map(|(version, info)| version.parse().ok().map(|version| (version, info)))
+            .collect();
+        versions.sort_by(|a, b| b.0.cmp(&a.0));
+
+        versions
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FetchSecretMetadata {
+    pub data: SecretMetadata,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -58,6 +109,7 @@ impl Default for VaultClient {
 
 impl VaultClient {
     pub const FETCH_SECRETS: &'static str = "/v1/secret/data";
+    pub const FETCH_SECRET_METADATA: &'static str = "/v1/secret/metadata";
     pub const UPDATE_SECRET: &'static str = "/v1/secret/data";
 
     pub fn new() -> Self {
@@ -97,17 +149,78 @@ impl VaultClient {
         Ok(response)
     }
 
+    pub async fn fetch_secrets_version(
+        &self,
+        api_token: &str,
+        path: &str,
+        version: u64,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let url = format!("{}{}/{}", self.base_url, Self::FETCH_SECRETS, path);
+
+        let response = self
+            .client
+            .get(url)
+            .query(&[("version", version)])
+            .header("X-Vault-Token", api_token)
+            .send()
+            .await?;
+
+        Ok(response)
+    }
+
+    pub async fn fetch_secret_metadata(
+        &self,
+        api_token: &str,
+        path: &str,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let url = format!("{}{}/{}", self.base_url, Self::FETCH_SECRET_METADATA, path);
+
+        let response = self
+            .client
+            .get(url)
+            .header("X-Vault-Token", api_token)
+            .send()
+            .await?;
+
+        Ok(response)
+    }
+
     pub async fn update_secret(
         &self,
         api_token: &str,
         path: &str,
         data: &HashMap<&str, &str>,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        self.patch_secret(api_token, path, data, None).await
+    }
+
+    /// Update the secret only if its current version is `cas`, the check-and-set of KV v2,
+    /// so the changes made since that version are not overwritten.
+    pub async fn update_secret_with_cas(
+        &self,
+        api_token: &str,
+        path: &str,
+        data: &HashMap<&str, &str>,
+        cas: u64,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        self.patch_secret(api_token, path, data, Some(cas)).await
+    }
+
+    async fn patch_secret(
+        &self,
+        api_token: &str,
+        path: &str,
+        data: &HashMap<&str, &str>,
+        cas: Option<u64>,
     ) -> Result<reqwest::Response, reqwest::Error> {
         let url = format!("{}{}/{}", self.base_url, Self::UPDATE_SECRET, path);
 
         // Update the secret with updated value:
-        let mut secret_data = HashMap::new();
-        secret_data.insert("data", data);
+        let mut secret_data = serde_json::Map::new();
+        secret_data.insert("data".to_string(), serde_json::json!(data));
+        if let Some(cas) = cas {
+            secret_data.insert("options".to_string(), serde_json::json!({ "cas": cas }));
+        }
 
         let response = self
             .client
@@ -206,4 +319,149 @@ mod tests {
         let response = response.unwrap();
         assert_eq!(response.status(), 200);
     }
+
+    #[tokio::test]
+    async fn test_fetch_secrets_version() {
+        let server = MockServer::start();
+
+        let api_token = "test_token";
+        let path = "devenv/test";
+
+        let api_resp = r#"
+            {
+              "data": {
+                "data": {
+                  "test2": "old_secret_token"
+                },
+                "metadata": {
+                  "created_time": "2024-02-22T02:24:06.945319214Z",
+                  "deletion_time": "",
+                  "destroyed": false,
+                  "version": 1
+                }
+              }
+            }"#;
+
+        let mock_server = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("{}/{}", VaultClient::FETCH_SECRETS, path))
+                .query_param("version", "1")
+                .header("X-Vault-Token", api_token);
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(api_resp);
+        });
+
+        let vault_client = VaultClient::new().with_base_url(server.base_url());
+        let response = vault_client
+            .fetch_secrets_version(api_token, path, 1)
+            .await
+            .unwrap();
+
+        mock_server.assert();
+
+        let secrets = response.json::<FetchSecrets>().await.unwrap();
+        assert_eq!(secrets.data.data.get("test2").unwrap(), "old_secret_token");
+        assert_eq!(secrets.data.metadata.unwrap().version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_secret_metadata() {
+        let server = MockServer::start();
+
+        let api_token = "test_token";
+        let path = "devenv/test";
+
+        let api_resp = r#"
+            {
+              "data": {
+                "cas_required": false,
+                "created_time": "2024-02-22T02:24:06.945319214Z",
+                "current_version": 3,
+                "max_versions": 0,
+                "oldest_version": 1,
+                "updated_time": "2024-03-22T02:24:06.945319214Z",
+                "versions": {
+                  "1": {
+                    "created_time": "2024-02-22T02:24:06.945319214Z",
+                    "deletion_time": "",
+                    "destroyed": false
+                  },
+                  "2": {
+                    "created_time": "2024-03-01T02:24:06.945319214Z",
+                    "deletion_time": "2024-03-02T02:24:06.945319214Z",
+                    "destroyed": false
+                  },
+                  "3": {
+                    "created_time": "2024-03-22T02:24:06.945319214Z",
+                    "deletion_time": "",
+                    "destroyed": false
+                  }
+                }
+              }
+            }"#;
+
+        let mock_server = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("{}/{}", VaultClient::FETCH_SECRET_METADATA, path))
+                .header("X-Vault-Token", api_token);
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(api_resp);
+        });
+
+        let vault_client = VaultClient::new().with_base_url(server.base_url());
+        let response = vault_client
+            .fetch_secret_metadata(api_token, path)
+            .await
+            .unwrap();
+
+        mock_server.assert();
+
+        let metadata = response.json::<FetchSecretMetadata>().await.unwrap().data;
+        assert_eq!(metadata.current_version, 3);
+
+        let versions = metadata.sorted_versions();
+        assert_eq!(
+            versions
+                .iter()
+                .map(|(version, _)| *version)
+                .collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+        assert_eq!(
+            versions[1].1.deletion_time,
+            "2024-03-02T02:24:06.945319214Z"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_secret_with_cas() {
+        let server = MockServer::start();
+
+        let api_token = "test_token";
+        let path = "devenv/test";
+        let mut update_data = HashMap::new();
+        update_data.insert("test", "test4");
+
+        let mock_server = server.mock(|when, then| {
+            when.method("PATCH")
+                .path_contains(VaultClient::UPDATE_SECRET)
+                .body(r#"{"data":{"test":"test4"},"options":{"cas":2}}"#)
+                .header("X-Vault-Token", api_token);
+            then.status(200)
+                .header("content-type", "application/json; charset=UTF-8")
+                .body(r#"{"data": {"version": 3}}"#);
+        });
+
+        let vault_client = VaultClient::new().with_base_url(server.base_url());
+
+        let response = vault_client
+            .update_secret_with_cas(api_token, path, &update_data, 2)
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(response.status(), 200);
+    }
 }
@@ -1,8 +1,8 @@
 pub mod client;
 
-use self::client::{FetchSecretsData, VaultClient};
+use self::client::{FetchSecretsData, SecretMetadata, VaultClient};
 use crate::error::{APIError, VaultError, WKError};
-use crate::services::vault::client::FetchSecrets;
+use crate::services::vault::client::{FetchSecretMetadata, FetchSecrets};
 use crate::WKClient;
 use log::debug;
 use reqwest::StatusCode;
@@ -20,6 +20,8 @@ async fn handle_error(response: reqwest::Response) -> Result<(), VaultError> {
         StatusCode::BAD_REQUEST => {
             if message.contains("Okta auth failed") {
                 Err(VaultError::AuthenticationFailed)
+            } else if message.contains("check-and-set parameter did not match") {
+                Err(VaultError::CheckAndSetMismatch)
             } else {
                 Err(VaultError::ResponseError {
                     code: status.to_string(),
@@ -63,6 +65,61 @@ impl WKClient {
         }
     }
 
+    /// Get the secrets of a `version` from Vault, a KV v2 secret keeps its previous versions.
+    ///
+    /// It will return [`WKError::VaultError`] if the response is not success.
+    pub async fn get_secrets_version(
+        &self,
+        api_token: &str,
+        path: &str,
+        version: u64,
+    ) -> Result<FetchSecretsData, WKError> {
+        let vault_client = VaultClient::new();
+        let response = vault_client
+            .fetch_secrets_version(api_token, path, version)
+            .await
+            .map_err(<reqwest::Error as Into<APIError>>::into)?;
+
+        if response.status().is_success() {
+            let secrets = response
+                .json::<FetchSecrets>()
+                .await
+                .map_err(<reqwest::Error as Into<APIError>>::into)?;
+
+            Ok(secrets.data)
+        } else {
+            handle_error(response).await?;
+            unreachable!()
+        }
+    }
+
+    /// Get the KV v2 metadata of the secrets from Vault, with the versions kept.
+    ///
+    /// It will return [`WKError::VaultError`] if the response is not success.
+    pub async fn get_secret_metadata(
+        &self,
+        api_token: &str,
+        path: &str,
+    ) -> Result<SecretMetadata, WKError> {
+        let vault_client = VaultClient::new();
+        let response = vault_client
+            .fetch_secret_metadata(api_token, path)
+            .await
+            .map_err(<reqwest::Error as Into<APIError>>::into)?;
+
+        if response.status().is_success() {
+            let metadata = response
+                .json::<FetchSecretMetadata>()
+                .await
+                .map_err(<reqwest::Error as Into<APIError>>::into)?;
+
+            Ok(metadata.data)
+        } else {
+            handle_error(response).await?;
+            unreachable!()
+        }
+    }
+
     /// Get secret by `key` value from Vault.
     ///
     /// It will return [`WKError::VaultError`] if the response is not success.
@@ -100,4 +157,28 @@ impl WKClient {
 
         Ok(true)
     }
+
+    /// Update secret on Vault, only if its current version is still `cas`.
+    ///
+    /// It will return [`VaultError::CheckAndSetMismatch`] if the secret has a newer version.
+    pub async fn update_secret_with_cas(
+        &self,
+        api_token: &str,
+        path: &str,
+        data: &HashMap<&str, &str>,
+        cas: u64,
+    ) -> Result<bool, WKError> {
+        let vault_client = VaultClient::new();
+
+        let response = vault_client
+            .update_secret_with_cas(api_token, path, data, cas)
+            .await
+            .map_err(<reqwest::Error as Into<APIError>>::into)?;
+
+        if !response.status().is_success() {
+            handle_error(response).await?;
+        }
+
+        Ok(true)
+    }
 }
@@ -11,6 +11,8 @@ use tree_sitter::{Parser, Query, QueryCursor};
 // secret_path = path/to/secret
 // secret_name = secret_key
 // destination_file = dev.secret.exs
+pub const SECRETS_LOCATION_KEY: &str = "wukong.mindvalley.dev/config-secrets-location";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VaultSecretAnnotation {
     pub key: String,
@@ -78,60 +80,132 @@ pub fn read_vault_annotation(src: &str) -> Vec<VaultSecretAnnotation> {
             .find(|c| c.index == comment_idx)
             .unwrap();
         let annotation_text = annotation.node.utf8_text(src.as_bytes()).unwrap();
-        let annotation_part: Vec<String> = annotation_text
-            .replacen('#', "", 1)
-            .split(": ")
-            .map(|each| each.trim().to_string())
-            .collect();
 
-        if annotation_part.len() != 2 {
-            continue;
+        let file_name = each
+            .captures
+            .iter()
+            .find(|c| c.index == import_file_idx)
+            .unwrap();
+        let destination_file = file_name.node.utf8_text(src.as_bytes()).unwrap().trim();
+
+        if let Some(annotation) = parse_vault_annotation(annotation_text, destination_file) {
+            annotations.push(annotation);
         }
+    }
+
+    annotations
+}
+
+/// Parse the annotation comment, e.g.
+/// `# wukong.mindvalley.dev/config-secrets-location: vault:secret/path/to/secret#secret_key`,
+/// of the `destination_file`.
+fn parse_vault_annotation(
+    annotation_text: &str,
+    destination_file: &str,
+) -> Option<VaultSecretAnnotation> {
+    let annotation_part: Vec<String> = annotation_text
+        .replacen('#', "", 1)
+        .split(": ")
+        .map(|each| each.trim().to_string())
+        .collect();
+
+    if annotation_part.len() != 2 {
+        return None;
+    }
+
+    let key = annotation_part[0].clone();
+    let value = &annotation_part[1];
+
+    parse_vault_location(value, destination_file)
+        .map(|annotation| VaultSecretAnnotation { key, ..annotation })
+}
+
+/// Parse the location of the secrets, e.g. `vault:secret/path/to/secret#secret_key`, as if it
+/// was annotated on the `destination_file`.
+pub fn parse_vault_location(value: &str, destination_file: &str) -> Option<VaultSecretAnnotation> {
+    let value_part = value.split('#').collect::<Vec<&str>>();
+    if value_part.len() != 2 {
+        return None;
+    }
+    let secret_path_with_source = value_part[0].to_string();
+    let secret_name = value_part[1].to_string();
 
-        let key = annotation_part[0].clone();
-        let value = annotation_part[1].clone();
-        let value_part = value.split('#').collect::<Vec<&str>>();
-        if value_part.len() != 2 {
+    let splited_source_and_path = secret_path_with_source.split(':').collect::<Vec<&str>>();
+    if splited_source_and_path.len() != 2 {
+        return None;
+    }
+    let source = splited_source_and_path[0].to_string();
+    let path_with_engine = splited_source_and_path[1].to_string();
+
+    let splited_engine_and_path = path_with_engine.split('/').collect::<Vec<&str>>();
+    let (engine, path) = splited_engine_and_path.split_at(1);
+
+    let secret_path = path.join("/");
+
+    Some(VaultSecretAnnotation {
+        key: SECRETS_LOCATION_KEY.to_string(),
+        source,
+        engine: engine[0].to_string(),
+        secret_path,
+        secret_name,
+        destination_file: destination_file.to_string(),
+        raw: value.to_string(),
+    })
+}
+
+// The YAML annotation pattern, the value of the next key is the destination file:
+// # wukong.mindvalley.dev/config-secrets-location: vault:secret/path/to/secret#secret_key
+// secrets_file: config/secrets.yaml
+pub fn read_yaml_vault_annotation(src: &str) -> Vec<VaultSecretAnnotation> {
+    let mut annotations = vec![];
+    let mut lines = src.lines().map(str::trim).peekable();
+
+    while let Some(line) = lines.next() {
+        let is_annotation = line
+            .strip_prefix('#')
+            .is_some_and(|comment| comment.trim_start().starts_with(SECRETS_LOCATION_KEY));
+        if !is_annotation {
             continue;
         }
-        let secret_path_with_source = value_part[0].to_string();
-        let secret_name = value_part[1].to_string();
 
-        let splited_source_and_path = secret_path_with_source.split(':').collect::<Vec<&str>>();
-        if splited_source_and_path.len() != 2 {
+        // like the Elixir annotation, it should be followed by the destination, only the
+        // blank lines are allowed in between
+        while lines.next_if(|next| next.is_empty()).is_some() {}
+        let Some(destination_file) = lines.peek().and_then(|next| yaml_scalar_value(next)) else {
             continue;
-        }
-        let source = splited_source_and_path[0].to_string();
-        let path_with_engine = splited_source_and_path[1].to_string();
+        };
 
-        let splited_engine_and_path = path_with_engine.split('/').collect::<Vec<&str>>();
-        let (engine, path) = splited_engine_and_path.split_at(1);
+        if let Some(annotation) = parse_vault_annotation(line, destination_file) {
+            annotations.push(annotation);
+        }
+    }
 
-        let secret_path = path.join("/");
+    annotations
+}
 
-        let file_name = each
-            .captures
-            .iter()
-            .find(|c| c.index == import_file_idx)
-            .unwrap();
+/// The value of a `key: value` or `- value` line, without the quotes and the trailing
+/// comment. `None` if it's a comment or the value is a nested one.
+fn yaml_scalar_value(line: &str) -> Option<&str> {
+    if line.starts_with('#') {
+        return None;
+    }
 
-        annotations.push(VaultSecretAnnotation {
-            key,
-            source,
-            engine: engine[0].to_string(),
-            secret_path,
-            secret_name,
-            destination_file: file_name
-                .node
-                .utf8_text(src.as_bytes())
-                .unwrap()
-                .trim()
-                .to_string(),
-            raw: value,
-        });
+    let value = match line.strip_prefix("- ") {
+        Some(item) => item,
+        None => line.split_once(": ").map(|(_, value)| value)?,
     }
+    .trim();
 
-    annotations
+    let value = match value.chars().next() {
+        Some(quote @ ('"' | '\'')) => value[1..].split(quote).next()?,
+        _ => value.split(" #").next()?.trim(),
+    };
+
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
 }
 
 #[cfg(test)]
@@ -241,4 +315,62 @@ mod test {
         assert_eq!(annotations[5].secret_name, "prod.secrets.exs".to_string());
         assert_eq!(annotations[5].destination_file, "prod.secrets.exs");
     }
+
+    #[test]
+    fn test_read_yaml_vault_annotation() {
+        let src = r#"
+    app:
+      name: my-app
+
+    # wukong.mindvalley.dev/config-secrets-location: vault:secret/path/to/secret#secret_key
+    secrets_file: config/secrets.yaml
+
+    database:
+      # wukong.mindvalley.dev/config-secrets-location: vault:secret/abc/development#db.json
+      credentials: "config/db.json" # loaded on boot
+
+    # random comment
+
+    # wukong.mindvalley.dev/config-secrets-location: vault:secret/osiris/development#dev.yaml
+
+    # wukong.mindvalley.dev/config-secrets-location: vault:secret/xyz/development#.env
+
+    env_files:
+      - '.env'
+
+    includes:
+      # wukong.mindvalley.dev/config-secrets-location: vault:secret/xyz/development#local.yml
+      - local.yml
+
+    # wukong.mindvalley.dev/config-secrets-location: vault:secret/abc/development#nested.yaml
+    nested:
+      file: nested.yaml
+
+    # you can use wukong.mindvalley.dev/config-secrets-location: vault:secret/location to annotate the secret path
+    not_match: not_match.yaml
+
+    # wukong.mindvalley.dev/config-secrets-location: vault:secret
+    invalid: invalid.yaml
+    "#;
+
+        let annotations = read_yaml_vault_annotation(src);
+        assert_eq!(annotations.len(), 3);
+        assert_eq!(
+            annotations[0].key,
+            "wukong.mindvalley.dev/config-secrets-location".to_string(),
+        );
+        assert_eq!(annotations[0].source, "vault".to_string());
+        assert_eq!(annotations[0].engine, "secret".to_string());
+        assert_eq!(annotations[0].secret_path, "path/to/secret".to_string());
+        assert_eq!(annotations[0].secret_name, "secret_key".to_string());
+        assert_eq!(annotations[0].destination_file, "config/secrets.yaml");
+
+        assert_eq!(annotations[1].secret_path, "abc/development".to_string());
+        assert_eq!(annotations[1].secret_name, "db.json".to_string());
+        assert_eq!(annotations[1].destination_file, "config/db.json");
+
+        assert_eq!(annotations[2].secret_path, "xyz/development".to_string());
+        assert_eq!(annotations[2].secret_name, "local.yml".to_string());
+        assert_eq!(annotations[2].destination_file, "local.yml");
+    }
 }
@@ -0,0 +1,194 @@
+use crate::{error::ExtractError, utils::annotations::parse_vault_location};
+
+use super::{SecretExtractor, SecretInfo};
+use owo_colors::OwoColorize;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+struct SecretsLocation {
+    /// Like the value of the annotation, e.g. `vault:secret/wukong-cli/development#dotenv`.
+    location: String,
+    dst: String,
+}
+
+/// Extract secret locations from the `$wukongSecrets` key of JSON config file, as JSON has no
+/// comments to annotate with.
+///
+/// For example,
+/// ```json
+/// // at /a/b/c/config/default.json
+/// {
+///   "$wukongSecrets": [
+///     { "location": "vault:secret/wukong-cli/development#secrets.json", "dst": "secrets.json" }
+///   ]
+/// }
+/// ```
+///
+/// Extract to
+/// ```
+/// # use wukong_sdk::secret_extractors::SecretInfo;
+/// SecretInfo {
+///     key: "vault:secret/wukong-cli/development#secrets.json".to_string(),
+///     provider: "bunker".to_string(),
+///     kind: "json_config".to_string(),
+///     src: "wukong-cli/development".to_string(),
+///     destination_file: "secrets.json".to_string(),
+///     name: "secrets.json".to_string(),
+///     annotated_file: "/a/b/c/config/default.json".into()
+/// };
+/// ```
+pub struct JsonConfigExtractor;
+impl JsonConfigExtractor {
+    /// Only the JSON files with it are annotated.
+    pub const SECRETS_KEY: &'static str = "$wukongSecrets";
+
+    /// Same as [SecretExtractor::extract], from the `content` of the file already read.
+    ///
+    /// A file which isn't strict JSON, e.g. a JSONC one with comments, is skipped with a
+    /// warning, as it can only be loaded by its own application.
+    pub fn extract_from(file: &Path, content: &str) -> Result<Vec<SecretInfo>, ExtractError> {
+        let mut config: serde_json::Value = match serde_json::from_str(content) {
+            Ok(config) => config,
+            Err(error) => {
+                eprintln!(
+                    "⚠️  [json_config] {} has the {} key but it's not valid JSON ({}). It will be ignored.",
+                    file.to_string_lossy().cyan(),
+                    Self::SECRETS_KEY.cyan(),
+                    error
+                );
+                return Ok(Vec::new());
+            }
+        };
+
+        let locations: Vec<SecretsLocation> = match config.get_mut(Self::SECRETS_KEY) {
+            Some(locations) => serde_json::from_value(locations.take())?,
+            None => Vec::new(),
+        };
+
+        let mut extracted = Vec::new();
+
+        for location in locations {
+            let Some(annotation) = parse_vault_location(&location.location, &location.dst) else {
+                continue;
+            };
+
+            if annotation.source == "vault" && annotation.engine == "secret" {
+                extracted.push(SecretInfo {
+                    key: annotation.raw,
+                    provider: "bunker".to_string(),
+                    kind: "json_config".to_string(),
+                    src: annotation.secret_path.clone(),
+                    destination_file: annotation.destination_file.clone(),
+                    name: annotation.secret_name.clone(),
+                    annotated_file: file.to_path_buf(),
+                });
+            }
+        }
+
+        Ok(extracted)
+    }
+}
+
+impl SecretExtractor for JsonConfigExtractor {
+    fn extract(file: &Path) -> Result<Vec<SecretInfo>, ExtractError> {
+        let content = std::fs::read_to_string(file).unwrap();
+
+        Self::extract_from(file, &content)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+
+    #[test]
+    fn test_json_config_extractor() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let config_path = dir.path().join("default.json");
+
+        let mut config = File::create(&config_path).unwrap();
+        writeln!(
+            config,
+            r#"{{
+              "$wukongSecrets": [
+                {{ "location": "vault:secret/wukong-cli/sandboxes#secrets.json", "dst": "secrets.json" }},
+                {{ "location": "vault:secret/wukong-cli/sandboxes#dotenv", "dst": "app/.env" }},
+                {{ "location": "vault:secret", "dst": "invalid.json" }}
+              ],
+              "port": 3000
+            }}"#
+        )
+        .unwrap();
+
+        let secret_infos = JsonConfigExtractor::extract(&config_path).unwrap();
+
+        assert_eq!(secret_infos.len(), 2);
+
+        assert_eq!(
+            secret_infos[0].key,
+            "vault:secret/wukong-cli/sandboxes#secrets.json"
+        );
+        assert_eq!(secret_infos[0].name, "secrets.json");
+        assert_eq!(secret_infos[0].src, "wukong-cli/sandboxes");
+        assert_eq!(secret_infos[0].destination_file, "secrets.json");
+        assert_eq!(secret_infos[0].provider, "bunker");
+        assert_eq!(secret_infos[0].kind, "json_config");
+        assert_eq!(secret_infos[0].annotated_file, config_path);
+
+        assert_eq!(
+            secret_infos[1].key,
+            "vault:secret/wukong-cli/sandboxes#dotenv"
+        );
+        assert_eq!(secret_infos[1].name, "dotenv");
+        assert_eq!(secret_infos[1].src, "wukong-cli/sandboxes");
+        assert_eq!(secret_infos[1].destination_file, "app/.env");
+        assert_eq!(secret_infos[1].provider, "bunker");
+        assert_eq!(secret_infos[1].kind, "json_config");
+        assert_eq!(secret_infos[1].annotated_file, config_path);
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_json_config_extractor_bad_secrets() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let config_path = dir.path().join("default.json");
+
+        let mut config = File::create(&config_path).unwrap();
+        writeln!(config, r#"{{ "$wukongSecrets": "secrets.json" }}"#).unwrap();
+
+        assert!(matches!(
+            JsonConfigExtractor::extract(&config_path),
+            Err(ExtractError::BadJsonData(_))
+        ));
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_json_config_extractor_skips_jsonc() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let config_path = dir.path().join("default.json");
+
+        let mut config = File::create(&config_path).unwrap();
+        writeln!(
+            config,
+            r#"{{
+              // the secrets of the dev environment
+              "$wukongSecrets": [
+                {{ "location": "vault:secret/wukong-cli/sandboxes#secrets.json", "dst": "secrets.json" }},
+              ],
+            }}"#
+        )
+        .unwrap();
+
+        assert!(JsonConfigExtractor::extract(&config_path)
+            .unwrap()
+            .is_empty());
+
+        dir.close().unwrap();
+    }
+}
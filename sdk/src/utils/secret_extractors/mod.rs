@@ -1,10 +1,19 @@
 use std::path::{Path, PathBuf};
 
 mod elixir_config;
+mod json_config;
+mod providers;
 mod wk_toml_config;
+mod yaml_config;
 
 pub use elixir_config::ElixirConfigExtractor;
+pub use json_config::JsonConfigExtractor;
+pub use providers::{
+    BunkerProvider, FileProvider, SecretProvider, SecretProviders, SecretRevision,
+    VersionedSecrets, WukongProvider,
+};
 pub use wk_toml_config::WKTomlConfigExtractor;
+pub use yaml_config::YamlConfigExtractor;
 
 use crate::error::ExtractError;
 
@@ -29,7 +38,19 @@ use crate::error::ExtractError;
 //  │ │              │   │  │                       │    │  Vec<SecretInfo>  │  │                │  │
 //  │ └──────────────┘      └───────────────────────┘ │  │         └─────────┼─▶│  SecretDiffer  │  │
 //  │                    │                               │                   │  │                │  │
-//  │                     ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ┘  │                   │  └────────────────┘  │
+//  │ ┌──────────────┐      ┌───────────────────────┐ │  │                   │  └────────────────┘  │
+//  │ │              │   │  │                       │    │                   │                      │
+//  │ │ *.yaml/*.yml │──────▶  YamlConfigExtractor  │ │  │                   │                      │
+//  │ │              │   │  │                       │    │                   │                      │
+//  │ └──────────────┘      └───────────────────────┘ │  │                   │                      │
+//  │                    │                               │                   │                      │
+//  │ ┌──────────────┐      ┌───────────────────────┐ │  │                   │                      │
+//  │ │              │   │  │                       │    │                   │                      │
+//  │ │    *.json    │──────▶  JsonConfigExtractor  │ │  │                   │                      │
+//  │ │              │   │  │                       │    │                   │                      │
+//  │ └──────────────┘      └───────────────────────┘ │  │                   │                      │
+//  │                    │                               │                   │                      │
+//  │                     ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ─ ┘  │                   │                      │
 //  │                                                    │                   │                      │
 //  └────────────────────────────────────────────────────┘                   └──────────────────────┘
 //
@@ -38,9 +59,11 @@ pub struct SecretInfo {
     pub key: String,
     // Provider, such as "bunker"
     pub provider: String,
-    // Kind, such as "elixir_config" or "generic"
+    // Kind, such as "elixir_config", "yaml_config", "json_config", "generic", "dotenv" or
+    // "template"
     pub kind: String,
-    // the secret source path on the bunker if the provider is "bunker"
+    // the secret source path on the bunker if the provider is "bunker", served by the
+    // `SecretProvider` of the same name, or the path of the template for the "template" kind
     pub src: String,
     // the destination file path on the local
     pub destination_file: String,
@@ -0,0 +1,127 @@
+use super::{SecretProvider, SecretRevision, VersionedSecrets};
+use crate::{error::WKError, WKClient};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// The secrets in the Bunker, i.e. Vault, e.g. `vault:secret/wukong-cli/development#dotenv`.
+/// The `src` is the path after `secret/`.
+pub struct BunkerProvider<'a> {
+    client: &'a WKClient,
+    vault_token: &'a str,
+}
+
+impl<'a> BunkerProvider<'a> {
+    pub fn new(client: &'a WKClient, vault_token: &'a str) -> Self {
+        Self {
+            client,
+            vault_token,
+        }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for BunkerProvider<'_> {
+    fn name(&self) -> &'static str {
+        "bunker"
+    }
+
+    async fn fetch(&self, src: &str) -> Result<HashMap<String, String>, WKError> {
+        Ok(self.client.get_secrets(self.vault_token, src).await?.data)
+    }
+
+    async fn update(&self, src: &str, secrets: &HashMap<&str, &str>) -> Result<(), WKError> {
+        self.client
+            .update_secret(self.vault_token, src, secrets)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_versioned(&self, src: &str) -> Result<VersionedSecrets, WKError> {
+        let secrets = self.client.get_secrets(self.vault_token, src).await?;
+
+        Ok(VersionedSecrets {
+            data: secrets.data,
+            version: secrets.metadata.map(|metadata| metadata.version),
+        })
+    }
+
+    async fn update_versioned(
+        &self,
+        src: &str,
+        secrets: &HashMap<&str, &str>,
+        version: Option<u64>,
+    ) -> Result<(), WKError> {
+        match version {
+            Some(version) => {
+                self.client
+                    .update_secret_with_cas(self.vault_token, src, secrets, version)
+                    .await?;
+            }
+            None => {
+                self.client
+                    .update_secret(self.vault_token, src, secrets)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_version(&self, src: &str, version: u64) -> Result<VersionedSecrets, WKError> {
+        let secrets = self
+            .client
+            .get_secrets_version(self.vault_token, src, version)
+            .await?;
+
+        Ok(VersionedSecrets {
+            data: secrets.data,
+            version: Some(
+                secrets
+                    .metadata
+                    .map_or(version, |metadata| metadata.version),
+            ),
+        })
+    }
+
+    async fn history(&self, src: &str) -> Result<Vec<SecretRevision>, WKError> {
+        let metadata = self
+            .client
+            .get_secret_metadata(self.vault_token, src)
+            .await?;
+
+        let mut revisions = Vec::new();
+        for (version, info) in metadata.sorted_versions() {
+            let deleted = info.destroyed || !info.deletion_time.is_empty();
+            let data = if deleted {
+                None
+            } else {
+                Some(
+                    self.client
+                        .get_secrets_version(self.vault_token, src, version)
+                        .await?
+                        .data,
+                )
+            };
+
+            revisions.push(SecretRevision {
+                version,
+                created_time: info.created_time.clone(),
+                current: version == metadata.current_version,
+                deleted,
+                destroyed: info.destroyed,
+                data,
+            });
+        }
+
+        Ok(revisions)
+    }
+
+    fn creates_missing_secrets(&self) -> bool {
+        false
+    }
+
+    fn describe(&self, src: &str, name: &str) -> String {
+        format!("vault:secret/{src}#{name}")
+    }
+}
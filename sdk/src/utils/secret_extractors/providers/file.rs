@@ -0,0 +1,393 @@
+use super::SecretProvider;
+use crate::error::{SecretProviderError, WKError};
+use async_trait::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsString,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+/// The secrets in an encrypted file in the repo, e.g. `file:secrets/dev.json.age#dotenv`.
+/// The `src` is the path of the file, resolved from the directory of the `.wukong.toml`.
+///
+/// The file is a JSON object of the secret names and values, encrypted with `age` if it
+/// ends with `.age`, or with `gpg` if it ends with `.gpg` or `.asc`. It's decrypted with the
+/// identity file in `WUKONG_AGE_IDENTITY` (`~/.config/age/keys.txt` by default) or the gpg
+/// keyring, and encrypted for the recipients listed in `<file>.recipients`, one per line.
+pub struct FileProvider;
+
+#[async_trait]
+impl SecretProvider for FileProvider {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn fetch(&self, src: &str) -> Result<HashMap<String, String>, WKError> {
+        let path = Path::new(src);
+        let cipher = Cipher::of(path)?;
+
+        // a new file is created on the first update
+        if !path.try_exists().map_err(SecretProviderError::from)? {
+            return Ok(HashMap::new());
+        }
+
+        let plaintext = cipher.run(cipher.decrypt_args(path), None).await?;
+        let secrets = serde_json::from_slice(&plaintext).map_err(|source| {
+            SecretProviderError::BadSecretsFile {
+                path: src.to_string(),
+                source,
+            }
+        })?;
+
+        Ok(secrets)
+    }
+
+    async fn update(&self, src: &str, secrets: &HashMap<&str, &str>) -> Result<(), WKError> {
+        let path = Path::new(src);
+        let cipher = Cipher::of(path)?;
+        let recipients = read_recipients(&recipients_path(path))?;
+
+        // sorted, so the changes are easy to review once decrypted
+        let mut updated = self
+            .fetch(src)
+            .await?
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        for (name, value) in secrets {
+            updated.insert(name.to_string(), value.to_string());
+        }
+        let mut plaintext = serde_json::to_vec_pretty(&updated).map_err(|source| {
+            SecretProviderError::BadSecretsFile {
+                path: src.to_string(),
+                source,
+            }
+        })?;
+        plaintext.push(b'\n');
+
+        cipher
+            .run(cipher.encrypt_args(path, &recipients), Some(&plaintext))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cipher {
+    Age,
+    Gpg { armor: bool },
+}
+
+impl Cipher {
+    fn of(path: &Path) -> Result<Self, SecretProviderError> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("age") => Ok(Self::Age),
+            Some("gpg") => Ok(Self::Gpg { armor: false }),
+            Some("asc") => Ok(Self::Gpg { armor: true }),
+            _ => Err(SecretProviderError::UnsupportedEncryption {
+                path: path.to_string_lossy().to_string(),
+            }),
+        }
+    }
+
+    fn command(&self) -> &'static str {
+        match self {
+            Self::Age => "age",
+            Self::Gpg { .. } => "gpg",
+        }
+    }
+
+    fn decrypt_args(&self, path: &Path) -> Vec<OsString> {
+        let mut args: Vec<OsString> = match self {
+            Self::Age => vec![
+                "--decrypt".into(),
+                "--identity".into(),
+                age_identity().into(),
+            ],
+            Self::Gpg { .. } => vec!["--quiet".into(), "--decrypt".into()],
+        };
+        args.push(path.into());
+
+        args
+    }
+
+    fn encrypt_args(&self, path: &Path, recipients: &[String]) -> Vec<OsString> {
+        let mut args: Vec<OsString> = match self {
+            Self::Age => vec!["--encrypt".into()],
+            Self::Gpg { armor: false } => {
+                vec!["--batch".into(), "--yes".into(), "--encrypt".into()]
+            }
+            Self::Gpg { armor: true } => vec![
+                "--batch".into(),
+                "--yes".into(),
+                "--encrypt".into(),
+                "--armor".into(),
+            ],
+        };
+        for recipient in recipients {
+            args.push("--recipient".into());
+            args.push(recipient.into());
+        }
+        args.push("--output".into());
+        args.push(path.into());
+
+        args
+    }
+
+    /// Run the command with the `input` on its stdin, returning its stdout.
+    async fn run(
+        &self,
+        args: Vec<OsString>,
+        input: Option<&[u8]>,
+    ) -> Result<Vec<u8>, SecretProviderError> {
+        let command = self.command();
+        let command_failed = |error: std::io::Error| SecretProviderError::CommandFailed {
+            command,
+            message: error.to_string(),
+        };
+
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(command_failed)?;
+
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input).await.map_err(command_failed)?;
+            // closing the stdin, so the command knows the input is complete
+            drop(stdin);
+        }
+
+        let output = child.wait_with_output().await.map_err(command_failed)?;
+        if !output.status.success() {
+            return Err(SecretProviderError::CommandFailed {
+                command,
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+fn age_identity() -> PathBuf {
+    match std::env::var("WUKONG_AGE_IDENTITY") {
+        Ok(identity) => PathBuf::from(identity),
+        Err(_) => dirs::home_dir()
+            .unwrap_or_default()
+            .join(".config")
+            .join("age")
+            .join("keys.txt"),
+    }
+}
+
+fn recipients_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".recipients");
+
+    PathBuf::from(name)
+}
+
+/// The recipients in the file, one per line, the empty lines and the `#` comments are
+/// skipped.
+fn read_recipients(path: &Path) -> Result<Vec<String>, SecretProviderError> {
+    let recipients_not_found = || SecretProviderError::RecipientsNotFound {
+        path: path.to_string_lossy().to_string(),
+    };
+
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Err(recipients_not_found()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let recipients = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    if recipients.is_empty() {
+        return Err(recipients_not_found());
+    }
+
+    Ok(recipients)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_fs::prelude::{FileWriteStr, PathChild};
+
+    fn args(args: Vec<OsString>) -> Vec<String> {
+        args.into_iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_cipher_of() {
+        assert_eq!(
+            Cipher::of(Path::new("secrets/dev.json.age")).unwrap(),
+            Cipher::Age
+        );
+        assert_eq!(
+            Cipher::of(Path::new("secrets/dev.json.gpg")).unwrap(),
+            Cipher::Gpg { armor: false }
+        );
+        assert_eq!(
+            Cipher::of(Path::new("secrets/dev.json.asc")).unwrap(),
+            Cipher::Gpg { armor: true }
+        );
+        assert!(matches!(
+            Cipher::of(Path::new("secrets/dev.json")),
+            Err(SecretProviderError::UnsupportedEncryption { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cipher_args() {
+        let path = Path::new("secrets/dev.json.asc");
+        let recipients = ["dev@example.com".to_string(), "ops@example.com".to_string()];
+
+        assert_eq!(
+            args(Cipher::Gpg { armor: true }.decrypt_args(path)),
+            ["--quiet", "--decrypt", "secrets/dev.json.asc"]
+        );
+        assert_eq!(
+            args(Cipher::Gpg { armor: true }.encrypt_args(path, &recipients)),
+            [
+                "--batch",
+                "--yes",
+                "--encrypt",
+                "--armor",
+                "--recipient",
+                "dev@example.com",
+                "--recipient",
+                "ops@example.com",
+                "--output",
+                "secrets/dev.json.asc"
+            ]
+        );
+
+        let path = Path::new("secrets/dev.json.age");
+        let recipients = ["age1abc".to_string()];
+        assert_eq!(
+            args(Cipher::Age.encrypt_args(path, &recipients)),
+            [
+                "--encrypt",
+                "--recipient",
+                "age1abc",
+                "--output",
+                "secrets/dev.json.age"
+            ]
+        );
+        assert_eq!(args(Cipher::Age.decrypt_args(path))[0], "--decrypt");
+    }
+
+    #[test]
+    fn test_read_recipients() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let recipients = dir.child("dev.json.age.recipients");
+
+        assert!(matches!(
+            read_recipients(recipients.path()),
+            Err(SecretProviderError::RecipientsNotFound { .. })
+        ));
+
+        recipients
+            .write_str("# the team\nage1abc\n\n  age1xyz  \n")
+            .unwrap();
+        assert_eq!(
+            read_recipients(recipients.path()).unwrap(),
+            ["age1abc", "age1xyz"]
+        );
+        assert_eq!(
+            recipients_path(&dir.path().join("dev.json.age")),
+            recipients.path()
+        );
+
+        dir.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_missing_file() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let src = dir.path().join("dev.json.age");
+
+        assert!(FileProvider
+            .fetch(&src.to_string_lossy())
+            .await
+            .unwrap()
+            .is_empty());
+
+        dir.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_decrypt_round_trip_with_age() {
+        // `age` isn't needed to build, the round trip is only checked where it's installed
+        let is_installed = |command: &str| {
+            std::process::Command::new(command)
+                .arg("--version")
+                .output()
+                .is_ok()
+        };
+        if !is_installed("age") || !is_installed("age-keygen") {
+            eprintln!("Skipping the round trip, `age` is not installed.");
+            return;
+        }
+
+        let dir = assert_fs::TempDir::new().unwrap();
+        let identity = dir.child("keys.txt");
+        let keygen = |args: &[&std::ffi::OsStr]| {
+            let output = std::process::Command::new("age-keygen")
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap()
+        };
+        keygen(&["-o".as_ref(), identity.path().as_os_str()]);
+        dir.child("dev.json.age.recipients")
+            .write_str(&keygen(&["-y".as_ref(), identity.path().as_os_str()]))
+            .unwrap();
+        std::env::set_var("WUKONG_AGE_IDENTITY", identity.path());
+
+        let src = dir
+            .path()
+            .join("dev.json.age")
+            .to_string_lossy()
+            .to_string();
+        FileProvider
+            .update(&src, &HashMap::from([("DB_PASS", "hunter2")]))
+            .await
+            .unwrap();
+        // the secrets already in the file are kept
+        FileProvider
+            .update(&src, &HashMap::from([("API_KEY", "sk_live_123")]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            FileProvider.fetch(&src).await.unwrap(),
+            HashMap::from([
+                ("DB_PASS".to_string(), "hunter2".to_string()),
+                ("API_KEY".to_string(), "sk_live_123".to_string()),
+            ])
+        );
+        assert!(!String::from_utf8_lossy(&std::fs::read(&src).unwrap()).contains("hunter2"));
+
+        dir.close().unwrap();
+    }
+}
@@ -0,0 +1,203 @@
+mod bunker;
+mod file;
+mod wukong;
+
+pub use bunker::BunkerProvider;
+pub use file::FileProvider;
+pub use wukong::WukongProvider;
+
+use crate::{
+    error::{SecretProviderError, WKError},
+    WKClient,
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// The secrets at a `src`, with the version read for the providers keeping versions.
+#[derive(Debug, Clone, Default)]
+pub struct VersionedSecrets {
+    pub data: HashMap<String, String>,
+    /// `None` if the provider doesn't keep the versions of the secrets.
+    pub version: Option<u64>,
+}
+
+/// A version of the secrets kept by a provider.
+#[derive(Debug, Clone)]
+pub struct SecretRevision {
+    pub version: u64,
+    pub created_time: String,
+    /// Whether it's the version read by [SecretProvider::fetch].
+    pub current: bool,
+    pub deleted: bool,
+    pub destroyed: bool,
+    /// The secrets of this version by their names, `None` if they're deleted.
+    pub data: Option<HashMap<String, String>>,
+}
+
+/// Where the secrets of the `.wukong.toml` entries and the annotations are stored, by the
+/// `provider` of the entry, e.g. `bunker`. The `src` is the one extracted into the
+/// [SecretInfo](super::SecretInfo).
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// The `provider` of the entries served by this provider.
+    fn name(&self) -> &'static str;
+
+    /// Get the secrets at `src`, by their names.
+    async fn fetch(&self, src: &str) -> Result<HashMap<String, String>, WKError>;
+
+    /// Update the secrets at `src`, the other secrets there are kept.
+    async fn update(&self, src: &str, secrets: &HashMap<&str, &str>) -> Result<(), WKError>;
+
+    /// Same as [SecretProvider::fetch], with the version read if the provider keeps the
+    /// versions of the secrets.
+    async fn fetch_versioned(&self, src: &str) -> Result<VersionedSecrets, WKError> {
+        Ok(VersionedSecrets {
+            data: self.fetch(src).await?,
+            version: None,
+        })
+    }
+
+    /// Update the secrets at `src` only if their current version is still `version`, read
+    /// by [SecretProvider::fetch_versioned], so the changes made since then are not
+    /// overwritten. Without a version, it's the same as [SecretProvider::update].
+    async fn update_versioned(
+        &self,
+        src: &str,
+        secrets: &HashMap<&str, &str>,
+        _version: Option<u64>,
+    ) -> Result<(), WKError> {
+        self.update(src, secrets).await
+    }
+
+    /// The secrets at `src` as they were in `version`, for the providers keeping the
+    /// versions of the secrets.
+    async fn fetch_version(&self, _src: &str, _version: u64) -> Result<VersionedSecrets, WKError> {
+        Err(SecretProviderError::HistoryNotSupported {
+            provider: self.name().to_string(),
+        }
+        .into())
+    }
+
+    /// The versions of the secrets at `src`, the latest first.
+    async fn history(&self, _src: &str) -> Result<Vec<SecretRevision>, WKError> {
+        Err(SecretProviderError::HistoryNotSupported {
+            provider: self.name().to_string(),
+        }
+        .into())
+    }
+
+    /// Whether a secret missing at `src` is created by [SecretProvider::update], instead
+    /// of being a wrong annotation.
+    fn creates_missing_secrets(&self) -> bool {
+        true
+    }
+
+    /// How the secret `name` at `src` is shown to the user, like in the annotation.
+    fn describe(&self, src: &str, name: &str) -> String {
+        format!("{}:{src}#{name}", self.name())
+    }
+}
+
+/// The providers by their names, used to serve each entry by the provider it names.
+pub struct SecretProviders<'a> {
+    providers: HashMap<&'static str, Box<dyn SecretProvider + 'a>>,
+}
+
+impl<'a> SecretProviders<'a> {
+    /// The `bunker`, `wukong` and `file` providers. The `vault_token` is only needed by
+    /// the `bunker` provider.
+    pub fn new(client: &'a WKClient, vault_token: &'a str) -> Self {
+        let mut providers = Self {
+            providers: HashMap::new(),
+        };
+        providers.register(BunkerProvider::new(client, vault_token));
+        providers.register(WukongProvider::new(client));
+        providers.register(FileProvider);
+
+        providers
+    }
+
+    /// Add a provider, replacing the one with the same name.
+    pub fn register(&mut self, provider: impl SecretProvider + 'a) {
+        self.providers.insert(provider.name(), Box::new(provider));
+    }
+
+    pub fn get(&self, provider: &str) -> Result<&(dyn SecretProvider + 'a), WKError> {
+        self.providers
+            .get(provider)
+            .map(|provider| provider.as_ref())
+            .ok_or_else(|| {
+                SecretProviderError::UnknownProvider {
+                    provider: provider.to_string(),
+                }
+                .into()
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::WKConfig;
+
+    struct MemoryProvider;
+
+    #[async_trait]
+    impl SecretProvider for MemoryProvider {
+        fn name(&self) -> &'static str {
+            "memory"
+        }
+
+        async fn fetch(&self, src: &str) -> Result<HashMap<String, String>, WKError> {
+            Ok(HashMap::from([(
+                "dotenv".to_string(),
+                format!("SRC={src}\n"),
+            )]))
+        }
+
+        async fn update(&self, _src: &str, _secrets: &HashMap<&str, &str>) -> Result<(), WKError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_secret_providers() {
+        let client = WKClient::new(WKConfig {
+            api_url: "http://localhost".to_string(),
+            access_token: String::new(),
+            channel: crate::ApiChannel::Stable,
+        });
+        let mut providers = SecretProviders::new(&client, "");
+
+        assert_eq!(providers.get("bunker").unwrap().name(), "bunker");
+        assert_eq!(providers.get("wukong").unwrap().name(), "wukong");
+        assert_eq!(providers.get("file").unwrap().name(), "file");
+        assert!(matches!(
+            providers.get("memory"),
+            Err(WKError::SecretProviderError(
+                SecretProviderError::UnknownProvider { .. }
+            ))
+        ));
+
+        providers.register(MemoryProvider);
+        let memory = providers.get("memory").unwrap();
+        assert_eq!(
+            memory.fetch("a/b").await.unwrap()["dotenv"],
+            "SRC=a/b\n".to_string()
+        );
+        assert_eq!(memory.describe("a/b", "dotenv"), "memory:a/b#dotenv");
+        assert_eq!(memory.fetch_versioned("a/b").await.unwrap().version, None);
+        assert!(matches!(
+            memory.history("a/b").await,
+            Err(WKError::SecretProviderError(
+                SecretProviderError::HistoryNotSupported { .. }
+            ))
+        ));
+        assert!(matches!(
+            memory.fetch_version("a/b", 1).await,
+            Err(WKError::SecretProviderError(
+                SecretProviderError::HistoryNotSupported { .. }
+            ))
+        ));
+    }
+}
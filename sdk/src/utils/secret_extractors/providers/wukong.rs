@@ -0,0 +1,100 @@
+use super::SecretProvider;
+use crate::{
+    error::{SecretProviderError, WKError},
+    WKClient,
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// The application secrets kept by the Wukong API, e.g. `wukong:my-app/prod/db#dotenv`.
+/// The `src` is the `APPLICATION/NAMESPACE/PATH...` after `wukong:`.
+pub struct WukongProvider<'a> {
+    client: &'a WKClient,
+}
+
+impl<'a> WukongProvider<'a> {
+    pub fn new(client: &'a WKClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for WukongProvider<'_> {
+    fn name(&self) -> &'static str {
+        "wukong"
+    }
+
+    async fn fetch(&self, src: &str) -> Result<HashMap<String, String>, WKError> {
+        let (application, namespace, path) = parse_wukong_src(src)?;
+
+        Ok(self
+            .client
+            .get_wukong_secrets(application, namespace, path)
+            .await?
+            .data)
+    }
+
+    async fn update(&self, src: &str, secrets: &HashMap<&str, &str>) -> Result<(), WKError> {
+        let (application, namespace, path) = parse_wukong_src(src)?;
+        self.client
+            .update_wukong_secrets(application, namespace, path, secrets)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Split a wukong-provider `src` (`APPLICATION/NAMESPACE/PATH...`) into its
+/// three components.
+///
+/// The `src` doesn't only come from a validated `.wukong.toml`, e.g. it's given to
+/// `wukong dev config rotate` or referenced in a template, so an empty segment is an
+/// error rather than being forwarded to the backend.
+fn parse_wukong_src(src: &str) -> Result<(&str, &str, &str), SecretProviderError> {
+    let mut parts = src.splitn(3, '/');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(application), Some(namespace), Some(path))
+            if !application.is_empty() && !namespace.is_empty() && !path.is_empty() =>
+        {
+            Ok((application, namespace, path))
+        }
+        _ => Err(SecretProviderError::InvalidSource {
+            src: src.to_string(),
+            expected: "APPLICATION/NAMESPACE/PATH",
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_wukong_src() {
+        assert_eq!(
+            parse_wukong_src("my-app/prod/db/primary").unwrap(),
+            ("my-app", "prod", "db/primary")
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed_wukong_src() {
+        for src in [
+            "",
+            "my-app",
+            "my-app/prod",
+            "my-app//db",
+            "/prod/db",
+            "my-app/prod/",
+        ] {
+            assert!(
+                matches!(
+                    parse_wukong_src(src),
+                    Err(SecretProviderError::InvalidSource { .. })
+                ),
+                "{src:?} should be invalid"
+            );
+        }
+    }
+}
@@ -31,6 +31,11 @@ use toml::Value;
 /// ```
 pub struct WKTomlConfigExtractor;
 
+/// The kinds of the secrets in `.wukong.toml`. A `generic` secret is compared as text, while
+/// the `dotenv`, `json` and `yaml` ones are compared key by key. A `template` is a local
+/// file rendered with the secrets it references, e.g. `{{ bunker:mv/tech/app/dev#DB_PASS }}`.
+const SUPPORTED_KINDS: [&str; 5] = ["generic", "dotenv", "json", "yaml", "template"];
+
 impl SecretExtractor for WKTomlConfigExtractor {
     fn extract(file: &Path) -> Result<Vec<SecretInfo>, ExtractError> {
         let toml_string = std::fs::read_to_string(file).expect("Failed to read toml file");
@@ -47,6 +52,13 @@ impl SecretExtractor for WKTomlConfigExtractor {
                         for (key, value) in secret_table.iter() {
                             let provider = match value.get("provider") {
                                 Some(v) => v.as_str().unwrap().to_string(),
+                                // the secrets of a template are referenced in it, by
+                                // their providers
+                                None if value.get("kind").and_then(Value::as_str)
+                                    == Some("template") =>
+                                {
+                                    "template".to_string()
+                                }
                                 None => {
                                     eprintln!(
                                         "⚠️  [wukong_toml] The {} not found under {} table. It will be ignored.",
@@ -90,7 +102,40 @@ impl SecretExtractor for WKTomlConfigExtractor {
                                 }
                             };
 
-                            if kind != "generic" {
+                            if !SUPPORTED_KINDS.contains(&kind.as_str()) {
+                                eprintln!(
+                                    "⚠️  [wukong_toml] Unknown kind {:?} under {} table, it should be one of {}. It will be ignored.",
+                                    kind,
+                                    key.cyan(),
+                                    SUPPORTED_KINDS.join(", ").cyan()
+                                );
+                                continue;
+                            }
+
+                            if kind == "template" {
+                                // Expect: src = TEMPLATE_PATH, relative to this file
+                                if [&source, &destination_file].iter().any(|path| {
+                                    path.is_empty()
+                                        || path.starts_with("~/")
+                                        || path.starts_with('/')
+                                }) {
+                                    eprintln!(
+                                        "⚠️  [wukong_toml] The template {} or its destination {} is not under the project directory. It will be ignored.",
+                                        source.cyan(),
+                                        destination_file.cyan()
+                                    );
+                                    continue;
+                                }
+
+                                extracted.push(SecretInfo {
+                                    key: key.to_string(),
+                                    provider: "template".to_string(),
+                                    kind,
+                                    src: source,
+                                    destination_file,
+                                    name: key.to_string(),
+                                    annotated_file: file.to_path_buf(),
+                                });
                                 continue;
                             }
 
@@ -153,6 +198,35 @@ impl SecretExtractor for WKTomlConfigExtractor {
                                     // (application, namespace, path) tuple.
                                     scheme_split[1].to_string()
                                 }
+                                "file" => {
+                                    // Expect: file:PATH#KEY, an encrypted file under the
+                                    // project directory
+                                    let path = match source.strip_prefix("file:") {
+                                        Some(path)
+                                            if !path.is_empty()
+                                                && !path.starts_with("~/")
+                                                && !path.starts_with('/') =>
+                                        {
+                                            path
+                                        }
+                                        _ => {
+                                            eprintln!(
+                                                "⚠️  [wukong_toml] The {} entry has provider = \"file\" but {} does not match {}, a path under the project directory. It will be ignored.",
+                                                key.cyan(),
+                                                "src".cyan(),
+                                                "file:PATH#KEY".cyan()
+                                            );
+                                            continue;
+                                        }
+                                    };
+                                    // Resolved from the `.wukong.toml`, so the same file is
+                                    // read once whichever directory it's annotated from.
+                                    file.parent()
+                                        .unwrap_or(Path::new(""))
+                                        .join(path)
+                                        .to_string_lossy()
+                                        .to_string()
+                                }
                                 _ => {
                                     eprintln!(
                                         "⚠️  [wukong_toml] Unknown provider {:?} under {} table. It will be ignored.",
@@ -294,4 +368,135 @@ dst = "bar.txt"
 
         dir.close().unwrap();
     }
+
+    #[test]
+    fn test_wk_toml_config_extractor_kinds() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let dev_config_path = dir.path().join(".wukong.toml");
+
+        let mut dev_config = File::create(&dev_config_path).unwrap();
+        writeln!(
+            dev_config,
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "bunker"
+kind = "dotenv"
+src = "vault:secret/wukong-cli/development#dotenv"
+dst = ".env"
+
+[secrets.settings]
+provider = "bunker"
+kind = "json"
+src = "vault:secret/wukong-cli/development#settings.json"
+dst = "settings.json"
+
+[secrets.values]
+provider = "bunker"
+kind = "yaml"
+src = "vault:secret/wukong-cli/development#values.yaml"
+dst = "values.yaml"
+
+[secrets.unknown]
+provider = "bunker"
+kind = "xml"
+src = "vault:secret/wukong-cli/development#config.xml"
+dst = "config.xml"
+            "#
+        )
+        .unwrap();
+
+        let secret_infos = WKTomlConfigExtractor::extract(&dev_config_path).unwrap();
+
+        assert_eq!(secret_infos.len(), 3);
+        assert_eq!(secret_infos[0].key, "dotenv");
+        assert_eq!(secret_infos[0].kind, "dotenv");
+        assert_eq!(secret_infos[1].key, "settings");
+        assert_eq!(secret_infos[1].kind, "json");
+        assert_eq!(secret_infos[2].key, "values");
+        assert_eq!(secret_infos[2].kind, "yaml");
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_wk_toml_config_extractor_file_provider() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let dev_config_path = dir.path().join(".wukong.toml");
+
+        let mut dev_config = File::create(&dev_config_path).unwrap();
+        writeln!(
+            dev_config,
+            r#"
+[[secrets]]
+
+[secrets.dotenv]
+provider = "file"
+kind = "dotenv"
+src = "file:secrets/development.json.age#dotenv"
+dst = ".env"
+
+[secrets.outside]
+provider = "file"
+kind = "generic"
+src = "file:/etc/secrets.json.age#foo"
+dst = "foo.txt"
+            "#
+        )
+        .unwrap();
+
+        let secret_infos = WKTomlConfigExtractor::extract(&dev_config_path).unwrap();
+
+        assert_eq!(secret_infos.len(), 1);
+        assert_eq!(secret_infos[0].key, "dotenv");
+        assert_eq!(secret_infos[0].name, "dotenv");
+        assert_eq!(
+            secret_infos[0].src,
+            dir.path()
+                .join("secrets/development.json.age")
+                .to_string_lossy()
+        );
+        assert_eq!(secret_infos[0].destination_file, ".env");
+        assert_eq!(secret_infos[0].provider, "file");
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_wk_toml_config_extractor_template_kind() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let dev_config_path = dir.path().join(".wukong.toml");
+
+        let mut dev_config = File::create(&dev_config_path).unwrap();
+        writeln!(
+            dev_config,
+            r#"
+[[secrets]]
+
+[secrets.dev_secrets]
+kind = "template"
+src = "config/dev.secret.exs.tmpl"
+dst = "config/dev.secret.exs"
+
+[secrets.outside]
+kind = "template"
+src = "/etc/dev.secret.exs.tmpl"
+dst = "config/other.secret.exs"
+            "#
+        )
+        .unwrap();
+
+        let secret_infos = WKTomlConfigExtractor::extract(&dev_config_path).unwrap();
+
+        assert_eq!(secret_infos.len(), 1);
+        assert_eq!(secret_infos[0].key, "dev_secrets");
+        assert_eq!(secret_infos[0].name, "dev_secrets");
+        assert_eq!(secret_infos[0].provider, "template");
+        assert_eq!(secret_infos[0].kind, "template");
+        assert_eq!(secret_infos[0].src, "config/dev.secret.exs.tmpl");
+        assert_eq!(secret_infos[0].destination_file, "config/dev.secret.exs");
+
+        dir.close().unwrap();
+    }
 }
@@ -0,0 +1,123 @@
+use crate::{
+    error::ExtractError,
+    utils::annotations::{read_yaml_vault_annotation, SECRETS_LOCATION_KEY},
+};
+
+use super::{SecretExtractor, SecretInfo};
+use std::path::Path;
+
+/// Extract secret annotations from YAML config file, the value of the key following the
+/// annotation is the destination file.
+///
+/// For example,
+/// ```yaml
+/// # at /a/b/c/config/default.yaml
+/// # wukong.mindvalley.dev/config-secrets-location: vault:secret/wukong-cli/development#secrets.yaml
+/// secrets_file: config/secrets.yaml
+/// ```
+///
+/// Extract to
+/// ```
+/// # use wukong_sdk::secret_extractors::SecretInfo;
+/// SecretInfo {
+///     key: "vault:secret/wukong-cli/development#secrets.yaml".to_string(),
+///     provider: "bunker".to_string(),
+///     kind: "yaml_config".to_string(),
+///     src: "wukong-cli/development".to_string(),
+///     destination_file: "config/secrets.yaml".to_string(),
+///     name: "secrets.yaml".to_string(),
+///     annotated_file: "/a/b/c/config/default.yaml".into()
+/// };
+/// ```
+pub struct YamlConfigExtractor;
+impl YamlConfigExtractor {
+    /// Only the YAML files with it are annotated.
+    pub const ANNOTATION: &'static str = SECRETS_LOCATION_KEY;
+
+    /// Same as [SecretExtractor::extract], from the `content` of the file already read.
+    pub fn extract_from(file: &Path, content: &str) -> Result<Vec<SecretInfo>, ExtractError> {
+        let annotations = read_yaml_vault_annotation(content);
+
+        let mut extracted = Vec::new();
+
+        for annotation in annotations {
+            if annotation.key == SECRETS_LOCATION_KEY
+                && annotation.source == "vault"
+                && annotation.engine == "secret"
+            {
+                extracted.push(SecretInfo {
+                    key: annotation.raw,
+                    provider: "bunker".to_string(),
+                    kind: "yaml_config".to_string(),
+                    src: annotation.secret_path.clone(),
+                    destination_file: annotation.destination_file.clone(),
+                    name: annotation.secret_name.clone(),
+                    annotated_file: file.to_path_buf(),
+                });
+            }
+        }
+
+        Ok(extracted)
+    }
+}
+
+impl SecretExtractor for YamlConfigExtractor {
+    fn extract(file: &Path) -> Result<Vec<SecretInfo>, ExtractError> {
+        let content = std::fs::read_to_string(file).unwrap();
+
+        Self::extract_from(file, &content)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+
+    #[test]
+    fn test_yaml_config_extractor() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let config_path = dir.path().join("default.yaml");
+
+        let mut config = File::create(&config_path).unwrap();
+        writeln!(
+            config,
+            r#"# Load development secrets
+            # wukong.mindvalley.dev/config-secrets-location: vault:secret/wukong-cli/sandboxes#secrets.yaml
+            secrets_file: secrets.yaml
+
+            env_files:
+              # wukong.mindvalley.dev/config-secrets-location: vault:secret/wukong-cli/sandboxes#dotenv
+              - "app/.env""#
+        ).unwrap();
+
+        let secret_infos = YamlConfigExtractor::extract(&config_path).unwrap();
+
+        assert_eq!(secret_infos.len(), 2);
+
+        assert_eq!(
+            secret_infos[0].key,
+            "vault:secret/wukong-cli/sandboxes#secrets.yaml"
+        );
+        assert_eq!(secret_infos[0].name, "secrets.yaml");
+        assert_eq!(secret_infos[0].src, "wukong-cli/sandboxes");
+        assert_eq!(secret_infos[0].destination_file, "secrets.yaml");
+        assert_eq!(secret_infos[0].provider, "bunker");
+        assert_eq!(secret_infos[0].kind, "yaml_config");
+        assert_eq!(secret_infos[0].annotated_file, config_path);
+
+        assert_eq!(
+            secret_infos[1].key,
+            "vault:secret/wukong-cli/sandboxes#dotenv"
+        );
+        assert_eq!(secret_infos[1].name, "dotenv");
+        assert_eq!(secret_infos[1].src, "wukong-cli/sandboxes");
+        assert_eq!(secret_infos[1].destination_file, "app/.env");
+        assert_eq!(secret_infos[1].provider, "bunker");
+        assert_eq!(secret_infos[1].kind, "yaml_config");
+        assert_eq!(secret_infos[1].annotated_file, config_path);
+
+        dir.close().unwrap();
+    }
+}
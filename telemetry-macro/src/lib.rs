@@ -2,7 +2,7 @@
 mod utils;
 
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{parse_macro_input, AttributeArgs, ItemFn};
 #[cfg(not(doctest))]
 use utils::attribute_args_ext::AttributeArgsExt;
@@ -15,6 +15,16 @@ pub fn wukong_telemetry(args: TokenStream, item: TokenStream) -> TokenStream {
     let command_event_value = args.get_value("command_event");
     let api_event_value = args.get_value("api_event");
 
+    // the recorded body is awaited, and so is the recording of the event
+    if item.sig.asyncness.is_none() {
+        return syn::Error::new_spanned(
+            &item.sig,
+            "Expected an `async fn`, the telemetry is recorded asynchronously.",
+        )
+        .to_compile_error()
+        .into();
+    }
+
     let fn_ident = item.sig.ident;
     let fn_inputs = item.sig.inputs;
     let fn_output = item.sig.output;
@@ -25,30 +35,63 @@ pub fn wukong_telemetry(args: TokenStream, item: TokenStream) -> TokenStream {
     let generated_func;
 
     if let Some(command_event) = command_event_value {
+        let fn_result_type = match &fn_output {
+            syn::ReturnType::Type(_, ty) if is_result(ty) => quote! { #ty },
+            syn::ReturnType::Type(_, ty) => return expected_result_error(ty),
+            syn::ReturnType::Default => return expected_result_error(&fn_ident),
+        };
+
         generated_func = quote! {
             #[allow(clippy::too_many_arguments)]
             #visibility #asyncness fn #fn_ident(#fn_inputs) #fn_output {
                 use clap::{crate_version};
 
+                // read before the body, which may move the context
                 let current_application = context.current_application.clone();
-                let current_sub = match context.sub {
-                    Some(sub) => sub,
-                    None => "unknown".to_string(),
-                };
-
-                TelemetryData::new(
-                    TelemetryEvent::Command {
-                        name: #command_event.to_string(),
-                        run_mode: CommandRunMode::NonInteractive,
-                    },
-                    Some(current_application),
-                    current_sub,
-                    crate_version!().to_string()
-                )
-                .record_event()
-                .await;
-
-                #fn_block
+                let current_sub = context.sub.clone().unwrap_or_else(|| "unknown".to_string());
+
+                // the opt-out is checked before touching the telemetry file
+                let telemetry_enabled = ::wukong_telemetry::is_enabled();
+                if telemetry_enabled {
+                    ::wukong_telemetry::start_command_span();
+                }
+
+                let now = std::time::Instant::now();
+
+                // the body runs in its own block, so the early returns and `?` are recorded too
+                let fn_result: #fn_result_type = async move #fn_block.await;
+
+                if telemetry_enabled {
+                    let (result, error) = match &fn_result {
+                        Ok(_) => (::wukong_telemetry::CommandResult::Success, None),
+                        Err(err) => (
+                            ::wukong_telemetry::CommandResult::Error,
+                            Some(<&'static str>::from(err).to_string()),
+                        ),
+                    };
+                    let run_mode = if ::wukong_telemetry::is_interactive() {
+                        CommandRunMode::Interactive
+                    } else {
+                        CommandRunMode::NonInteractive
+                    };
+
+                    TelemetryData::new(
+                        TelemetryEvent::Command {
+                            name: #command_event.to_string(),
+                            run_mode,
+                            duration: now.elapsed().as_millis() as u64,
+                            result: Some(result),
+                            error,
+                        },
+                        Some(current_application),
+                        current_sub,
+                        crate_version!().to_string()
+                    )
+                    .record_event()
+                    .await;
+                }
+
+                fn_result
             }
         };
     } else if let Some(api_event) = api_event_value {
@@ -85,43 +128,68 @@ pub fn wukong_telemetry(args: TokenStream, item: TokenStream) -> TokenStream {
 
                 let fn_result = #fn_block;
 
-                let telemetry_data = match fn_result {
-                    Ok(_) => {
-                        TelemetryData::new(
-                            TelemetryEvent::Api {
-                                name: #api_event.to_string(),
-                                duration: now.elapsed().as_millis() as u64,
-                                response: APIResponse::Success,
-                            },
-                            #current_application,
-                            current_sub,
-                            crate_version!().to_string()
-                        )
-                    },
-                    Err(_) => {
-                        TelemetryData::new(
-                            TelemetryEvent::Api {
-                                name: #api_event.to_string(),
-                                duration: now.elapsed().as_millis() as u64,
-                                response: APIResponse::Error,
-                            },
-                            #current_application,
-                            current_sub,
-                            crate_version!().to_string()
-                        )
-                    }
-                };
+                if ::wukong_telemetry::is_enabled() {
+                    let telemetry_data = match fn_result {
+                        Ok(_) => {
+                            TelemetryData::new(
+                                TelemetryEvent::Api {
+                                    name: #api_event.to_string(),
+                                    duration: now.elapsed().as_millis() as u64,
+                                    response: APIResponse::Success,
+                                },
+                                #current_application,
+                                current_sub,
+                                crate_version!().to_string()
+                            )
+                        },
+                        Err(_) => {
+                            TelemetryData::new(
+                                TelemetryEvent::Api {
+                                    name: #api_event.to_string(),
+                                    duration: now.elapsed().as_millis() as u64,
+                                    response: APIResponse::Error,
+                                },
+                                #current_application,
+                                current_sub,
+                                crate_version!().to_string()
+                            )
+                        }
+                    };
 
-                telemetry_data
-                .record_event()
-                .await;
+                    telemetry_data
+                    .record_event()
+                    .await;
+                }
 
                 fn_result.map_err(|err| err.into())
             }
         };
     } else {
-        panic!("Expected `command_event` or `api_event` key.");
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "Expected `command_event` or `api_event` key.",
+        )
+        .to_compile_error()
+        .into();
     }
 
     generated_func.into()
 }
+
+/// Whether the type is a `Result`, e.g. `Result<bool, WKCliError>`.
+fn is_result(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Result"),
+        _ => false,
+    }
+}
+
+fn expected_result_error(tokens: impl ToTokens) -> TokenStream {
+    syn::Error::new_spanned(tokens, "Expected the command to return a `Result`.")
+        .to_compile_error()
+        .into()
+}
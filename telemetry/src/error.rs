@@ -0,0 +1,13 @@
+use thiserror::Error as ThisError;
+
+#[derive(Debug, ThisError)]
+pub enum TelemetryError {
+    #[error(transparent)]
+    Io(#[from] ::std::io::Error),
+    #[error(transparent)]
+    ExportError(#[from] reqwest::Error),
+    #[error("The queued events are being sent by another wukong process.")]
+    FlushInProgress,
+    #[error("The events can't be sent by this build, they are kept queued.")]
+    ExporterNotConfigured,
+}
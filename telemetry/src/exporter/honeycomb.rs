@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+
+use super::{post_json, Exporter, ExporterConfig};
+use crate::{HoneycombEventData, TelemetryData};
+
+const HONEYCOMB_API_URL: &str = "https://api.honeycomb.io";
+
+#[cfg(feature = "prod")]
+const HONEYCOMB_API_KEY: &str = env!("WUKONG_HONEYCOMB_API_KEY");
+
+#[cfg(feature = "prod")]
+const HONEYCOMB_DATASET: &str = "wukong_telemetry_prod";
+
+#[cfg(not(feature = "prod"))]
+const HONEYCOMB_DATASET: &str = "wukong_telemetry_dev";
+
+/// Send the events to the batch API of Honeycomb.
+pub struct HoneycombExporter {
+    url: String,
+    headers: BTreeMap<String, String>,
+}
+
+impl HoneycombExporter {
+    /// The release builds send the events with the API key they are built with, unless
+    /// another one is configured.
+    pub fn new(config: ExporterConfig) -> Self {
+        let endpoint = config
+            .endpoint
+            .unwrap_or_else(|| HONEYCOMB_API_URL.to_string());
+
+        let mut headers = BTreeMap::new();
+        #[cfg(feature = "prod")]
+        headers.insert(
+            "X-Honeycomb-Team".to_string(),
+            HONEYCOMB_API_KEY.to_string(),
+        );
+        headers.extend(config.headers);
+
+        Self {
+            url: format!(
+                "{}/1/batch/{}",
+                endpoint.trim_end_matches('/'),
+                HONEYCOMB_DATASET
+            ),
+            headers,
+        }
+    }
+}
+
+#[async_trait]
+impl Exporter for HoneycombExporter {
+    async fn export(&self, events: &[TelemetryData]) -> Result<(), reqwest::Error> {
+        let event_data: Vec<HoneycombEventData> =
+            events.iter().cloned().map(|each| each.into()).collect();
+
+        post_json(&self.url, &self.headers, &event_data).await
+    }
+
+    /// Only the release builds have an API key, unless one is configured.
+    fn can_export(&self) -> bool {
+        self.headers
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("X-Honeycomb-Team"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{exporter::ExporterKind, CommandResult, CommandRunMode, TelemetryEvent};
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn test_export_to_honeycomb_batch_api() {
+        let server = MockServer::start();
+        let batch_api = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/1/batch/{HONEYCOMB_DATASET}"))
+                .header("X-Honeycomb-Team", "test-api-key")
+                .body_contains("\"cmd_name\":\"config list\"")
+                .body_contains("\"trace.span_id\"");
+            then.status(200).body("[{\"status\":202}]");
+        });
+
+        let exporter = HoneycombExporter::new(ExporterConfig {
+            kind: ExporterKind::Honeycomb,
+            endpoint: Some(server.base_url()),
+            headers: BTreeMap::from([("X-Honeycomb-Team".to_string(), "test-api-key".to_string())]),
+        });
+        let event = TelemetryData {
+            timestamp: "2024-05-06T07:08:09.010Z".to_string(),
+            actor: "user@mindvalley.com".to_string(),
+            application: None,
+            event: TelemetryEvent::Command {
+                name: "config list".to_string(),
+                run_mode: CommandRunMode::NonInteractive,
+                duration: 800,
+                result: Some(CommandResult::Success),
+                error: None,
+            },
+            version: "2.0.0".to_string(),
+            trace_id: Some("5b8efff798038103d269b633813fc60c".to_string()),
+            span_id: Some("eee19b7ec3c1b174".to_string()),
+            parent_span_id: None,
+        };
+
+        assert!(exporter.can_export());
+        assert!(exporter.export(&[event]).await.is_ok());
+        batch_api.assert();
+    }
+
+    #[cfg(not(feature = "prod"))]
+    #[test]
+    fn test_cannot_export_without_an_api_key() {
+        let exporter = HoneycombExporter::new(ExporterConfig::default());
+
+        assert!(!exporter.can_export());
+    }
+}
@@ -0,0 +1,94 @@
+//! Where the queued events are sent: Honeycomb by default, or any OpenTelemetry
+//! collector with [ExporterKind::Otlp].
+
+mod honeycomb;
+mod otlp;
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use reqwest::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
+
+use crate::TelemetryData;
+
+pub use self::{honeycomb::HoneycombExporter, otlp::OtlpExporter};
+
+#[async_trait]
+pub trait Exporter: Send + Sync {
+    /// Send a batch of events.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the error of the request, or the error status of the
+    /// response.
+    async fn export(&self, events: &[TelemetryData]) -> Result<(), reqwest::Error>;
+
+    /// Whether the events can be sent at all, e.g. the development builds have no
+    /// Honeycomb API key. The events are kept queued when they can't.
+    fn can_export(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExporterKind {
+    #[default]
+    Honeycomb,
+    Otlp,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExporterConfig {
+    pub kind: ExporterKind,
+    /// The base URL, the default of the exporter when it's `None`.
+    pub endpoint: Option<String>,
+    /// Sent with every request, e.g. the API key of the collector.
+    pub headers: BTreeMap<String, String>,
+}
+
+// set from the `telemetry` section of the CLI config when the CLI starts
+static EXPORTER_CONFIG: Lazy<Mutex<ExporterConfig>> =
+    Lazy::new(|| Mutex::new(ExporterConfig::default()));
+
+/// Use `config` for the events sent for the rest of the process.
+pub fn set_exporter_config(config: ExporterConfig) {
+    *EXPORTER_CONFIG
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = config;
+}
+
+/// The exporter of the config set with [set_exporter_config].
+pub fn exporter() -> Box<dyn Exporter> {
+    let config = EXPORTER_CONFIG
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+
+    match config.kind {
+        ExporterKind::Honeycomb => Box::new(HoneycombExporter::new(config)),
+        ExporterKind::Otlp => Box::new(OtlpExporter::new(config)),
+    }
+}
+
+async fn post_json<T: Serialize + ?Sized>(
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    body: &T,
+) -> Result<(), reqwest::Error> {
+    let client = reqwest::Client::builder().build()?;
+
+    let mut request = client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .json(body);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    request.send().await?.error_for_status()?;
+
+    Ok(())
+}
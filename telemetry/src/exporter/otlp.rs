@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use serde_json::{json, Value};
+
+use super::{post_json, Exporter, ExporterConfig};
+use crate::{
+    span::{new_span_id, new_trace_id},
+    APIResponse, CommandResult, CommandRunMode, TelemetryData, TelemetryEvent,
+};
+
+const OTLP_DEFAULT_ENDPOINT: &str = "http://localhost:4318";
+
+/// The standard OpenTelemetry variables, which take precedence over the config.
+const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+const OTLP_HEADERS_ENV: &str = "OTEL_EXPORTER_OTLP_HEADERS";
+
+const SERVICE_NAME: &str = "wukong-cli";
+const SCOPE_NAME: &str = "wukong-telemetry";
+
+// https://opentelemetry.io/docs/specs/otel/trace/api/#spankind
+const SPAN_KIND_INTERNAL: u8 = 1;
+const SPAN_KIND_CLIENT: u8 = 3;
+const STATUS_CODE_ERROR: u8 = 2;
+
+/// Send the events as spans to an OpenTelemetry collector, with the OTLP/HTTP JSON
+/// encoding.
+pub struct OtlpExporter {
+    url: String,
+    headers: BTreeMap<String, String>,
+}
+
+impl OtlpExporter {
+    pub fn new(config: ExporterConfig) -> Self {
+        let endpoint = std::env::var(OTLP_ENDPOINT_ENV)
+            .ok()
+            .or(config.endpoint)
+            .unwrap_or_else(|| OTLP_DEFAULT_ENDPOINT.to_string());
+
+        let mut headers = config.headers;
+        if let Ok(env_headers) = std::env::var(OTLP_HEADERS_ENV) {
+            headers.extend(parse_headers(&env_headers));
+        }
+
+        Self {
+            url: format!("{}/v1/traces", endpoint.trim_end_matches('/')),
+            headers,
+        }
+    }
+}
+
+#[async_trait]
+impl Exporter for OtlpExporter {
+    async fn export(&self, events: &[TelemetryData]) -> Result<(), reqwest::Error> {
+        post_json(&self.url, &self.headers, &trace_request(events)).await
+    }
+}
+
+/// Parse the `key1=value1,key2=value2` format of `OTEL_EXPORTER_OTLP_HEADERS`.
+fn parse_headers(value: &str) -> BTreeMap<String, String> {
+    value
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+/// The body of `POST /v1/traces`, an `ExportTraceServiceRequest` in JSON.
+fn trace_request(events: &[TelemetryData]) -> Value {
+    let spans: Vec<Value> = events.iter().map(span).collect();
+
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [string_attribute("service.name", SERVICE_NAME)]
+            },
+            "scopeSpans": [{
+                "scope": {
+                    "name": SCOPE_NAME,
+                    "version": env!("CARGO_PKG_VERSION")
+                },
+                "spans": spans
+            }]
+        }]
+    })
+}
+
+fn span(data: &TelemetryData) -> Value {
+    // the event is recorded when it ends, so the timestamp is the end of the span
+    let end_time = DateTime::parse_from_rfc3339(&data.timestamp)
+        .ok()
+        .and_then(|time| time.timestamp_nanos_opt())
+        .unwrap_or_default()
+        .max(0) as u64;
+
+    let mut attributes = vec![
+        string_attribute("wukong.actor", &data.actor),
+        string_attribute("wukong.version", &data.version),
+    ];
+    if let Some(application) = &data.application {
+        attributes.push(string_attribute("wukong.application", application));
+    }
+
+    let (name, kind, duration_ms, is_error) = match &data.event {
+        TelemetryEvent::Command {
+            name,
+            run_mode,
+            duration,
+            result,
+            error,
+        } => {
+            let run_mode = match run_mode {
+                CommandRunMode::Interactive => "interactive",
+                CommandRunMode::NonInteractive => "non-interactive",
+            };
+            attributes.push(string_attribute("wukong.command.run_mode", run_mode));
+
+            let is_error = matches!(result, Some(CommandResult::Error));
+            match result {
+                Some(CommandResult::Success) => {
+                    attributes.push(string_attribute("wukong.command.result", "success"))
+                }
+                Some(CommandResult::Error) => {
+                    attributes.push(string_attribute("wukong.command.result", "error"))
+                }
+                None => {}
+            }
+            if let Some(error) = error {
+                attributes.push(string_attribute("wukong.command.error", error));
+            }
+
+            (name, SPAN_KIND_INTERNAL, *duration, is_error)
+        }
+        TelemetryEvent::Api {
+            name,
+            duration,
+            response,
+        } => {
+            let is_error = matches!(response, APIResponse::Error);
+            attributes.push(string_attribute(
+                "wukong.api.response",
+                if is_error { "error" } else { "success" },
+            ));
+
+            (name, SPAN_KIND_CLIENT, *duration, is_error)
+        }
+    };
+
+    let mut span = json!({
+        // the queued events of the older versions have no IDs
+        "traceId": data.trace_id.clone().unwrap_or_else(new_trace_id),
+        "spanId": data.span_id.clone().unwrap_or_else(new_span_id),
+        "name": name,
+        "kind": kind,
+        "startTimeUnixNano": end_time
+            .saturating_sub(duration_ms * 1_000_000)
+            .to_string(),
+        "endTimeUnixNano": end_time.to_string(),
+        "attributes": attributes,
+    });
+    if let Some(parent_span_id) = &data.parent_span_id {
+        span["parentSpanId"] = json!(parent_span_id);
+    }
+    if is_error {
+        span["attributes"]
+            .as_array_mut()
+            .expect("attributes is an array")
+            .push(json!({ "key": "error", "value": { "boolValue": true } }));
+        span["status"] = json!({ "code": STATUS_CODE_ERROR });
+    }
+
+    span
+}
+
+fn string_attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::exporter::ExporterKind;
+    use httpmock::prelude::*;
+
+    // built by hand, as `TelemetryData::new` changes the span of the current command
+    fn event(event: TelemetryEvent, parent: Option<&TelemetryData>) -> TelemetryData {
+        TelemetryData {
+            timestamp: "2024-05-06T07:08:09.010Z".to_string(),
+            actor: "user@mindvalley.com".to_string(),
+            application: Some("valid-application".to_string()),
+            event,
+            version: "2.0.0".to_string(),
+            trace_id: Some(
+                parent
+                    .and_then(|parent| parent.trace_id.clone())
+                    .unwrap_or_else(new_trace_id),
+            ),
+            span_id: Some(new_span_id()),
+            parent_span_id: parent.and_then(|parent| parent.span_id.clone()),
+        }
+    }
+
+    fn command_event() -> TelemetryData {
+        event(
+            TelemetryEvent::Command {
+                name: "deployment list".to_string(),
+                run_mode: CommandRunMode::NonInteractive,
+                duration: 1500,
+                result: Some(CommandResult::Error),
+                error: Some("APIError".to_string()),
+            },
+            None,
+        )
+    }
+
+    fn api_event(command: &TelemetryData, response: APIResponse) -> TelemetryData {
+        event(
+            TelemetryEvent::Api {
+                name: "fetch_cd_pipelines".to_string(),
+                duration: 250,
+                response,
+            },
+            Some(command),
+        )
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let headers = parse_headers("x-api-key=secret, x-tenant = wukong,invalid");
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["x-api-key"], "secret");
+        assert_eq!(headers["x-tenant"], "wukong");
+    }
+
+    #[test]
+    fn test_api_spans_are_children_of_the_command_span() {
+        let command = command_event();
+        let api_success = api_event(&command, APIResponse::Success);
+        let api_error = api_event(&command, APIResponse::Error);
+
+        let request = trace_request(&[command, api_success, api_error]);
+        let spans = request["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .unwrap();
+
+        assert_eq!(spans.len(), 3);
+        let command_span = &spans[0];
+        assert_eq!(command_span["name"], "deployment list");
+        assert_eq!(command_span["kind"], SPAN_KIND_INTERNAL);
+        assert!(command_span.get("parentSpanId").is_none());
+        assert_eq!(command_span["status"]["code"], STATUS_CODE_ERROR);
+        assert!(command_span["attributes"]
+            .as_array()
+            .unwrap()
+            .contains(&string_attribute("wukong.command.error", "APIError")));
+
+        for api_span in &spans[1..] {
+            assert_eq!(api_span["name"], "fetch_cd_pipelines");
+            assert_eq!(api_span["kind"], SPAN_KIND_CLIENT);
+            assert_eq!(api_span["traceId"], command_span["traceId"]);
+            assert_eq!(api_span["parentSpanId"], command_span["spanId"]);
+
+            let start: u64 = api_span["startTimeUnixNano"]
+                .as_str()
+                .unwrap()
+                .parse()
+                .unwrap();
+            let end: u64 = api_span["endTimeUnixNano"]
+                .as_str()
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert_eq!(end - start, 250_000_000);
+        }
+
+        assert!(spans[1].get("status").is_none());
+        assert_eq!(spans[2]["status"]["code"], STATUS_CODE_ERROR);
+        assert!(spans[2]["attributes"]
+            .as_array()
+            .unwrap()
+            .contains(&json!({ "key": "error", "value": { "boolValue": true } })));
+    }
+
+    #[tokio::test]
+    async fn test_export_to_otlp_collector() {
+        let server = MockServer::start();
+        let collector = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/traces")
+                .header("content-type", "application/json")
+                .header("x-api-key", "secret")
+                .body_contains("\"service.name\"")
+                .body_contains("\"fetch_cd_pipelines\"");
+            then.status(200).body("{}");
+        });
+
+        let exporter = OtlpExporter::new(ExporterConfig {
+            kind: ExporterKind::Otlp,
+            endpoint: Some(server.base_url()),
+            headers: BTreeMap::from([("x-api-key".to_string(), "secret".to_string())]),
+        });
+        let command = command_event();
+        let api = api_event(&command, APIResponse::Success);
+        let result = exporter.export(&[command, api]).await;
+
+        assert!(result.is_ok());
+        collector.assert();
+    }
+
+    #[tokio::test]
+    async fn test_export_returns_the_collector_error() {
+        let server = MockServer::start();
+        let collector = server.mock(|when, then| {
+            when.method(POST).path("/v1/traces");
+            then.status(503);
+        });
+
+        let exporter = OtlpExporter::new(ExporterConfig {
+            kind: ExporterKind::Otlp,
+            endpoint: Some(format!("{}/", server.base_url())),
+            headers: BTreeMap::new(),
+        });
+        let result = exporter.export(&[command_event()]).await;
+
+        assert!(result.is_err());
+        collector.assert();
+    }
+}
@@ -1,45 +1,96 @@
+mod error;
+mod exporter;
+mod queue;
+mod span;
+
 use std::{
-    fs::{create_dir_all, File},
-    io::Write,
-    path::Path,
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 use chrono::{offset::Utc, SecondsFormat};
 
-#[cfg(feature = "prod")]
 use once_cell::sync::Lazy;
-#[cfg(feature = "prod")]
-use reqwest::header;
 
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "prod")]
-const EVENT_THRESHOLD: usize = 20;
+pub use error::TelemetryError;
+pub use exporter::{
+    exporter, set_exporter_config, Exporter, ExporterConfig, ExporterKind, HoneycombExporter,
+    OtlpExporter,
+};
+pub use queue::Telemetry;
 
+/// The size of the queue, in bytes, from which the events are sent, about 20 events.
 #[cfg(feature = "prod")]
-const HONEYCOMB_API_KEY: &str = env!("WUKONG_HONEYCOMB_API_KEY");
+const FLUSH_THRESHOLD: u64 = 8 * 1024;
 
-#[cfg(feature = "prod")]
-const HONEYCOMB_DATASET: &str = "wukong_telemetry_prod";
+/// The environment variable to opt out of the telemetry, with `WUKONG_TELEMETRY=off`,
+/// whatever the config says.
+pub const TELEMETRY_ENV: &str = "WUKONG_TELEMETRY";
 
-/// The default path to the wukong telemetry file.
+// set from the `telemetry` section of the CLI config when the CLI starts
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// The default path to the wukong telemetry file, the queue of the events not sent yet.
 ///
 /// This is a [Lazy] of `Option<String>`, the value of which is
 ///
-/// > `~/.config/wukong/telemetry.yml`
+/// > `~/.config/wukong/telemetry.json`
 ///
 /// It will only be `None` if it is unable to identify the user's home
 /// directory, which should not happen under typical OS environments.
 ///
 /// [Lazy]: https://docs.rs/once_cell/latest/once_cell/sync/struct.Lazy.html
-#[cfg(feature = "prod")]
 pub static TELEMETRY_FILE: Lazy<Option<String>> = Lazy::new(|| {
-    dirs::home_dir().map(|mut path| {
+    #[cfg(feature = "prod")]
+    return dirs::home_dir().map(|mut path| {
         path.extend([".config", "wukong", "telemetry.json"]);
         path.to_str().unwrap().to_string()
-    })
+    });
+
+    #[cfg(not(feature = "prod"))]
+    {
+        match std::env::var("WUKONG_DEV_TELEMETRY_FILE") {
+            Ok(telemetry_file) => Some(telemetry_file),
+            Err(_) => dirs::home_dir().map(|mut path| {
+                path.extend([".config", "wukong", "dev", "telemetry.json"]);
+                path.to_str().unwrap().to_string()
+            }),
+        }
+    }
 });
 
+/// Enable or disable the telemetry for the rest of the process.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether the events are recorded, i.e. it's not disabled with [TELEMETRY_ENV] nor
+/// with [set_enabled].
+pub fn is_enabled() -> bool {
+    !is_disabled_by_env() && ENABLED.load(Ordering::Relaxed)
+}
+
+/// Whether [TELEMETRY_ENV] is set to `off` (or `false`, `0`).
+pub fn is_disabled_by_env() -> bool {
+    std::env::var(TELEMETRY_ENV)
+        .map(|value| matches!(value.to_lowercase().as_str(), "off" | "false" | "0"))
+        .unwrap_or(false)
+}
+
+/// Whether the command is run by someone at a terminal, who can answer the prompts,
+/// rather than by a script or a CI pipeline.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+/// Start the span of the command being run, so the API events recorded while it runs
+/// are linked to its event, which is only recorded when it ends.
+pub fn start_command_span() {
+    span::start_command_span();
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TelemetryData {
     timestamp: String,
@@ -48,6 +99,25 @@ pub struct TelemetryData {
     #[serde(flatten)]
     event: TelemetryEvent,
     version: String,
+    // the field names of Honeycomb's tracing, the spans are linked by the exporters
+    #[serde(
+        rename = "trace.trace_id",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    trace_id: Option<String>,
+    #[serde(
+        rename = "trace.span_id",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    span_id: Option<String>,
+    #[serde(
+        rename = "trace.parent_id",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    parent_span_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -58,6 +128,18 @@ pub enum TelemetryEvent {
         name: String,
         #[serde(rename = "cmd_run_mode")]
         run_mode: CommandRunMode,
+        // the fields below are not in the events queued by the older versions
+        #[serde(rename = "cmd_duration", default)]
+        duration: u64,
+        #[serde(
+            rename = "cmd_result",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        result: Option<CommandResult>,
+        /// The name of the error variant, e.g. `ApplicationConfigNotFound`.
+        #[serde(rename = "cmd_error", default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
     },
     Api {
         #[serde(rename = "api_name")]
@@ -91,61 +173,17 @@ pub enum CommandRunMode {
     NonInteractive,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum APIResponse {
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CommandResult {
     Success,
     Error,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Telemetry {
-    data: Vec<TelemetryData>,
-}
-
-impl Telemetry {
-    /// Load telemetry data from file.
-    ///
-    /// # Errors
-    ///
-    /// This function may return typical file I/O errors.
-    #[allow(dead_code)]
-    pub fn load(path: &'static str) -> Self {
-        let telemetry_file_path = Path::new(path);
-
-        let telemetry_data = {
-            let content = std::fs::read_to_string(telemetry_file_path);
-            match content {
-                Ok(data) => serde_json::from_str::<Vec<TelemetryData>>(&data).unwrap_or_default(),
-                Err(_) => Vec::new(),
-            }
-        };
-
-        Telemetry {
-            data: telemetry_data,
-        }
-    }
-
-    /// Save telemetry data to file.
-    ///
-    /// If the file's directory does not exist, it will be created. If the file
-    /// already exists, it will be overwritten.
-    ///
-    /// # Errors
-    ///
-    /// This function may return typical file I/O errors.
-    #[allow(dead_code)]
-    pub fn save(&self, path: &str) {
-        let telemetry_file_path = Path::new(path);
-
-        if let Some(outdir) = telemetry_file_path.parent() {
-            create_dir_all(outdir).unwrap();
-        }
-
-        if let Ok(mut file) = File::create(path) {
-            file.write_all(serde_json::to_string_pretty(&self.data).unwrap().as_bytes())
-                .unwrap();
-        }
-    }
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum APIResponse {
+    Success,
+    Error,
 }
 
 impl TelemetryData {
@@ -155,53 +193,139 @@ impl TelemetryData {
         actor: String,
         version: String,
     ) -> Self {
+        // a command starts a trace, and the API calls it makes are the children of it
+        let (trace_id, span_id, parent_span_id) = match &event {
+            TelemetryEvent::Command { .. } => {
+                let span = span::command_span().unwrap_or_else(span::start_command_span);
+                (span.trace_id, span.span_id, None)
+            }
+            TelemetryEvent::Api { .. } => match span::command_span() {
+                Some(parent) => (parent.trace_id, span::new_span_id(), Some(parent.span_id)),
+                None => (span::new_trace_id(), span::new_span_id(), None),
+            },
+        };
+
         Self {
             timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
             actor,
             application,
             event,
             version,
+            trace_id: Some(trace_id),
+            span_id: Some(span_id),
+            parent_span_id,
         }
     }
 
     pub async fn record_event(&self) {
+        // checked first, so nothing is read or written when it's disabled
+        if !is_enabled() {
+            return;
+        }
+
         #[cfg(feature = "prod")]
         {
             let telemetry_file = TELEMETRY_FILE
                 .as_ref()
                 .expect("Unable to identify user's home directory");
 
-            let mut telemetry = Telemetry::load(telemetry_file);
-
-            telemetry.data.push(self.clone());
-
-            // if telemetry_data is more than the EVENT_THRESHOLD, then send the events in batch to honeycomb
-            if telemetry.data.len() >= EVENT_THRESHOLD {
-                let event_data: Vec<HoneycombEventData> =
-                    telemetry.data.into_iter().map(|each| each.into()).collect();
-
-                send_event(event_data).await;
-
-                telemetry.data = Vec::new();
-                telemetry.save(telemetry_file);
-            } else {
-                telemetry.save(telemetry_file);
+            // if the queue reaches the FLUSH_THRESHOLD, then send the events in batch, without
+            // making the command wait for it
+            if let Ok(size) = Telemetry::new(telemetry_file).append(self) {
+                if size >= FLUSH_THRESHOLD {
+                    let _ = spawn_flush();
+                }
             }
         }
     }
 }
 
+/// Run `wukong telemetry flush` in a detached process, which keeps running after the
+/// command ends.
 #[cfg(feature = "prod")]
-async fn send_event(event_data: Vec<HoneycombEventData>) {
-    if let Ok(client) = reqwest::Client::builder().build() {
-        let url = format!("https://api.honeycomb.io/1/batch/{}", HONEYCOMB_DATASET);
-
-        let _ = client
-            .post(url)
-            .header("X-Honeycomb-Team", HONEYCOMB_API_KEY)
-            .header(header::CONTENT_TYPE, "application/json")
-            .json(&event_data)
-            .send()
-            .await;
+fn spawn_flush() -> std::io::Result<()> {
+    use std::process::{Command, Stdio};
+
+    let flush_process = Command::new(std::env::current_exe()?)
+        .args(["telemetry", "flush"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // it's not waited for, the events stay queued if it fails
+    drop(flush_process);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_api_event_is_a_child_of_the_command_span() {
+        let command = TelemetryData::new(
+            TelemetryEvent::Command {
+                name: "pipeline list".to_string(),
+                run_mode: CommandRunMode::NonInteractive,
+                duration: 1200,
+                result: Some(CommandResult::Error),
+                error: Some("APIError".to_string()),
+            },
+            None,
+            "user@mindvalley.com".to_string(),
+            "2.0.0".to_string(),
+        );
+        let api = TelemetryData::new(
+            TelemetryEvent::Api {
+                name: "fetch_pipeline_list".to_string(),
+                duration: 100,
+                response: APIResponse::Success,
+            },
+            None,
+            "user@mindvalley.com".to_string(),
+            "2.0.0".to_string(),
+        );
+
+        assert!(command.parent_span_id.is_none());
+        assert_eq!(api.trace_id, command.trace_id);
+        assert_eq!(api.parent_span_id, command.span_id);
+        assert_ne!(api.span_id, command.span_id);
+
+        let json = serde_json::to_value(&api).unwrap();
+        assert_eq!(json["trace.parent_id"], command.span_id.clone().unwrap());
+
+        let json = serde_json::to_value(&command).unwrap();
+        assert_eq!(json["cmd_duration"], 1200);
+        assert_eq!(json["cmd_result"], "error");
+        assert_eq!(json["cmd_error"], "APIError");
+    }
+
+    #[test]
+    fn test_events_without_trace_fields_are_loaded() {
+        let data: TelemetryData = serde_json::from_str(
+            r#"{
+                "timestamp": "2024-05-06T07:08:09.010Z",
+                "actor": "user@mindvalley.com",
+                "application": null,
+                "event": "command",
+                "cmd_name": "pipeline list",
+                "cmd_run_mode": "non-interactive",
+                "version": "1.0.0"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(data.trace_id.is_none());
+        assert!(data.span_id.is_none());
+        assert!(matches!(
+            data.event,
+            TelemetryEvent::Command {
+                duration: 0,
+                result: None,
+                error: None,
+                ..
+            }
+        ));
     }
 }
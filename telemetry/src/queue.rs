@@ -0,0 +1,563 @@
+//! The queue of the events not sent yet, shared by every running wukong process.
+//!
+//! It's a log of one JSON event per line. The events are appended while holding an
+//! exclusive lock on the file, and it's only rewritten to drop the oldest events when
+//! it's full, or the ones which have been sent.
+
+use std::{
+    fs::{create_dir_all, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use fs2::FileExt;
+
+use crate::{error::TelemetryError, exporter, Exporter, TelemetryData};
+
+/// The queue never grows over this, the oldest events are dropped first.
+const MAX_QUEUE_SIZE: u64 = 512 * 1024;
+
+const FLUSH_ATTEMPTS: u32 = 3;
+const FLUSH_BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct Telemetry {
+    path: PathBuf,
+    max_size: u64,
+}
+
+impl Telemetry {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            max_size: MAX_QUEUE_SIZE,
+        }
+    }
+
+    /// The queued events, which are not sent yet.
+    ///
+    /// # Errors
+    ///
+    /// This function may return typical file I/O errors.
+    pub fn events(&self) -> Result<Vec<TelemetryData>, TelemetryError> {
+        let mut file = self.lock()?;
+        let lines = read_lines(&mut file)?;
+
+        Ok(parse_events(&lines))
+    }
+
+    /// Append the event to the queue, and return the size of the queue in bytes.
+    ///
+    /// The queue is only read and rewritten when it's over its maximum size, to drop the
+    /// oldest events, or when it's still in the format of the older versions.
+    ///
+    /// If the file's directory does not exist, it will be created.
+    ///
+    /// # Errors
+    ///
+    /// This function may return typical file I/O errors.
+    pub fn append(&self, event: &TelemetryData) -> Result<u64, TelemetryError> {
+        let line = serde_json::to_string(event).expect("The telemetry events are serializable");
+
+        let mut file = self.lock()?;
+        if is_legacy(&mut file)? {
+            read_lines(&mut file)?;
+        }
+
+        // a line cut by a crash is ended first, so that it doesn't swallow the new one
+        if !ends_with_newline(&mut file)? {
+            writeln!(file)?;
+        }
+        // opened to append, so it's written at the end whatever the position
+        writeln!(file, "{line}")?;
+
+        let size = file.metadata()?.len();
+        if size <= self.max_size {
+            return Ok(size);
+        }
+
+        // drop the oldest events, but never the new one
+        let mut lines = read_lines(&mut file)?;
+        let mut size: u64 = lines.iter().map(|line| line.len() as u64 + 1).sum();
+        let mut dropped = 0;
+        while size > self.max_size && dropped < lines.len() - 1 {
+            size -= lines[dropped].len() as u64 + 1;
+            dropped += 1;
+        }
+        lines.drain(..dropped);
+        write_lines(&mut file, &lines)?;
+
+        Ok(size)
+    }
+
+    /// Drop the queued events without sending them, and return how many were dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function may return typical file I/O errors.
+    pub fn clear(&self) -> Result<usize, TelemetryError> {
+        let mut file = self.lock()?;
+        let count = parse_events(&read_lines(&mut file)?).len();
+        write_lines(&mut file, &[])?;
+
+        Ok(count)
+    }
+
+    /// Send the queued events with the configured [exporter], retrying with a backoff,
+    /// and return the number of events sent.
+    ///
+    /// The events are kept queued if they can't be sent, and the events queued while
+    /// sending are kept for the next flush.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the error of the last attempt to send the events,
+    /// [TelemetryError::FlushInProgress] if another process is already sending them, or
+    /// [TelemetryError::ExporterNotConfigured] if the exporter can't send them at all.
+    pub async fn flush(&self) -> Result<usize, TelemetryError> {
+        self.flush_with(exporter().as_ref(), FLUSH_BACKOFF).await
+    }
+
+    async fn flush_with(
+        &self,
+        exporter: &dyn Exporter,
+        backoff: Duration,
+    ) -> Result<usize, TelemetryError> {
+        if !exporter.can_export() {
+            return Err(TelemetryError::ExporterNotConfigured);
+        }
+
+        // held until the end, so the same events are not sent twice
+        let _flush_lock = self.try_lock_flush()?;
+
+        let sent_lines = {
+            let mut file = self.lock()?;
+            read_lines(&mut file)?
+        };
+        let events = parse_events(&sent_lines);
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        export_with_retry(exporter, &events, backoff).await?;
+
+        let mut file = self.lock()?;
+        let mut lines = read_lines(&mut file)?;
+        for sent_line in &sent_lines {
+            if let Some(index) = lines.iter().position(|line| line == sent_line) {
+                lines.remove(index);
+            }
+        }
+        write_lines(&mut file, &lines)?;
+
+        Ok(events.len())
+    }
+
+    /// Open the queue file, waiting for the other processes to release it.
+    fn lock(&self) -> Result<File, TelemetryError> {
+        let file = self.open(&self.path)?;
+        file.lock_exclusive()?;
+
+        Ok(file)
+    }
+
+    fn try_lock_flush(&self) -> Result<File, TelemetryError> {
+        let mut lock_path = self.path.clone().into_os_string();
+        lock_path.push(".lock");
+
+        let file = self.open(Path::new(&lock_path))?;
+        file.try_lock_exclusive().map_err(|err| {
+            if err.kind() == fs2::lock_contended_error().kind() {
+                TelemetryError::FlushInProgress
+            } else {
+                err.into()
+            }
+        })?;
+
+        Ok(file)
+    }
+
+    fn open(&self, path: &Path) -> Result<File, TelemetryError> {
+        if let Some(outdir) = path.parent() {
+            create_dir_all(outdir)?;
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        Ok(file)
+    }
+}
+
+/// Whether the locked queue file is a JSON array, as saved by the older versions.
+fn is_legacy(file: &mut File) -> Result<bool, TelemetryError> {
+    let mut first = [0u8; 1];
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok(file.read(&mut first)? == 1 && first[0] == b'[')
+}
+
+/// Whether the locked queue file is empty or its last line is ended.
+fn ends_with_newline(file: &mut File) -> Result<bool, TelemetryError> {
+    if file.metadata()?.len() == 0 {
+        return Ok(true);
+    }
+
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+
+    Ok(last[0] == b'\n')
+}
+
+/// Read the lines of the locked queue file.
+///
+/// The older versions saved the queue as a JSON array, which is converted to lines.
+fn read_lines(file: &mut File) -> Result<Vec<String>, TelemetryError> {
+    let mut content = String::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_string(&mut content)?;
+
+    if content.trim_start().starts_with('[') {
+        let lines: Vec<String> = serde_json::from_str::<Vec<TelemetryData>>(&content)
+            .unwrap_or_default()
+            .iter()
+            .map(|event| {
+                serde_json::to_string(event).expect("The telemetry events are serializable")
+            })
+            .collect();
+        write_lines(file, &lines)?;
+
+        return Ok(lines);
+    }
+
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect())
+}
+
+fn write_lines(file: &mut File, lines: &[String]) -> Result<(), TelemetryError> {
+    // the file is opened to append, so the lines are written from the start
+    file.set_len(0)?;
+    for line in lines {
+        writeln!(file, "{line}")?;
+    }
+
+    Ok(())
+}
+
+// a line cut by a crash of the process writing it is skipped
+fn parse_events(lines: &[String]) -> Vec<TelemetryData> {
+    lines
+        .iter()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+async fn export_with_retry(
+    exporter: &dyn Exporter,
+    events: &[TelemetryData],
+    backoff: Duration,
+) -> Result<(), TelemetryError> {
+    let mut backoff = backoff;
+    let mut attempt = 1;
+
+    loop {
+        match exporter.export(events).await {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt >= FLUSH_ATTEMPTS => return Err(err.into()),
+            Err(_) => {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CommandRunMode, TelemetryEvent};
+    use async_trait::async_trait;
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    fn command_event(name: &str) -> TelemetryData {
+        TelemetryData {
+            timestamp: "2024-05-06T07:08:09.010Z".to_string(),
+            actor: "user@mindvalley.com".to_string(),
+            application: None,
+            event: TelemetryEvent::Command {
+                name: name.to_string(),
+                run_mode: CommandRunMode::NonInteractive,
+                duration: 0,
+                result: None,
+                error: None,
+            },
+            version: "2.0.0".to_string(),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+        }
+    }
+
+    fn command_name(event: &TelemetryData) -> &str {
+        match &event.event {
+            TelemetryEvent::Command { name, .. } => name,
+            TelemetryEvent::Api { name, .. } => name,
+        }
+    }
+
+    /// Fail the first `failures` exports, and queue an event while exporting.
+    struct FakeExporter {
+        failures: u32,
+        attempts: AtomicU32,
+        queue: Telemetry,
+    }
+
+    #[async_trait]
+    impl Exporter for FakeExporter {
+        async fn export(&self, _events: &[TelemetryData]) -> Result<(), reqwest::Error> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            self.queue
+                .append(&command_event(&format!("queued while sending {attempt}")))
+                .unwrap();
+
+            if attempt <= self.failures {
+                // any `reqwest::Error` will do
+                return Err(reqwest::Client::new().get("not a url").build().unwrap_err());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = Arc::new(temp.path().join("telemetry.json"));
+
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    // every writer has its own handle of the file, like another process
+                    let queue = Telemetry::new(path.as_path());
+                    for event in 0..25 {
+                        queue
+                            .append(&command_event(&format!("writer {writer} event {event}")))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let events = Telemetry::new(path.as_path()).events().unwrap();
+        assert_eq!(events.len(), 200);
+
+        let mut names: Vec<&str> = events.iter().map(command_name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), 200);
+
+        // the events of each writer are in order
+        let writer_0: Vec<&str> = events
+            .iter()
+            .map(command_name)
+            .filter(|name| name.starts_with("writer 0 "))
+            .collect();
+        let expected: Vec<String> = (0..25)
+            .map(|event| format!("writer 0 event {event}"))
+            .collect();
+        assert_eq!(writer_0, expected);
+    }
+
+    #[test]
+    fn test_the_oldest_events_are_dropped_when_full() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let queue = Telemetry {
+            path: temp.path().join("telemetry.json"),
+            max_size: 2048,
+        };
+
+        for event in 0..50 {
+            queue
+                .append(&command_event(&format!("event {event}")))
+                .unwrap();
+        }
+
+        assert!(std::fs::metadata(&queue.path).unwrap().len() <= 2048);
+        let events = queue.events().unwrap();
+        assert!(events.len() < 50);
+        assert_eq!(command_name(events.last().unwrap()), "event 49");
+        assert_eq!(
+            command_name(&events[0]),
+            format!("event {}", 50 - events.len())
+        );
+    }
+
+    #[test]
+    fn test_append_after_a_cut_line() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let queue = Telemetry::new(temp.path().join("telemetry.json"));
+
+        queue.append(&command_event("deployment list")).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&queue.path)
+            .unwrap()
+            .write_all(b"{\"event\":")
+            .unwrap();
+        let size = queue.append(&command_event("config list")).unwrap();
+
+        assert_eq!(size, std::fs::metadata(&queue.path).unwrap().len());
+        let events = queue.events().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(command_name(&events[1]), "config list");
+    }
+
+    #[test]
+    fn test_read_the_queue_of_the_older_versions() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("telemetry.json");
+        let legacy = vec![
+            command_event("deployment list"),
+            command_event("config list"),
+        ];
+        std::fs::write(&path, serde_json::to_string_pretty(&legacy).unwrap()).unwrap();
+
+        let queue = Telemetry::new(&path);
+        queue.append(&command_event("pipeline list")).unwrap();
+
+        let names: Vec<String> = queue
+            .events()
+            .unwrap()
+            .iter()
+            .map(|event| command_name(event).to_string())
+            .collect();
+        assert_eq!(names, ["deployment list", "config list", "pipeline list"]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_skip_broken_lines() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("telemetry.json");
+        let line = serde_json::to_string(&command_event("deployment list")).unwrap();
+        std::fs::write(&path, format!("{line}\n{{\"timestamp\": \"2024-")).unwrap();
+
+        assert_eq!(Telemetry::new(&path).events().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_flush_retries_and_keeps_the_events_queued_meanwhile() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let queue = Telemetry::new(temp.path().join("telemetry.json"));
+        queue.append(&command_event("deployment list")).unwrap();
+        queue.append(&command_event("config list")).unwrap();
+
+        let exporter = FakeExporter {
+            failures: 2,
+            attempts: AtomicU32::new(0),
+            queue: queue.clone(),
+        };
+        let sent = queue
+            .flush_with(&exporter, Duration::from_millis(1))
+            .await
+            .unwrap();
+
+        assert_eq!(sent, 2);
+        assert_eq!(exporter.attempts.load(Ordering::SeqCst), 3);
+        let names: Vec<String> = queue
+            .events()
+            .unwrap()
+            .iter()
+            .map(|event| command_name(event).to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "queued while sending 1",
+                "queued while sending 2",
+                "queued while sending 3"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flush_keeps_the_events_when_it_fails() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let queue = Telemetry::new(temp.path().join("telemetry.json"));
+        queue.append(&command_event("deployment list")).unwrap();
+
+        let exporter = FakeExporter {
+            failures: FLUSH_ATTEMPTS,
+            attempts: AtomicU32::new(0),
+            queue: queue.clone(),
+        };
+        let result = queue.flush_with(&exporter, Duration::from_millis(1)).await;
+
+        assert!(matches!(result, Err(TelemetryError::ExportError(_))));
+        assert_eq!(exporter.attempts.load(Ordering::SeqCst), FLUSH_ATTEMPTS);
+        assert_eq!(queue.events().unwrap().len(), 4);
+    }
+
+    // the release builds have an API key
+    #[cfg(not(feature = "prod"))]
+    #[tokio::test]
+    async fn test_flush_keeps_the_events_when_the_exporter_is_not_configured() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let queue = Telemetry::new(temp.path().join("telemetry.json"));
+        queue.append(&command_event("deployment list")).unwrap();
+
+        let exporter = crate::HoneycombExporter::new(crate::ExporterConfig::default());
+        let result = queue.flush_with(&exporter, Duration::from_millis(1)).await;
+
+        assert!(matches!(result, Err(TelemetryError::ExporterNotConfigured)));
+        assert_eq!(queue.events().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_one_flush_at_a_time() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let queue = Telemetry::new(temp.path().join("telemetry.json"));
+        queue.append(&command_event("deployment list")).unwrap();
+
+        let _flush_lock = queue.try_lock_flush().unwrap();
+        let exporter = FakeExporter {
+            failures: 0,
+            attempts: AtomicU32::new(0),
+            queue: queue.clone(),
+        };
+        let result = queue.flush_with(&exporter, Duration::from_millis(1)).await;
+
+        assert!(matches!(result, Err(TelemetryError::FlushInProgress)));
+        assert_eq!(exporter.attempts.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_clear() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let queue = Telemetry::new(temp.path().join("telemetry.json"));
+        queue.append(&command_event("deployment list")).unwrap();
+        queue.append(&command_event("config list")).unwrap();
+
+        assert_eq!(queue.clear().unwrap(), 2);
+        assert!(queue.events().unwrap().is_empty());
+    }
+}
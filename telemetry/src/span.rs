@@ -0,0 +1,62 @@
+//! The trace context of the events, so that the API calls made by a command are
+//! exported as the children of the command's span.
+
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SpanContext {
+    pub trace_id: String,
+    pub span_id: String,
+}
+
+// a wukong process runs a single command, so the current command is global
+static COMMAND_SPAN: Mutex<Option<SpanContext>> = Mutex::new(None);
+
+/// Start the span of a command, the parent of the API spans recorded after it.
+pub(crate) fn start_command_span() -> SpanContext {
+    let span = SpanContext {
+        trace_id: new_trace_id(),
+        span_id: new_span_id(),
+    };
+    *COMMAND_SPAN.lock().unwrap_or_else(|err| err.into_inner()) = Some(span.clone());
+
+    span
+}
+
+/// The span of the command being run, if any.
+pub(crate) fn command_span() -> Option<SpanContext> {
+    COMMAND_SPAN
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+/// A random 16 bytes trace ID, hex encoded.
+pub(crate) fn new_trace_id() -> String {
+    format!(
+        "{:016x}{:016x}",
+        rand::random::<u64>(),
+        rand::random::<u64>()
+    )
+}
+
+/// A random 8 bytes span ID, hex encoded.
+pub(crate) fn new_span_id() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_random_hex() {
+        let trace_id = new_trace_id();
+        let span_id = new_span_id();
+
+        assert_eq!(trace_id.len(), 32);
+        assert_eq!(span_id.len(), 16);
+        assert!(trace_id.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(new_span_id(), span_id);
+    }
+}